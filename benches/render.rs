@@ -0,0 +1,85 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+
+use metro::{Event, TrackId};
+
+/// A single track with `count` stations.
+fn deep_track(count: usize) -> Vec<Event<'static>> {
+    (0..count)
+        .map(|i| Event::station(0, format!("Station {}", i)))
+        .collect()
+}
+
+/// `count` tracks split from the default track, each with a station,
+/// which are then joined back one at a time.
+fn wide_fan(count: usize) -> Vec<Event<'static>> {
+    let mut events = Vec::new();
+
+    for track_id in (1..=count).map(TrackId) {
+        events.push(Event::SplitTrack(TrackId(0), track_id));
+        events.push(Event::station(track_id, format!("Station {}", track_id)));
+    }
+
+    for track_id in (1..=count).rev().map(TrackId) {
+        events.push(Event::JoinTrack(track_id, TrackId(0)));
+    }
+
+    events
+}
+
+/// The graph of the README, repeated `count` times.
+fn readme(count: usize) -> Vec<Event<'static>> {
+    let graph = [
+        Event::station(0, "Station 1"),
+        Event::station(0, "Station 2"),
+        Event::station(0, "Station 3"),
+        Event::SplitTrack(TrackId(0), TrackId(1)),
+        Event::station(1, "Station 4"),
+        Event::SplitTrack(TrackId(1), TrackId(2)),
+        Event::station(1, "Station 5"),
+        Event::station(2, "Station 6"),
+        Event::station(0, "Station 7"),
+        Event::station(1, "Station 8"),
+        Event::station(2, "Station 9"),
+        Event::SplitTrack(TrackId(2), TrackId(3)),
+        Event::SplitTrack(TrackId(3), TrackId(4)),
+        Event::station(5, "Station 10 (Detached)"),
+        Event::JoinTrack(TrackId(4), TrackId(0)),
+        Event::station(3, "Station 11"),
+        Event::StopTrack(TrackId(1)),
+        Event::station(0, "Station 12"),
+        Event::station(2, "Station 13"),
+        Event::station(3, "Station 14"),
+        Event::JoinTrack(TrackId(3), TrackId(0)),
+        Event::station(2, "Station 15"),
+        Event::StopTrack(TrackId(2)),
+        Event::station(0, "Station 16"),
+    ];
+
+    graph
+        .iter()
+        .cycle()
+        .take(graph.len() * count)
+        .cloned()
+        .collect()
+}
+
+fn to_string(c: &mut Criterion) {
+    let mut group = c.benchmark_group("to_string");
+
+    let graphs = [
+        ("deep_track", deep_track(10_000)),
+        ("wide_fan", wide_fan(50)),
+        ("readme", readme(100)),
+    ];
+
+    for (name, events) in graphs.iter() {
+        group.bench_function(*name, |b| {
+            b.iter(|| metro::to_string(black_box(events)).unwrap())
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, to_string);
+criterion_main!(benches);
@@ -0,0 +1,86 @@
+//! Counts the allocations made while rendering the README graph
+//! repeated 10,000 times, e.g. for comparing changes to the renderer.
+//!
+//! ```text
+//! cargo run --release --example allocations
+//! ```
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::io;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Instant;
+
+use metro::{Event, TrackId};
+
+struct CountingAllocator;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+const REPEAT: usize = 10_000;
+
+fn main() {
+    let graph = [
+        Event::station(0, "Station 1"),
+        Event::station(0, "Station 2"),
+        Event::station(0, "Station 3"),
+        Event::SplitTrack(TrackId(0), TrackId(1)),
+        Event::station(1, "Station 4"),
+        Event::SplitTrack(TrackId(1), TrackId(2)),
+        Event::station(1, "Station 5"),
+        Event::station(2, "Station 6"),
+        Event::station(0, "Station 7"),
+        Event::station(1, "Station 8"),
+        Event::station(2, "Station 9"),
+        Event::SplitTrack(TrackId(2), TrackId(3)),
+        Event::SplitTrack(TrackId(3), TrackId(4)),
+        Event::station(5, "Station 10 (Detached)"),
+        Event::JoinTrack(TrackId(4), TrackId(0)),
+        Event::station(3, "Station 11"),
+        Event::StopTrack(TrackId(1)),
+        Event::station(0, "Station 12"),
+        Event::station(2, "Station 13"),
+        Event::station(3, "Station 14"),
+        Event::JoinTrack(TrackId(3), TrackId(0)),
+        Event::station(2, "Station 15"),
+        Event::StopTrack(TrackId(2)),
+        Event::station(0, "Station 16"),
+    ];
+
+    let events = graph
+        .iter()
+        .cycle()
+        .take(graph.len() * REPEAT)
+        .cloned()
+        .collect::<Vec<_>>();
+
+    let before = ALLOCATIONS.load(Ordering::Relaxed);
+    let start = Instant::now();
+
+    metro::to_writer(io::sink(), &events).unwrap();
+
+    let elapsed = start.elapsed();
+    let allocations = ALLOCATIONS.load(Ordering::Relaxed) - before;
+
+    println!("events:      {}", events.len());
+    println!("allocations: {}", allocations);
+    println!("elapsed:     {:?}", elapsed);
+}
@@ -1,6 +1,16 @@
 use std::borrow::Cow;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fmt;
 use std::io::{self, Write};
 use std::iter;
+use std::mem;
+use std::ops::Range;
+use std::slice;
+use std::str;
+
+use crate::options::{Direction, LineEnding, Options, UnknownTrackPolicy};
+use crate::track_id::TrackId;
+use crate::width::{char_width, text_width};
 
 /// `Event`s are produced automatically by using [`Metro`],
 /// but can also be created and used manually.
@@ -13,32 +23,32 @@ use std::iter;
 /// # Example
 ///
 /// ```no_run
-/// use metro::Event;
+/// use metro::{Event, TrackId};
 ///
 /// let events = [
 ///     Event::station(0, "Station 1"),
 ///     Event::station(0, "Station 2"),
 ///     Event::station(0, "Station 3"),
-///     Event::SplitTrack(0, 1),
+///     Event::SplitTrack(TrackId(0), TrackId(1)),
 ///     Event::station(1, "Station 4"),
-///     Event::SplitTrack(1, 2),
+///     Event::SplitTrack(TrackId(1), TrackId(2)),
 ///     Event::station(1, "Station 5"),
 ///     Event::station(2, "Station 6"),
 ///     Event::station(0, "Station 7"),
 ///     Event::station(1, "Station 8"),
 ///     Event::station(2, "Station 9"),
-///     Event::SplitTrack(2, 3),
-///     Event::SplitTrack(3, 4),
+///     Event::SplitTrack(TrackId(2), TrackId(3)),
+///     Event::SplitTrack(TrackId(3), TrackId(4)),
 ///     Event::station(5, "Station 10 (Detached)"),
-///     Event::JoinTrack(4, 0),
+///     Event::JoinTrack(TrackId(4), TrackId(0)),
 ///     Event::station(3, "Station 11"),
-///     Event::StopTrack(1),
+///     Event::StopTrack(TrackId(1)),
 ///     Event::station(0, "Station 12"),
 ///     Event::station(2, "Station 13"),
 ///     Event::station(3, "Station 14"),
-///     Event::JoinTrack(3, 0),
+///     Event::JoinTrack(TrackId(3), TrackId(0)),
 ///     Event::station(2, "Station 15"),
-///     Event::StopTrack(2),
+///     Event::StopTrack(TrackId(2)),
 ///     Event::station(0, "Station 16"),
 /// ];
 ///
@@ -78,7 +88,7 @@ use std::iter;
 /// | "
 /// * Station 16
 /// ```
-#[derive(Clone, Debug)]
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
 pub enum Event<'a> {
     /// `StartTrack(track_id)`
     ///
@@ -88,13 +98,13 @@ pub enum Event<'a> {
     ///
     /// ## Output Example
     ///
-    /// Given 3 tracks `0, 1, 2` then `StartTrack(4)` would render as:
+    /// Given 3 tracks `0, 1, 2` then `StartTrack(TrackId(4))` would render as:
     ///
     /// ```text
     /// | | |
     /// | | | |
     /// ```
-    StartTrack(usize),
+    StartTrack(TrackId),
 
     /// `StartTracks(track_ids)`
     ///
@@ -105,13 +115,32 @@ pub enum Event<'a> {
     ///
     /// ## Output Example
     ///
-    /// Given 3 tracks `0, 1, 2` then `StartTracks(&[4, 5])` would render as:
+    /// Given 3 tracks `0, 1, 2` then `StartTracks(&[TrackId(4), TrackId(5)])` would render as:
     ///
     /// ```text
     /// | | |
     /// | | | | |
     /// ```
-    StartTracks(&'a [usize]),
+    StartTracks(&'a [TrackId]),
+
+    /// `StartTrackAt(track_id, column_index)`
+    ///
+    /// - If `track_id` already exists, then this event does nothing.
+    /// - If `column_index` is out of range, then the track is added rightmost.
+    ///
+    /// The new `track_id` is inserted at `column_index`, and the rails
+    /// at and after `column_index` are pushed to the right.
+    ///
+    /// ## Output Example
+    ///
+    /// Given 3 tracks `0, 1, 2` then `StartTrackAt(TrackId(4), 1)` would render as:
+    ///
+    /// ```text
+    /// | | |
+    /// |  \ \
+    /// | | | |
+    /// ```
+    StartTrackAt(TrackId, usize),
 
     /// `StopTrack(track_id)`
     ///
@@ -121,7 +150,7 @@ pub enum Event<'a> {
     ///
     /// ## Output Example
     ///
-    /// Given 3 tracks `0, 1, 2` then `StopTrack(1)` would render as:
+    /// Given 3 tracks `0, 1, 2` then `StopTrack(TrackId(1))` would render as:
     ///
     /// ```text
     /// | | |
@@ -129,16 +158,61 @@ pub enum Event<'a> {
     /// |  /
     /// | |
     /// ```
-    StopTrack(usize),
+    StopTrack(TrackId),
+
+    /// `StopTracks(track_ids)`
+    ///
+    /// - If a `track_id` from `track_ids` does not exist, then it is ignored.
+    /// - If all `track_ids` do not exist, then this event does nothing.
+    ///
+    /// All rails to the right of the stopped tracks, are pulled to the left.
+    ///
+    /// ## Output Example
+    ///
+    /// Given 5 tracks `0, 1, 2, 3, 4` then `StopTracks(&[TrackId(1), TrackId(3)])` would render as:
+    ///
+    /// ```text
+    /// | | | | |
+    /// | " | " |
+    /// |  / __/
+    /// | | |
+    /// ```
+    StopTracks(&'a [TrackId]),
+
+    /// `StopTrackLabeled(track_id, label)`
+    ///
+    /// - If `track_id` does not exist, then this event does nothing.
+    ///
+    /// Like [`StopTrack`], but `label` is rendered on the same row as
+    /// the end of the track, e.g. to note why the track ended.
+    /// If `label` has multiple lines, then the remaining lines are
+    /// rendered after the rails have been pulled to the left.
+    ///
+    /// [`StopTrack`]: enum.Event.html#variant.StopTrack
+    ///
+    /// ## Output Example
+    ///
+    /// Given 3 tracks `0, 1, 2` then `StopTrackLabeled(TrackId(1), "Deleted")` would render as:
+    ///
+    /// ```text
+    /// | | |
+    /// | " | Deleted
+    /// |  /
+    /// | |
+    /// ```
+    StopTrackLabeled(TrackId, Cow<'a, str>),
 
     /// `Station(track_id, text)`
     ///
     /// - If the `track_id` does not exist, then `text` is still
-    /// rendered, just not tied to any track.
+    ///   rendered, just not tied to any track. Use [`DetachedStation`]
+    ///   for stations which are never tied to any track.
+    ///
+    /// [`DetachedStation`]: enum.Event.html#variant.DetachedStation
     ///
     /// ## Output Example
     ///
-    /// Given 3 tracks `0, 1, 2` then `Station(1, "Hello World")` would render as:
+    /// Given 3 tracks `0, 1, 2` then `Station(TrackId(1), "Hello World")` would render as:
     ///
     /// ```text
     /// | | |
@@ -147,7 +221,7 @@ pub enum Event<'a> {
     /// ```
     ///
     /// Text with multiple lines is also allowed.
-    /// Given 3 tracks `0, 1, 2` then `Station(1, "Hello\nWorld")` would render as:
+    /// Given 3 tracks `0, 1, 2` then `Station(TrackId(1), "Hello\nWorld")` would render as:
     ///
     /// ```text
     /// | | |
@@ -157,14 +231,119 @@ pub enum Event<'a> {
     /// ```
     ///
     /// If the `track_id` does not exist, then no rail is highlighted.
-    /// Thus `Station(10, "Hello World")` would render as:
+    /// Thus `Station(TrackId(10), "Hello World")` would render as:
+    ///
+    /// ```text
+    /// | | |
+    /// | | | Hello World
+    /// | | |
+    /// ```
+    ///
+    /// *[See `Options::unknown_track_policy` for alternatives.][`Options::unknown_track_policy`]*
+    ///
+    /// [`Options::unknown_track_policy`]: struct.Options.html#structfield.unknown_track_policy
+    ///
+    /// Lines may end with either `\n` or `\r\n`. When rendered, tabs
+    /// in `text` are expanded to spaces, and other control characters
+    /// are escaped, e.g. `"\x1b"` renders as `\u{1b}`. The same
+    /// applies to all other text, e.g. of an [`Annotation`].
+    ///
+    /// [`Annotation`]: enum.Event.html#variant.Annotation
+    Station(TrackId, Cow<'a, str>),
+
+    /// `StationMarked(track_id, text, marker)`
+    ///
+    /// The same as [`Station`], except the station is rendered using
+    /// `marker` instead of `*`, e.g. to highlight the current station.
+    ///
+    /// The `marker` should be a single column wide, such that the
+    /// rails remain aligned with the surrounding rows.
+    ///
+    /// [`Station`]: enum.Event.html#variant.Station
+    ///
+    /// ## Output Example
+    ///
+    /// Given 3 tracks `0, 1, 2` then `StationMarked(TrackId(1), "Hello World", '@')` would render as:
+    ///
+    /// ```text
+    /// | | |
+    /// | @ | Hello World
+    /// | | |
+    /// ```
+    StationMarked(TrackId, Cow<'a, str>, char),
+
+    /// `StationMulti(track_ids, text)`
+    ///
+    /// The same as [`Station`], except the station is rendered on the
+    /// rail of every track in `track_ids` within a single row, e.g. when
+    /// multiple tracks share the same station. The `text` is rendered once.
+    ///
+    /// - If a `track_id` from `track_ids` does not exist, then it is ignored.
+    ///
+    /// [`Station`]: enum.Event.html#variant.Station
+    ///
+    /// ## Output Example
+    ///
+    /// Given 3 tracks `0, 1, 2` then `StationMulti(&[TrackId(0), TrackId(2)], "Hello World")` would render as:
+    ///
+    /// ```text
+    /// | | |
+    /// * | * Hello World
+    /// | | |
+    /// ```
+    StationMulti(&'a [TrackId], Cow<'a, str>),
+
+    /// `DetachedStation(text)`
+    ///
+    /// Produces a station that is not tied to any track, e.g. a note added
+    /// with [`Metro::add_station`]. It renders the same as a [`Station`]
+    /// whose track does not exist, i.e. no rail is highlighted.
+    ///
+    /// Unlike a [`Station`], it has no `track_id`. Thereby it is never
+    /// placed on a track, regardless of which track ids are in use.
+    ///
+    /// [`Station`]: enum.Event.html#variant.Station
+    /// [`Metro::add_station`]: struct.Metro.html#method.add_station
+    ///
+    /// ## Output Example
+    ///
+    /// Given 3 tracks `0, 1, 2` then `DetachedStation("Hello World")` would render as:
     ///
     /// ```text
     /// | | |
     /// | | | Hello World
     /// | | |
     /// ```
-    Station(usize, Cow<'a, str>),
+    DetachedStation(Cow<'a, str>),
+
+    /// `Annotation(text)`
+    ///
+    /// Produces a row of rails followed by the `text`, e.g. for notes
+    /// or section dividers. Unlike [`Station`], no rail is highlighted,
+    /// as an annotation is not tied to any track.
+    ///
+    /// [`Station`]: enum.Event.html#variant.Station
+    ///
+    /// ## Output Example
+    ///
+    /// Given 3 tracks `0, 1, 2` then `Annotation("Hello World")` would render as:
+    ///
+    /// ```text
+    /// | | |
+    /// | | | Hello World
+    /// | | |
+    /// ```
+    ///
+    /// Text with multiple lines is also allowed.
+    /// Given 3 tracks `0, 1, 2` then `Annotation("Hello\nWorld")` would render as:
+    ///
+    /// ```text
+    /// | | |
+    /// | | | Hello
+    /// | | | World
+    /// | | |
+    /// ```
+    Annotation(Cow<'a, str>),
 
     /// `SplitTrack(from_track_id, new_track_id)`
     ///
@@ -173,19 +352,131 @@ pub enum Event<'a> {
     /// right to make space for the new track.
     ///
     /// - If `from_track_id` does not exist, then this event is the
-    /// same as `StartTrack(new_track_id)`.
+    ///   same as `StartTrack(new_track_id)`.
+    /// - If `new_track_id` already exists, then this event does nothing.
+    ///
+    /// ## Output Example
+    ///
+    /// Given 3 tracks `0, 1, 2` then `SplitTrack(TrackId(1), TrackId(4))` would render as:
+    ///
+    /// ```text
+    /// | | |
+    /// | |\ \
+    /// | | | |
+    /// ```
+    SplitTrack(TrackId, TrackId),
+
+    /// `SplitTrackAt(from_track_id, new_track_id, neighbor_track_id)`
+    ///
+    /// Creates a new track diverging from `from_track_id`, which is placed
+    /// immediately to the right of `neighbor_track_id`. All rails to the
+    /// right of `neighbor_track_id`, are pushed to the right to make space
+    /// for the new track.
+    ///
+    /// - If `neighbor_track_id` does not exist, then this event is the
+    ///   same as `SplitTrack(from_track_id, new_track_id)`.
+    /// - If `from_track_id` does not exist, then the new track is started
+    ///   to the right of `neighbor_track_id`.
     /// - If `new_track_id` already exists, then this event does nothing.
     ///
+    /// [`SplitTrack`]: enum.Event.html#variant.SplitTrack
+    ///
+    /// ## Output Example
+    ///
+    /// Given 4 tracks `0, 1, 2, 3` then `SplitTrackAt(TrackId(0), TrackId(4), TrackId(2))` would render as:
+    ///
+    /// ```text
+    /// | | | |
+    /// |\|_|_ \
+    /// | | | | |
+    /// ```
+    ///
+    /// If `neighbor_track_id` is to the left of `from_track_id`, then the new
+    /// track diverges like [`SplitTrack`], after which it moves into place.
+    ///
+    /// Given 4 tracks `0, 1, 2, 3` then `SplitTrackAt(TrackId(3), TrackId(4), TrackId(0))` would render as:
+    ///
+    /// ```text
+    /// | | | |
+    /// | | | |\
+    /// | |_|_|/
+    /// | |\ \ \
+    /// | | | | |
+    /// ```
+    SplitTrackAt(TrackId, TrackId, TrackId),
+
+    /// `SplitTrackN(from_track_id, new_track_ids)`
+    ///
+    /// Creates multiple new tracks diverging from `from_track_id` within
+    /// a single row, e.g. for an octopus-style fan of branches. The new
+    /// tracks are placed immediately to the right of `from_track_id`, in
+    /// the order of `new_track_ids`. All rails to the right of
+    /// `from_track_id`, are pushed to the right to make space for the
+    /// new tracks.
+    ///
+    /// - If `from_track_id` does not exist, then this event is the
+    ///   same as `StartTracks(new_track_ids)`.
+    /// - If a `new_track_id` already exists, or is repeated,
+    ///   then it is skipped.
+    ///
+    /// With a single `new_track_id` this is the same as [`SplitTrack`].
+    ///
+    /// *[See `Event::split_track_n` for creating the event from a `&[TrackId]`.][`Event::split_track_n`]*
+    ///
+    /// [`SplitTrack`]: enum.Event.html#variant.SplitTrack
+    /// [`Event::split_track_n`]: enum.Event.html#method.split_track_n
+    ///
+    /// ## Output Example
+    ///
+    /// Given 1 track `0` then `SplitTrackN(TrackId(0), [1, 2, 3])` would render as:
+    ///
+    /// ```text
+    /// |
+    /// |\_\_\
+    /// | | | |
+    /// ```
+    ///
+    /// The rails to the right of `from_track_id` are first pushed out of the way,
+    /// such that the new tracks can diverge within a single row.
+    ///
+    /// Given 2 tracks `0, 1` then `SplitTrackN(TrackId(0), [2, 3, 4])` would render as:
+    ///
+    /// ```text
+    /// | |
+    /// |  \
+    /// |    \
+    /// |\_\_\ \
+    /// | | | | |
+    /// ```
+    SplitTrackN(TrackId, Cow<'a, [TrackId]>),
+
+    /// `SplitStation(from_track_id, new_track_id, text)`
+    ///
+    /// The same as [`SplitTrack`] immediately followed by a [`Station`]
+    /// on `new_track_id`, such that no other event can end up between
+    /// the split and the first station of the new track.
+    ///
+    /// - If `from_track_id` does not exist, then the new track is started,
+    ///   like [`SplitTrack`].
+    /// - If `new_track_id` already exists, then only the station is rendered.
+    ///
+    /// *[See `Event::split_station` for more information.][`Event::split_station`]*
+    ///
+    /// [`SplitTrack`]: enum.Event.html#variant.SplitTrack
+    /// [`Station`]: enum.Event.html#variant.Station
+    /// [`Event::split_station`]: enum.Event.html#method.split_station
+    ///
     /// ## Output Example
     ///
-    /// Given 3 tracks `0, 1, 2` then `SplitTrack(1, 4)` would render as:
+    /// Given 3 tracks `0, 1, 2` then `SplitStation(TrackId(1), TrackId(3), "Hello World")` would render as:
     ///
     /// ```text
     /// | | |
     /// | |\ \
+    /// | | * | Hello World
     /// | | | |
     /// ```
-    SplitTrack(usize, usize),
+    SplitStation(TrackId, TrackId, Cow<'a, str>),
 
     /// `JoinTrack(from_track_id, to_track_id)`
     ///
@@ -196,14 +487,17 @@ pub enum Event<'a> {
     ///
     /// - If `from_track_id` does not exist, then this event does nothing.
     /// - If `to_track_id` does not exist, then it turns into `StopTrack(from_track_id)`.
+    ///   *[See `JoinTrackOrRename` to continue the track as `to_track_id` instead.][`JoinTrackOrRename`]*
     /// - If `from_track_id` and `to_track_id` are the same, then it turns into `StopTrack(from_track_id)`
     ///
     /// The track ID (`from_track_id`) can be reused for
     /// a new track after this event.
     ///
+    /// [`JoinTrackOrRename`]: enum.Event.html#variant.JoinTrackOrRename
+    ///
     /// ## Output Example
     ///
-    /// Given 3 tracks `0, 1, 2` then `JoinTrack(1, 0)` would render as:
+    /// Given 3 tracks `0, 1, 2` then `JoinTrack(TrackId(1), TrackId(0))` would render as:
     ///
     /// ```text
     /// | | |
@@ -211,7 +505,7 @@ pub enum Event<'a> {
     /// | |
     /// ```
     ///
-    /// Given 6 tracks `0, 1, 2, 3, 4, 5` then `JoinTrack(4, 0)` would render as:
+    /// Given 6 tracks `0, 1, 2, 3, 4, 5` then `JoinTrack(TrackId(4), TrackId(0))` would render as:
     ///
     /// ```text
     /// | | | | | |
@@ -219,7 +513,213 @@ pub enum Event<'a> {
     /// |/| | | |
     /// | | | | |
     /// ```
-    JoinTrack(usize, usize),
+    JoinTrack(TrackId, TrackId),
+
+    /// `JoinTrackToward(from_track_id, to_track_id)`
+    ///
+    /// Joins `from_track_id` and `to_track_id` like [`JoinTrack`],
+    /// except the rail of `from_track_id` is always joined towards
+    /// the rail of `to_track_id`, even if it is to the right. Thereby
+    /// `to_track_id` keeps its column, relative to the other rails.
+    ///
+    /// If `to_track_id` is to the left of `from_track_id`,
+    /// then this renders the same as [`JoinTrack`].
+    ///
+    /// [`JoinTrack`]: enum.Event.html#variant.JoinTrack
+    ///
+    /// ## Output Example
+    ///
+    /// Given 3 tracks `0, 1, 2` then `JoinTrackToward(TrackId(0), TrackId(2))` would render as:
+    ///
+    /// ```text
+    /// | | |
+    ///  \| |
+    ///   |\|
+    ///  / /
+    /// | |
+    /// ```
+    JoinTrackToward(TrackId, TrackId),
+
+    /// `JoinTrackKeepSource(from_track_id, to_track_id)`
+    ///
+    /// Joins `from_track_id` and `to_track_id` like [`JoinTrack`],
+    /// except `to_track_id` is removed instead of `from_track_id`,
+    /// such that the remaining rail keeps the `from_track_id`.
+    ///
+    /// As [`JoinTrack`] always joins towards the leftmost rail, this
+    /// renders the same merge, regardless of which track is removed.
+    /// Thereby this is the same as `JoinTrack(to_track_id, from_track_id)`,
+    /// and the remaining rail is the leftmost of the two, carrying
+    /// the `from_track_id`.
+    ///
+    /// - If `to_track_id` does not exist, then this event does nothing.
+    /// - If `from_track_id` does not exist, then it turns into `StopTrack(to_track_id)`.
+    /// - If `from_track_id` and `to_track_id` are the same, then it turns into `StopTrack(to_track_id)`
+    ///
+    /// The track ID (`to_track_id`) can be reused for
+    /// a new track after this event.
+    ///
+    /// [`JoinTrack`]: enum.Event.html#variant.JoinTrack
+    ///
+    /// ## Output Example
+    ///
+    /// Given 3 tracks `0, 1, 2` then `JoinTrackKeepSource(TrackId(2), TrackId(0))` would render
+    /// as follows, after which the tracks are `2, 1`:
+    ///
+    /// ```text
+    /// | | |
+    /// | |/
+    /// |/|
+    /// | |
+    /// ```
+    JoinTrackKeepSource(TrackId, TrackId),
+
+    /// `JoinTrackOrRename(from_track_id, to_track_id)`
+    ///
+    /// Joins `from_track_id` and `to_track_id` like [`JoinTrack`], except
+    /// if `to_track_id` does not exist. Then instead of turning into
+    /// `StopTrack(from_track_id)`, the track continues as `to_track_id`.
+    /// The rail of `to_track_id` is split from the rail of `from_track_id`,
+    /// after which `from_track_id` is stopped. Thereby the track is renamed
+    /// like [`RenameTrack`], but with a visible connector, and any label
+    /// of `from_track_id` is kept.
+    ///
+    /// - If `from_track_id` does not exist, then this event does nothing.
+    /// - If `to_track_id` exists, then it turns into `JoinTrack(from_track_id, to_track_id)`.
+    /// - If `from_track_id` and `to_track_id` are the same, then it turns into `StopTrack(from_track_id)`
+    ///
+    /// The track ID (`from_track_id`) can be reused for
+    /// a new track after this event.
+    ///
+    /// [`JoinTrack`]: enum.Event.html#variant.JoinTrack
+    /// [`RenameTrack`]: enum.Event.html#variant.RenameTrack
+    ///
+    /// ## Output Example
+    ///
+    /// Given 3 tracks `0, 1, 2` then `JoinTrackOrRename(TrackId(1), TrackId(5))` would render
+    /// as follows, after which the tracks are `0, 5, 2`:
+    ///
+    /// ```text
+    /// | | |
+    /// | |\ \
+    /// | " | |
+    /// |  / /
+    /// | | |
+    /// ```
+    JoinTrackOrRename(TrackId, TrackId),
+
+    /// `JoinTracks(from_track_ids, to_track_id)`
+    ///
+    /// Joins all `from_track_ids` into `to_track_id`, resulting
+    /// in the `from_track_ids` being removed.
+    ///
+    /// Unlike [`JoinTrack`], the rails are joined towards the
+    /// `to_track_id` rail, instead of the leftmost rail. Rails
+    /// joining from the same side are joined in a single row.
+    ///
+    /// - If a `track_id` from `from_track_ids` does not exist, then it is ignored.
+    /// - If `to_track_id` does not exist, then it turns into `StopTracks(from_track_ids)`.
+    /// - If `to_track_id` is one of the `from_track_ids`, then it turns into
+    ///   `StopTracks` of the other `from_track_ids`.
+    ///
+    /// The track IDs (`from_track_ids`) can be reused for
+    /// new tracks after this event.
+    ///
+    /// [`JoinTrack`]: enum.Event.html#variant.JoinTrack
+    ///
+    /// ## Output Example
+    ///
+    /// Given 4 tracks `0, 1, 2, 3` then `JoinTracks(&[TrackId(1), TrackId(2), TrackId(3)], TrackId(0))` would render as:
+    ///
+    /// ```text
+    /// | | | |
+    /// |/_/_/
+    /// |
+    /// ```
+    ///
+    /// Given 5 tracks `0, 1, 2, 3, 4` then `JoinTracks(&[TrackId(0), TrackId(4)], TrackId(2))` would render as:
+    ///
+    /// ```text
+    /// | | | | |
+    ///  \| | |/
+    ///   |\|/|
+    ///  / / /
+    /// | | |
+    /// ```
+    JoinTracks(&'a [TrackId], TrackId),
+
+    /// `Link(from_track_id, to_track_id)`
+    ///
+    /// Produces a row connecting the rails of `from_track_id` and
+    /// `to_track_id` with `.`, e.g. to show a relationship between
+    /// the tracks. Both tracks remain afterwards.
+    ///
+    /// - If `from_track_id` or `to_track_id` does not exist,
+    ///   then this event does nothing.
+    /// - If `from_track_id` and `to_track_id` are the same,
+    ///   then this event does nothing.
+    ///
+    /// ## Output Example
+    ///
+    /// Given 4 tracks `0, 1, 2, 3` then `Link(TrackId(2), TrackId(0))` would render as:
+    ///
+    /// ```text
+    /// | | | |
+    /// |.|.| |
+    /// | | | |
+    /// ```
+    Link(TrackId, TrackId),
+
+    /// `RenameTrack(old_track_id, new_track_id)`
+    ///
+    /// Changes the id of the track `old_track_id` to `new_track_id`,
+    /// such that subsequent events target the track using `new_track_id`.
+    /// The rail remains in the same column, and any label is kept.
+    ///
+    /// - If `old_track_id` does not exist, then this event does nothing.
+    /// - If `new_track_id` already exists, then this event does nothing.
+    ///
+    /// `RenameTrack` does not produce any rows.
+    ///
+    /// The track ID (`old_track_id`) can be reused for
+    /// new tracks after this event.
+    RenameTrack(TrackId, TrackId),
+
+    /// `LabelTrack(track_id, label)`
+    ///
+    /// Associates a `label` with `track_id`, which is rendered by [`Legend`].
+    /// The label replaces any previous label of `track_id`.
+    ///
+    /// - If `track_id` does not exist, then this event does nothing.
+    /// - If the track is stopped, then its label is removed.
+    ///
+    /// `LabelTrack` does not produce any rows.
+    ///
+    /// [`Legend`]: enum.Event.html#variant.Legend
+    LabelTrack(TrackId, Cow<'a, str>),
+
+    /// `Legend` produces a row for each track with a label, from left to right.
+    /// Each label is placed to the right of the rail of its track, such that
+    /// the labels align under the rail columns.
+    ///
+    /// Tracks without a label are skipped, and if no tracks have
+    /// a label, then this event does nothing.
+    ///
+    /// *[See `LabelTrack`.][`LabelTrack`]*
+    ///
+    /// [`LabelTrack`]: enum.Event.html#variant.LabelTrack
+    ///
+    /// ## Output Example
+    ///
+    /// Given 3 tracks `0, 1, 2` labeled `main`, `release` and `feature/x`
+    /// then `Legend` would render as:
+    ///
+    /// ```text
+    /// main
+    /// | release
+    /// | | feature/x
+    /// ```
+    Legend,
 
     /// `NoEvent` produces one row of rails.
     ///
@@ -234,14 +734,349 @@ pub enum Event<'a> {
 }
 
 impl<'a> Event<'a> {
-    /// *[See `Event::Station` for more information.][`Event::Station`]*
+    /// *[See `Event::StopTrackLabeled` for more information.][`Event::StopTrackLabeled`]*
     ///
-    /// [`Event::Station`]: enum.Event.html#variant.Station
+    /// [`Event::StopTrackLabeled`]: enum.Event.html#variant.StopTrackLabeled
     #[inline]
-    pub fn station<S: Into<Cow<'a, str>>>(track_id: usize, text: S) -> Self {
-        Self::Station(track_id, text.into())
+    pub fn stop_track_labeled<T: Into<TrackId>, S: Into<Cow<'a, str>>>(
+        track_id: T,
+        label: S,
+    ) -> Self {
+        Self::StopTrackLabeled(track_id.into(), label.into())
     }
-}
+
+    /// *[See `Event::Station` for more information.][`Event::Station`]*
+    ///
+    /// [`Event::Station`]: enum.Event.html#variant.Station
+    #[inline]
+    pub fn station<T: Into<TrackId>, S: Into<Cow<'a, str>>>(track_id: T, text: S) -> Self {
+        Self::Station(track_id.into(), text.into())
+    }
+
+    /// Creates a [`Station`] with multiple lines of text, by joining
+    /// `lines` with `\n`.
+    ///
+    /// *[See `Event::Station` for more information.][`Station`]*
+    ///
+    /// [`Station`]: enum.Event.html#variant.Station
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use metro::Event;
+    ///
+    /// let lines = vec!["Hello", "World"];
+    ///
+    /// assert_eq!(
+    ///     Event::station_lines(0, &lines),
+    ///     Event::station(0, "Hello\nWorld"),
+    /// );
+    /// ```
+    pub fn station_lines<T: Into<TrackId>, S: AsRef<str>>(track_id: T, lines: &[S]) -> Self {
+        let mut text = String::new();
+
+        for (i, line) in lines.iter().enumerate() {
+            if i > 0 {
+                text.push('\n');
+            }
+            text.push_str(line.as_ref());
+        }
+
+        Self::Station(track_id.into(), Cow::Owned(text))
+    }
+
+    /// *[See `Event::SplitTrackN` for more information.][`Event::SplitTrackN`]*
+    ///
+    /// [`Event::SplitTrackN`]: enum.Event.html#variant.SplitTrackN
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use metro::{Event, TrackId};
+    ///
+    /// let events = [
+    ///     Event::station(0, "Station 1"),
+    ///     Event::split_track_n(0, &[TrackId(1), TrackId(2), TrackId(3)][..]),
+    ///     Event::station(3, "Station 2"),
+    /// ];
+    ///
+    /// let string = metro::to_string(&events).unwrap();
+    ///
+    /// assert_eq!(string, "* Station 1\n|\\_\\_\\\n| | | * Station 2\n");
+    /// ```
+    #[inline]
+    pub fn split_track_n<T, I>(from_track_id: T, new_track_ids: I) -> Self
+    where
+        T: Into<TrackId>,
+        I: Into<Cow<'a, [TrackId]>>,
+    {
+        Self::SplitTrackN(from_track_id.into(), new_track_ids.into())
+    }
+
+    /// *[See `Event::SplitStation` for more information.][`Event::SplitStation`]*
+    ///
+    /// [`Event::SplitStation`]: enum.Event.html#variant.SplitStation
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use metro::Event;
+    ///
+    /// let events = [
+    ///     Event::station(0, "Station 1"),
+    ///     Event::split_station(0, 1, "Station 2"),
+    ///     Event::station(0, "Station 3"),
+    /// ];
+    ///
+    /// let string = metro::to_string(&events).unwrap();
+    ///
+    /// assert_eq!(string, "* Station 1\n|\\\n| * Station 2\n* | Station 3\n");
+    /// ```
+    #[inline]
+    pub fn split_station<T, U, S>(from_track_id: T, new_track_id: U, text: S) -> Self
+    where
+        T: Into<TrackId>,
+        U: Into<TrackId>,
+        S: Into<Cow<'a, str>>,
+    {
+        Self::SplitStation(from_track_id.into(), new_track_id.into(), text.into())
+    }
+
+    /// *[See `Event::StationMarked` for more information.][`Event::StationMarked`]*
+    ///
+    /// [`Event::StationMarked`]: enum.Event.html#variant.StationMarked
+    #[inline]
+    pub fn station_marked<T: Into<TrackId>, S: Into<Cow<'a, str>>>(
+        track_id: T,
+        text: S,
+        marker: char,
+    ) -> Self {
+        Self::StationMarked(track_id.into(), text.into(), marker)
+    }
+
+    /// *[See `Event::StationMulti` for more information.][`Event::StationMulti`]*
+    ///
+    /// [`Event::StationMulti`]: enum.Event.html#variant.StationMulti
+    #[inline]
+    pub fn station_multi<S: Into<Cow<'a, str>>>(track_ids: &'a [TrackId], text: S) -> Self {
+        Self::StationMulti(track_ids, text.into())
+    }
+
+    /// *[See `Event::DetachedStation` for more information.][`Event::DetachedStation`]*
+    ///
+    /// [`Event::DetachedStation`]: enum.Event.html#variant.DetachedStation
+    #[inline]
+    pub fn detached_station<S: Into<Cow<'a, str>>>(text: S) -> Self {
+        Self::DetachedStation(text.into())
+    }
+
+    /// *[See `Event::Annotation` for more information.][`Event::Annotation`]*
+    ///
+    /// [`Event::Annotation`]: enum.Event.html#variant.Annotation
+    #[inline]
+    pub fn annotation<S: Into<Cow<'a, str>>>(text: S) -> Self {
+        Self::Annotation(text.into())
+    }
+
+    /// *[See `Event::LabelTrack` for more information.][`Event::LabelTrack`]*
+    ///
+    /// [`Event::LabelTrack`]: enum.Event.html#variant.LabelTrack
+    #[inline]
+    pub fn label_track<T: Into<TrackId>, S: Into<Cow<'a, str>>>(track_id: T, label: S) -> Self {
+        Self::LabelTrack(track_id.into(), label.into())
+    }
+
+    /// Returns the [`EventKind`] of the event, i.e. its variant without
+    /// any fields, e.g. for counting the events of each kind.
+    ///
+    /// [`EventKind`]: enum.EventKind.html
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::collections::HashMap;
+    ///
+    /// use metro::{Event, EventKind, TrackId};
+    ///
+    /// let events = [
+    ///     Event::station(0, "Station 1"),
+    ///     Event::SplitTrack(TrackId(0), TrackId(1)),
+    ///     Event::station(1, "Station 2"),
+    /// ];
+    ///
+    /// let mut counts = HashMap::new();
+    /// for event in &events {
+    ///     *counts.entry(event.kind()).or_insert(0) += 1;
+    /// }
+    ///
+    /// assert_eq!(counts[&EventKind::Station], 2);
+    /// assert_eq!(counts[&EventKind::SplitTrack], 1);
+    /// ```
+    pub fn kind(&self) -> EventKind {
+        use Event::*;
+        match self {
+            StartTrack(..) => EventKind::StartTrack,
+            StartTracks(..) => EventKind::StartTracks,
+            StartTrackAt(..) => EventKind::StartTrackAt,
+            StopTrack(..) => EventKind::StopTrack,
+            StopTracks(..) => EventKind::StopTracks,
+            StopTrackLabeled(..) => EventKind::StopTrackLabeled,
+            Station(..) => EventKind::Station,
+            StationMarked(..) => EventKind::StationMarked,
+            StationMulti(..) => EventKind::StationMulti,
+            DetachedStation(..) => EventKind::DetachedStation,
+            Annotation(..) => EventKind::Annotation,
+            SplitTrack(..) => EventKind::SplitTrack,
+            SplitTrackAt(..) => EventKind::SplitTrackAt,
+            SplitTrackN(..) => EventKind::SplitTrackN,
+            SplitStation(..) => EventKind::SplitStation,
+            JoinTrack(..) => EventKind::JoinTrack,
+            JoinTrackToward(..) => EventKind::JoinTrackToward,
+            JoinTrackKeepSource(..) => EventKind::JoinTrackKeepSource,
+            JoinTrackOrRename(..) => EventKind::JoinTrackOrRename,
+            JoinTracks(..) => EventKind::JoinTracks,
+            Link(..) => EventKind::Link,
+            RenameTrack(..) => EventKind::RenameTrack,
+            LabelTrack(..) => EventKind::LabelTrack,
+            Legend => EventKind::Legend,
+            NoEvent => EventKind::NoEvent,
+        }
+    }
+}
+
+/// The kind of an [`Event`], i.e. its variant without any fields.
+///
+/// *[See `Event::kind`.][`Event::kind`]*
+///
+/// [`Event`]: enum.Event.html
+/// [`Event::kind`]: enum.Event.html#method.kind
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum EventKind {
+    /// *[See `Event::StartTrack`.][`Event::StartTrack`]*
+    ///
+    /// [`Event::StartTrack`]: enum.Event.html#variant.StartTrack
+    StartTrack,
+
+    /// *[See `Event::StartTracks`.][`Event::StartTracks`]*
+    ///
+    /// [`Event::StartTracks`]: enum.Event.html#variant.StartTracks
+    StartTracks,
+
+    /// *[See `Event::StartTrackAt`.][`Event::StartTrackAt`]*
+    ///
+    /// [`Event::StartTrackAt`]: enum.Event.html#variant.StartTrackAt
+    StartTrackAt,
+
+    /// *[See `Event::StopTrack`.][`Event::StopTrack`]*
+    ///
+    /// [`Event::StopTrack`]: enum.Event.html#variant.StopTrack
+    StopTrack,
+
+    /// *[See `Event::StopTracks`.][`Event::StopTracks`]*
+    ///
+    /// [`Event::StopTracks`]: enum.Event.html#variant.StopTracks
+    StopTracks,
+
+    /// *[See `Event::StopTrackLabeled`.][`Event::StopTrackLabeled`]*
+    ///
+    /// [`Event::StopTrackLabeled`]: enum.Event.html#variant.StopTrackLabeled
+    StopTrackLabeled,
+
+    /// *[See `Event::Station`.][`Event::Station`]*
+    ///
+    /// [`Event::Station`]: enum.Event.html#variant.Station
+    Station,
+
+    /// *[See `Event::StationMarked`.][`Event::StationMarked`]*
+    ///
+    /// [`Event::StationMarked`]: enum.Event.html#variant.StationMarked
+    StationMarked,
+
+    /// *[See `Event::StationMulti`.][`Event::StationMulti`]*
+    ///
+    /// [`Event::StationMulti`]: enum.Event.html#variant.StationMulti
+    StationMulti,
+
+    /// *[See `Event::DetachedStation`.][`Event::DetachedStation`]*
+    ///
+    /// [`Event::DetachedStation`]: enum.Event.html#variant.DetachedStation
+    DetachedStation,
+
+    /// *[See `Event::Annotation`.][`Event::Annotation`]*
+    ///
+    /// [`Event::Annotation`]: enum.Event.html#variant.Annotation
+    Annotation,
+
+    /// *[See `Event::SplitTrack`.][`Event::SplitTrack`]*
+    ///
+    /// [`Event::SplitTrack`]: enum.Event.html#variant.SplitTrack
+    SplitTrack,
+
+    /// *[See `Event::SplitTrackAt`.][`Event::SplitTrackAt`]*
+    ///
+    /// [`Event::SplitTrackAt`]: enum.Event.html#variant.SplitTrackAt
+    SplitTrackAt,
+
+    /// *[See `Event::SplitTrackN`.][`Event::SplitTrackN`]*
+    ///
+    /// [`Event::SplitTrackN`]: enum.Event.html#variant.SplitTrackN
+    SplitTrackN,
+
+    /// *[See `Event::SplitStation`.][`Event::SplitStation`]*
+    ///
+    /// [`Event::SplitStation`]: enum.Event.html#variant.SplitStation
+    SplitStation,
+
+    /// *[See `Event::JoinTrack`.][`Event::JoinTrack`]*
+    ///
+    /// [`Event::JoinTrack`]: enum.Event.html#variant.JoinTrack
+    JoinTrack,
+
+    /// *[See `Event::JoinTrackToward`.][`Event::JoinTrackToward`]*
+    ///
+    /// [`Event::JoinTrackToward`]: enum.Event.html#variant.JoinTrackToward
+    JoinTrackToward,
+
+    /// *[See `Event::JoinTrackKeepSource`.][`Event::JoinTrackKeepSource`]*
+    ///
+    /// [`Event::JoinTrackKeepSource`]: enum.Event.html#variant.JoinTrackKeepSource
+    JoinTrackKeepSource,
+
+    /// *[See `Event::JoinTrackOrRename`.][`Event::JoinTrackOrRename`]*
+    ///
+    /// [`Event::JoinTrackOrRename`]: enum.Event.html#variant.JoinTrackOrRename
+    JoinTrackOrRename,
+
+    /// *[See `Event::JoinTracks`.][`Event::JoinTracks`]*
+    ///
+    /// [`Event::JoinTracks`]: enum.Event.html#variant.JoinTracks
+    JoinTracks,
+
+    /// *[See `Event::Link`.][`Event::Link`]*
+    ///
+    /// [`Event::Link`]: enum.Event.html#variant.Link
+    Link,
+
+    /// *[See `Event::RenameTrack`.][`Event::RenameTrack`]*
+    ///
+    /// [`Event::RenameTrack`]: enum.Event.html#variant.RenameTrack
+    RenameTrack,
+
+    /// *[See `Event::LabelTrack`.][`Event::LabelTrack`]*
+    ///
+    /// [`Event::LabelTrack`]: enum.Event.html#variant.LabelTrack
+    LabelTrack,
+
+    /// *[See `Event::Legend`.][`Event::Legend`]*
+    ///
+    /// [`Event::Legend`]: enum.Event.html#variant.Legend
+    Legend,
+
+    /// *[See `Event::NoEvent`.][`Event::NoEvent`]*
+    ///
+    /// [`Event::NoEvent`]: enum.Event.html#variant.NoEvent
+    NoEvent,
+}
 
 /// Write `&[`[`Event`]`]` to [`<W: io::Write>`].
 /// Defines a default track with `track_id` of `0`.
@@ -250,620 +1085,5356 @@ impl<'a> Event<'a> {
 ///
 /// *See also [`to_string`] and [`to_vec`].*
 ///
+/// *[See also `to_writer_with` for rendering with `Options`.][`to_writer_with`]*
+///
+/// As `&mut W` also implements `io::Write`, the writer can be passed by
+/// reference, including writers behind a trait object, i.e. `&mut dyn Write`.
+///
 /// [`to_vec`]: fn.to_vec.html
 /// [`to_string`]: fn.to_string.html
+/// [`to_writer_with`]: fn.to_writer_with.html
 ///
 /// [`Event`]: enum.Event.html
 ///
 /// [`Metro::to_writer`]: struct.Metro.html#method.to_writer
 ///
 /// [`<W: io::Write>`]: https://doc.rust-lang.org/stable/std/io/trait.Write.html
-pub fn to_writer<W: Write>(mut writer: W, events: &[Event]) -> io::Result<()> {
-    let mut tracks = vec![0];
+#[inline]
+pub fn to_writer<W: Write>(writer: W, events: &[Event]) -> io::Result<()> {
+    to_writer_with(writer, events, &Options::default())
+}
+
+/// Write `&[`[`Event`]`]` to [`<W: io::Write>`], using [`Options`].
+/// Defines the tracks of [`Options::initial_tracks`], by default
+/// a track with `track_id` of `0`.
+///
+/// *[See also `Metro::to_writer_with`.][`Metro::to_writer_with`]*
+///
+/// *See also [`to_string_with`] and [`to_vec_with`].*
+///
+/// [`to_vec_with`]: fn.to_vec_with.html
+/// [`to_string_with`]: fn.to_string_with.html
+///
+/// [`Event`]: enum.Event.html
+/// [`Options`]: struct.Options.html
+/// [`Options::initial_tracks`]: struct.Options.html#structfield.initial_tracks
+///
+/// [`Metro::to_writer_with`]: struct.Metro.html#method.to_writer_with
+///
+/// [`<W: io::Write>`]: https://doc.rust-lang.org/stable/std/io/trait.Write.html
+pub fn to_writer_with<W: Write>(writer: W, events: &[Event], opts: &Options) -> io::Result<()> {
+    let mut renderer = Renderer::with_options(writer, opts.clone());
 
     for event in events {
-        use Event::*;
-        match event {
-            &StartTrack(track_id) => {
-                if !tracks.contains(&track_id) {
-                    tracks.push(track_id);
+        renderer.push(event)?;
+    }
 
-                    let line = iter::repeat("|")
-                        .take(tracks.len())
-                        .collect::<Vec<_>>()
-                        .join(" ");
+    renderer.finish()?;
 
-                    writeln!(&mut writer, "{}", line)?;
-                }
-            }
+    Ok(())
+}
+
+/// Write `&[`[`Event`]`]` to [`<W: io::Write>`], and return the range
+/// of rows written for each event, as `(event_index, row_range)` pairs.
+/// Defines a default track with `track_id` of `0`.
+///
+/// The rows are the lines written to `writer`, such that an event can be
+/// correlated with its rows while rendering, e.g. for debugging an importer.
+/// Events that produce no rows have an empty range.
+///
+/// *[See also `event_row_ranges` for the ranges without rendering.][`event_row_ranges`]*
+///
+/// [`event_row_ranges`]: fn.event_row_ranges.html
+///
+/// [`Event`]: enum.Event.html
+///
+/// [`<W: io::Write>`]: https://doc.rust-lang.org/stable/std/io/trait.Write.html
+///
+/// # Example
+///
+/// ```
+/// use metro::{Event, TrackId};
+///
+/// let events = [
+///     Event::station(0, "Station 1"),
+///     Event::SplitTrack(TrackId(0), TrackId(1)),
+///     Event::station(1, "Station 2"),
+///     Event::StopTrack(TrackId(5)),
+/// ];
+///
+/// let mut output = Vec::new();
+/// let trace = metro::to_writer_traced(&mut output, &events).unwrap();
+///
+/// assert_eq!(trace, [(0, 0..1), (1, 1..2), (2, 2..3), (3, 3..3)]);
+/// assert_eq!(output, metro::to_vec(&events).unwrap());
+/// ```
+pub fn to_writer_traced<W: Write>(
+    mut writer: W,
+    events: &[Event],
+) -> io::Result<Vec<(usize, Range<usize>)>> {
+    let mut sink = LineCountSink {
+        sink: IoSink(&mut writer),
+        lines: 0,
+    };
+    let mut core = RenderCore::new(Options::default());
+
+    let mut trace = Vec::with_capacity(events.len());
+
+    for (index, event) in events.iter().enumerate() {
+        let start = sink.lines;
+        core.push(&mut sink, event)?;
+        trace.push((index, start..sink.lines));
+    }
 
-            &StartTracks(track_ids) => {
-                let mut render = false;
+    core.finish(&mut sink)?;
 
-                for track_id in track_ids.iter() {
-                    if !tracks.contains(track_id) {
-                        tracks.push(*track_id);
+    Ok(trace)
+}
 
-                        render = true;
-                    }
-                }
+/// Write `&[`[`Event`]`]` to [`<W: fmt::Write>`].
+/// Defines a default track with `track_id` of `0`.
+///
+/// The rendered lines are written directly as `str`s, so unlike
+/// [`to_writer`], no bytes are involved. This is useful for writing
+/// to a [`String`] or a [`fmt::Formatter`], e.g. in a `Display`
+/// implementation.
+///
+/// *[See also `to_fmt_writer_with` for rendering with `Options`.][`to_fmt_writer_with`]*
+///
+/// [`to_writer`]: fn.to_writer.html
+/// [`to_fmt_writer_with`]: fn.to_fmt_writer_with.html
+///
+/// [`Event`]: enum.Event.html
+///
+/// [`<W: fmt::Write>`]: https://doc.rust-lang.org/stable/std/fmt/trait.Write.html
+/// [`String`]: https://doc.rust-lang.org/stable/std/string/struct.String.html
+/// [`fmt::Formatter`]: https://doc.rust-lang.org/stable/std/fmt/struct.Formatter.html
+///
+/// # Example
+///
+/// ```
+/// use std::fmt;
+///
+/// use metro::{Event, TrackId};
+///
+/// struct Graph<'a>(Vec<Event<'a>>);
+///
+/// impl fmt::Display for Graph<'_> {
+///     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+///         metro::to_fmt_writer(f, &self.0)
+///     }
+/// }
+///
+/// let graph = Graph(vec![
+///     Event::station(0, "Station 1"),
+///     Event::SplitTrack(TrackId(0), TrackId(1)),
+///     Event::station(1, "Station 2"),
+/// ]);
+///
+/// assert_eq!(graph.to_string(), "* Station 1\n|\\\n| * Station 2\n");
+/// ```
+#[inline]
+pub fn to_fmt_writer<W: fmt::Write + ?Sized>(writer: &mut W, events: &[Event]) -> fmt::Result {
+    to_fmt_writer_with(writer, events, &Options::default())
+}
 
-                if render {
-                    let line = iter::repeat("|")
-                        .take(tracks.len())
-                        .collect::<Vec<_>>()
-                        .join(" ");
+/// Write `&[`[`Event`]`]` to [`<W: fmt::Write>`], using [`Options`].
+/// Defines the tracks of [`Options::initial_tracks`], by default
+/// a track with `track_id` of `0`.
+///
+/// *[See `to_fmt_writer` for more information.][`to_fmt_writer`]*
+///
+/// [`to_fmt_writer`]: fn.to_fmt_writer.html
+///
+/// [`Event`]: enum.Event.html
+/// [`Options`]: struct.Options.html
+/// [`Options::initial_tracks`]: struct.Options.html#structfield.initial_tracks
+///
+/// [`<W: fmt::Write>`]: https://doc.rust-lang.org/stable/std/fmt/trait.Write.html
+pub fn to_fmt_writer_with<W: fmt::Write + ?Sized>(
+    writer: &mut W,
+    events: &[Event],
+    opts: &Options,
+) -> fmt::Result {
+    let mut sink = FmtSink(writer);
+    let mut core = RenderCore::new(opts.clone());
 
-                    writeln!(&mut writer, "{}", line)?;
-                }
-            }
+    for event in events {
+        core.push(&mut sink, event)?;
+    }
 
-            &StopTrack(track_id) => stop_track(&mut writer, &mut tracks, track_id)?,
+    core.finish(&mut sink)
+}
 
-            Station(track_id, station_name) => {
-                let mut line = tracks
-                    .iter()
-                    .map(|&id| if id == *track_id { "*" } else { "|" })
-                    .collect::<Vec<_>>()
-                    .join(" ");
-
-                for (i, station_name) in station_name.lines().enumerate() {
-                    if i == 1 {
-                        line = iter::repeat("|")
-                            .take(tracks.len())
-                            .collect::<Vec<_>>()
-                            .join(" ");
-                    }
+/// Write only the rows of the events appended to `prev` in `next`,
+/// to [`<W: io::Write>`], e.g. for printing the graph as it grows.
+/// Defines a default track with `track_id` of `0`.
+///
+/// The tracks are reconstructed by replaying `prev` without writing it,
+/// such that the rows continue where the rows of `prev` left off.
+/// It is assumed that `next` starts with `prev`, so only the events
+/// of `next` after the length of `prev` are rendered.
+///
+/// [`<W: io::Write>`]: https://doc.rust-lang.org/stable/std/io/trait.Write.html
+///
+/// # Example
+///
+/// ```
+/// use metro::{Event, TrackId};
+///
+/// let prev = [
+///     Event::station(0, "Station 1"),
+///     Event::SplitTrack(TrackId(0), TrackId(1)),
+/// ];
+///
+/// let next = [
+///     Event::station(0, "Station 1"),
+///     Event::SplitTrack(TrackId(0), TrackId(1)),
+///     Event::station(1, "Station 2"),
+/// ];
+///
+/// let mut delta = Vec::new();
+/// metro::render_delta(&prev, &next, &mut delta).unwrap();
+///
+/// assert_eq!(String::from_utf8(delta).unwrap(), "| * Station 2\n");
+/// ```
+pub fn render_delta<W: Write>(prev: &[Event], next: &[Event], writer: W) -> io::Result<()> {
+    let mut renderer = Renderer::new(writer);
 
-                    writeln!(&mut writer, "{} {}", line, station_name)?;
-                }
-            }
+    for event in prev {
+        renderer.skip(event);
+    }
 
-            &SplitTrack(from_track_id, new_track_id) => {
-                if !tracks.contains(&new_track_id) {
-                    let from_track_index = tracks.iter().position(|&id| id == from_track_id);
-
-                    if let Some(from_track_index) = from_track_index {
-                        let line = (0..tracks.len())
-                            .map(|i| {
-                                use std::cmp::Ordering::*;
-                                match i.cmp(&from_track_index) {
-                                    Greater => "\\",
-                                    Equal => "|\\",
-                                    Less => "|",
-                                }
-                            })
-                            .collect::<Vec<_>>()
-                            .join(" ");
-
-                        writeln!(&mut writer, "{}", line)?;
-
-                        tracks.insert(from_track_index + 1, new_track_id);
-                    } else {
-                        tracks.push(new_track_id);
+    for event in next.get(prev.len()..).unwrap_or_default() {
+        renderer.push(event)?;
+    }
+
+    renderer.finish()?;
 
-                        let line = iter::repeat("|")
-                            .take(tracks.len())
-                            .collect::<Vec<_>>()
-                            .join(" ");
+    Ok(())
+}
+
+/// Write only the rows of the events in `range` of `&[`[`Event`]`]`
+/// to [`String`], e.g. for showing a window of a large graph.
+/// Defines a default track with `track_id` of `0`.
+///
+/// The tracks are reconstructed by replaying the events before `range`
+/// without writing them, such that the rows are identical to the
+/// corresponding rows of [`to_string`]. The bounds of `range` are
+/// clamped to the length of `events`.
+///
+/// *See also [`render_delta`].*
+///
+/// [`to_string`]: fn.to_string.html
+/// [`render_delta`]: fn.render_delta.html
+///
+/// [`Event`]: enum.Event.html
+///
+/// [`String`]: https://doc.rust-lang.org/stable/std/string/struct.String.html
+///
+/// # Example
+///
+/// ```
+/// use metro::{Event, TrackId};
+///
+/// let events = [
+///     Event::station(0, "Station 1"),
+///     Event::SplitTrack(TrackId(0), TrackId(1)),
+///     Event::station(1, "Station 2"),
+///     Event::JoinTrack(TrackId(1), TrackId(0)),
+///     Event::station(0, "Station 3"),
+/// ];
+///
+/// let string = metro::to_string_range(&events, 2..4);
+///
+/// assert_eq!(string, "| * Station 2\n|/\n");
+/// ```
+pub fn to_string_range(events: &[Event], range: Range<usize>) -> String {
+    let end = range.end.min(events.len());
+    let start = range.start.min(end);
+
+    let mut string = String::new();
+    let mut sink = FmtSink(&mut string);
+    let mut core = RenderCore::new(Options::default());
+
+    for event in &events[..start] {
+        core.skip(event);
+    }
+
+    // Writing to a `String` never fails, so it is safe to `unwrap`
+    for event in &events[start..end] {
+        core.push(&mut sink, event).unwrap();
+    }
+    core.finish(&mut sink).unwrap();
+
+    string
+}
+
+/// A streaming renderer, which renders [`Event`]s one at a time.
+/// Defines the tracks of [`Options::initial_tracks`], by default
+/// a track with `track_id` of `0`.
+///
+/// This is useful when the [`Event`]s are not known up front, e.g.
+/// when they are produced by a live source. The state of the tracks
+/// is kept between calls to [`push`], and the rows of each [`Event`]
+/// are written immediately.
+///
+/// Note that when using [`Direction::RightToLeft`], [`Options::align_text`],
+/// [`Options::detached_gutter`] or [`Options::align_detached_text`],
+/// all rows are buffered until [`finish`]
+/// is called, as the width of the whole graph must be known before
+/// the first row can be written.
+///
+/// *See also [`to_writer`] and [`to_writer_with`].*
+///
+/// [`push`]: struct.Renderer.html#method.push
+/// [`finish`]: struct.Renderer.html#method.finish
+/// [`to_writer`]: fn.to_writer.html
+/// [`to_writer_with`]: fn.to_writer_with.html
+///
+/// [`Event`]: enum.Event.html
+/// [`Direction::RightToLeft`]: enum.Direction.html#variant.RightToLeft
+/// [`Options::align_text`]: struct.Options.html#structfield.align_text
+/// [`Options::detached_gutter`]: struct.Options.html#structfield.detached_gutter
+/// [`Options::align_detached_text`]: struct.Options.html#structfield.align_detached_text
+/// [`Options::initial_tracks`]: struct.Options.html#structfield.initial_tracks
+///
+/// # Example
+///
+/// ```
+/// use metro::{Event, Renderer, TrackId};
+///
+/// let mut renderer = Renderer::new(Vec::new());
+///
+/// renderer.push(&Event::station(0, "Station 1"))?;
+/// renderer.push(&Event::SplitTrack(TrackId(0), TrackId(1)))?;
+/// renderer.push(&Event::station(1, "Station 2"))?;
+/// renderer.push(&Event::JoinTrack(TrackId(1), TrackId(0)))?;
+///
+/// let vec = renderer.finish()?;
+///
+/// assert_eq!(
+///     String::from_utf8(vec).unwrap(),
+///     "* Station 1\n|\\\n| * Station 2\n|/\n"
+/// );
+/// # Ok::<(), std::io::Error>(())
+/// ```
+#[derive(Debug)]
+pub struct Renderer<W: Write> {
+    writer: W,
+    core: RenderCore,
+}
+
+impl<W: Write> Renderer<W> {
+    /// Create a new `Renderer`, which writes to `writer`.
+    #[inline]
+    pub fn new(writer: W) -> Self {
+        Self::with_options(writer, Options::default())
+    }
+
+    /// Create a new `Renderer`, which writes to `writer`, using [`Options`].
+    ///
+    /// [`Options`]: struct.Options.html
+    #[inline]
+    pub fn with_options(writer: W, opts: Options) -> Self {
+        Self {
+            writer,
+            core: RenderCore::new(opts),
+        }
+    }
+
+    /// Render `event` and write its rows.
+    #[inline]
+    pub fn push(&mut self, event: &Event) -> io::Result<()> {
+        self.core.push(&mut IoSink(&mut self.writer), event)
+    }
+
+    /// Update the tracks as if `event` was rendered,
+    /// without writing any rows.
+    #[inline]
+    pub fn skip(&mut self, event: &Event) {
+        self.core.skip(event);
+    }
+
+    /// Write any remaining rows, and return the writer.
+    #[inline]
+    pub fn finish(mut self) -> io::Result<W> {
+        self.core.finish(&mut IoSink(&mut self.writer))?;
+        Ok(self.writer)
+    }
+}
+
+/// The state of a [`Renderer`], which writes the rendered lines
+/// to a [`Sink`], such that the same rendering is used for both
+/// [`io::Write`] and [`fmt::Write`].
+///
+/// [`Renderer`]: struct.Renderer.html
+///
+/// [`io::Write`]: https://doc.rust-lang.org/stable/std/io/trait.Write.html
+/// [`fmt::Write`]: https://doc.rust-lang.org/stable/std/fmt/trait.Write.html
+#[derive(Debug)]
+struct RenderCore {
+    opts: Options,
+    state: RenderState,
+    /// Rows buffered until `finish`, when rendering right-to-left,
+    /// otherwise a reused buffer for rendering a single event.
+    rows: Vec<Row<'static>>,
+    /// Whether the newline of the last written row is deferred.
+    pending_newline: bool,
+    /// Reused buffer for rendering a single row.
+    line: String,
+    /// The column text is aligned to, when aligning text.
+    text_column: Option<usize>,
+    /// The last row of the previous event, when compacting splits
+    /// and the row may be compacted with the next row.
+    pending_split: Option<Row<'static>>,
+    /// The tracks of the last track id header, when showing track ids.
+    header_tracks: Vec<TrackId>,
+    /// The width of the gutter for the text of detached stations.
+    gutter: Option<usize>,
+    /// The column the text of detached stations is aligned to,
+    /// when aligning the text of detached stations.
+    detached_column: Option<usize>,
+    /// The index of the row each current track was started at,
+    /// when showing the age of tracks.
+    track_starts: HashMap<TrackId, usize>,
+    /// The number of rows rendered, when showing the age of tracks.
+    row_index: usize,
+    /// Whether the row of the initial tracks is yet to be pushed,
+    /// when rendering the initial tracks.
+    initial_row: bool,
+    /// The current ghost tracks, i.e. the ghost tracks which
+    /// have not had a station yet, and were not stopped.
+    ghost_tracks: HashSet<TrackId>,
+}
+
+impl RenderCore {
+    #[inline]
+    fn new(opts: Options) -> Self {
+        let mut state = RenderState::with_tracks(opts.initial_tracks.clone());
+
+        if opts.sort_tracks {
+            state.tracks.sort();
+            state.sort_tracks = true;
+        }
+
+        // The initial tracks are started before the first row
+        let track_starts = state.tracks.iter().map(|&id| (id, 0)).collect();
+        let initial_row = opts.render_initial_tracks;
+        let ghost_tracks = opts.ghost_tracks.clone();
+
+        Self {
+            state,
+            opts,
+            rows: Vec::new(),
+            pending_newline: false,
+            line: String::new(),
+            text_column: None,
+            pending_split: None,
+            header_tracks: Vec::new(),
+            gutter: None,
+            detached_column: None,
+            track_starts,
+            row_index: 0,
+            initial_row,
+            ghost_tracks,
+        }
+    }
+
+    /// Render `event` and write its rows to `sink`.
+    fn push<S: Sink>(&mut self, sink: &mut S, event: &Event) -> Result<(), S::Error> {
+        if self.opts.unknown_track_policy == UnknownTrackPolicy::Error {
+            if let Some(&track_id) = self.unknown_tracks(event).first() {
+                return Err(S::unknown_track(track_id));
+            }
+        }
+
+        if self.is_buffered() {
+            let mut rows = Vec::new();
+            self.push_initial_row(&mut rows);
+            self.push_header(&mut rows);
+            self.render(&mut rows, event);
+
+            self.rows.extend(rows.into_iter().map(Row::into_owned));
+            return Ok(());
+        }
+
+        // The rows are only buffered until `finish` when rendering
+        // right-to-left or aligning text, so the buffer is reused
+        // for rendering a single event
+        let mut rows = mem::take(&mut self.rows);
+        rows.extend(self.pending_split.take());
+
+        self.push_initial_row(&mut rows);
+        self.push_header(&mut rows);
+        self.render(&mut rows, event);
+
+        if self.opts.compact_splits {
+            compact_splits(&mut rows);
+
+            // The split may be compacted with the first row of the next event
+            if rows.last().is_some_and(Row::is_rightmost_split) {
+                self.pending_split = rows.pop().map(Row::into_owned);
+            }
+        }
+
+        let result = self.write_rows(sink, &mut rows);
+
+        // `rows` is empty, so this reuses its allocation
+        self.rows = rows.into_iter().map(Row::into_owned).collect();
+
+        result
+    }
+
+    /// Render `event` into `rows`, applying the [`Options::station_markers`]
+    /// and [`Options::show_track_age`] to its station, the
+    /// [`Options::merge_marker`] to its join, and the
+    /// [`Options::ghost_tracks`] to its rails.
+    ///
+    /// [`Options::station_markers`]: struct.Options.html#structfield.station_markers
+    /// [`Options::show_track_age`]: struct.Options.html#structfield.show_track_age
+    /// [`Options::merge_marker`]: struct.Options.html#structfield.merge_marker
+    /// [`Options::ghost_tracks`]: struct.Options.html#structfield.ghost_tracks
+    fn render<'e>(&mut self, rows: &mut Vec<Row<'e>>, event: &'e Event) {
+        let start = rows.len();
+
+        // Only needed for finding the started, stopped and joined tracks
+        let before = if self.opts.show_track_age
+            || self.opts.merge_marker.is_some()
+            || !self.ghost_tracks.is_empty()
+        {
+            self.state.tracks.clone()
+        } else {
+            Vec::new()
+        };
+
+        if self.opts.unknown_track_policy == UnknownTrackPolicy::AutoStart {
+            for track_id in self.unknown_tracks(event) {
+                let start = Event::StartTrack(track_id);
+                let mut started = Vec::new();
+                render_event(&mut started, &mut self.state, &start);
+
+                for row in started {
+                    rows.push(row.into_owned());
+                }
+            }
+        }
+
+        render_event(rows, &mut self.state, event);
+
+        if self.opts.show_track_age {
+            self.push_track_age(&mut rows[start..], &before, event);
+        }
+
+        if !self.opts.station_markers.is_empty() {
+            self.replace_station_markers(&mut rows[start..], event);
+        }
+
+        if let Some(marker) = self.opts.merge_marker {
+            replace_merge_marker(&mut rows[start..], &before, event, marker);
+        }
+
+        if !self.ghost_tracks.is_empty() {
+            self.replace_ghost_rails(&mut rows[start..], &before, event);
+        }
+    }
+
+    /// Returns the tracks of the station of `event`, which do not exist.
+    ///
+    /// *[See `Options::unknown_track_policy`.][`Options::unknown_track_policy`]*
+    ///
+    /// [`Options::unknown_track_policy`]: struct.Options.html#structfield.unknown_track_policy
+    fn unknown_tracks(&self, event: &Event) -> Vec<TrackId> {
+        use Event::*;
+        let track_ids = match event {
+            Station(track_id, _) | StationMarked(track_id, _, _) => slice::from_ref(track_id),
+            StationMulti(track_ids, _) => track_ids,
+            _ => &[],
+        };
+
+        let mut unknown = Vec::new();
+        for &track_id in track_ids {
+            if !self.state.tracks.contains(&track_id) && !unknown.contains(&track_id) {
+                unknown.push(track_id);
+            }
+        }
+        unknown
+    }
+
+    /// Replaces the rails of the ghost tracks in `rows` with `:`,
+    /// given the `rows` of `event` and the tracks `before` it.
+    ///
+    /// *[See `Options::ghost_tracks`.][`Options::ghost_tracks`]*
+    ///
+    /// [`Options::ghost_tracks`]: struct.Options.html#structfield.ghost_tracks
+    fn replace_ghost_rails(&mut self, rows: &mut [Row], before: &[TrackId], event: &Event) {
+        let Self {
+            state,
+            ghost_tracks,
+            ..
+        } = self;
+
+        // Stopped tracks are no longer ghost tracks
+        ghost_tracks
+            .retain(|track_id| !before.contains(track_id) || state.tracks.contains(track_id));
+
+        use Event::*;
+        match event {
+            Station(track_id, _) | StationMarked(track_id, _, _) | SplitStation(_, track_id, _) => {
+                ghost_tracks.remove(track_id);
+            }
+            StationMulti(track_ids, _) => {
+                for track_id in track_ids.iter() {
+                    ghost_tracks.remove(track_id);
+                }
+            }
+            _ => {}
+        }
+
+        for row in rows.iter_mut() {
+            ghost_rails(row, &state.tracks, ghost_tracks);
+        }
+    }
+
+    /// Appends the age of the oldest track of the station of `event`
+    /// to its text, i.e. the number of rows since the track was started,
+    /// given the `rows` of `event` and the tracks `before` it.
+    ///
+    /// *[See `Options::show_track_age`.][`Options::show_track_age`]*
+    ///
+    /// [`Options::show_track_age`]: struct.Options.html#structfield.show_track_age
+    fn push_track_age(&mut self, rows: &mut [Row], before: &[TrackId], event: &Event) {
+        let Self {
+            state,
+            track_starts,
+            row_index,
+            ..
+        } = self;
+
+        track_starts.retain(|track_id, _| state.tracks.contains(track_id));
+        for &track_id in state.tracks.iter().filter(|id| !before.contains(id)) {
+            track_starts.insert(track_id, *row_index);
+        }
+
+        use Event::*;
+        let track_ids = match event {
+            Station(track_id, _) | StationMarked(track_id, _, _) | SplitStation(_, track_id, _) => {
+                slice::from_ref(track_id)
+            }
+            StationMulti(track_ids, _) => track_ids,
+            _ => &[],
+        };
+
+        let start = track_ids
+            .iter()
+            .filter_map(|track_id| track_starts.get(track_id))
+            .min();
+
+        // The station is the first row with text, as
+        // any rows of a split before it have no text
+        let station = rows
+            .iter_mut()
+            .enumerate()
+            .find(|(_, row)| row.text.is_some());
+
+        if let (Some(&start), Some((i, row))) = (start, station) {
+            let age = *row_index + i - start;
+
+            if let Some(text) = row.text.as_mut() {
+                *text = Cow::Owned(format!("{} (age {})", text, age));
+            }
+        }
+
+        *row_index += rows.len();
+    }
+
+    /// Replaces the `*` of the station of `event` in `rows`,
+    /// with the [`Options::station_markers`] of their track.
+    ///
+    /// [`Options::station_markers`]: struct.Options.html#structfield.station_markers
+    fn replace_station_markers(&self, rows: &mut [Row], event: &Event) {
+        if let Event::Station(..) | Event::StationMulti(..) | Event::SplitStation(..) = event {
+            // The station is rendered after any split, so the rails
+            // are in the same order as the current tracks
+            let tracks = &self.state.tracks;
+
+            let row = rows
+                .iter_mut()
+                .find(|row| row.cells.contains(&Glyph::Station));
+
+            if let Some(row) = row {
+                for (column, cell) in row.cells.iter_mut().step_by(2).enumerate() {
+                    if *cell != Glyph::Station {
+                        continue;
+                    }
+
+                    let marker = tracks
+                        .get(column)
+                        .and_then(|track_id| self.opts.station_markers.get(track_id));
+
+                    // A marker must be a single column wide,
+                    // otherwise the rails would no longer align
+                    if let Some(&marker) = marker.filter(|&&c| char_width(c, 0) == 1) {
+                        *cell = Glyph::Marker(marker);
+                    }
+                }
+            }
+        }
+    }
+
+    fn skip(&mut self, event: &Event) {
+        // The initial tracks precede the skipped rows
+        self.initial_row = false;
+
+        let mut rows = Vec::new();
+        self.render(&mut rows, event);
+    }
+
+    /// Write any remaining rows to `sink`.
+    fn finish<S: Sink>(&mut self, sink: &mut S) -> Result<(), S::Error> {
+        // Only pushed here if there were no events
+        let mut initial = Vec::new();
+        self.push_initial_row(&mut initial);
+
+        let continuation = self.continuation_row();
+
+        if !self.is_buffered() {
+            let mut rows = self
+                .pending_split
+                .take()
+                .into_iter()
+                .chain(initial)
+                .chain(continuation)
+                .collect();
+            return self.write_rows(sink, &mut rows);
+        }
+
+        self.rows.extend(initial);
+        self.rows.extend(continuation);
+
+        if self.opts.compact_splits {
+            compact_splits(&mut self.rows);
+        }
+
+        if self.opts.direction == Direction::RightToLeft {
+            // The width is kept odd, such that rails remain
+            // at even indices after being mirrored
+            let width = self
+                .rows
+                .iter()
+                .map(|row| row.cells.len())
+                .max()
+                .unwrap_or(0)
+                | 1;
+
+            for row in self.rows.iter_mut() {
+                row.mirror(width);
+            }
+        }
+
+        if self.opts.align_text {
+            let Self {
+                opts, rows, line, ..
+            } = self;
+
+            self.text_column = rows
+                .iter()
+                .filter(|row| row.aligned && row.text.is_some())
+                .map(|row| {
+                    line.clear();
+                    row.push_rails(line, opts.rail_spacing);
+
+                    match text_width(line) {
+                        0 => 0,
+                        width => width + opts.text_gap(),
+                    }
+                })
+                .max();
+        }
+
+        if self.opts.detached_gutter {
+            let Self {
+                opts, rows, line, ..
+            } = self;
+
+            self.gutter = rows
+                .iter()
+                .filter(|row| row.detached)
+                .filter_map(|row| row.text.as_deref())
+                .map(|text| {
+                    line.clear();
+                    push_text(line, text, 0, opts.max_text_width);
+                    text_width(line) + opts.text_gap()
+                })
+                .max();
+        }
+
+        if self.opts.align_detached_text && !self.opts.detached_gutter {
+            let Self {
+                opts, rows, line, ..
+            } = self;
+
+            self.detached_column = rows
+                .iter()
+                .map(|row| {
+                    line.clear();
+                    row.push_rails(line, opts.rail_spacing);
+
+                    match text_width(line) {
+                        0 => 0,
+                        width => width + opts.text_gap(),
+                    }
+                })
+                .max();
+        }
+
+        let mut rows = mem::take(&mut self.rows);
+        self.write_rows(sink, &mut rows)
+    }
+
+    /// Returns a row of [`Options::continuation_marker`] under each
+    /// rail of the current tracks, if any.
+    ///
+    /// [`Options::continuation_marker`]: struct.Options.html#structfield.continuation_marker
+    fn continuation_row(&self) -> Option<Row<'static>> {
+        let marker = self.opts.continuation_marker?;
+        if self.state.tracks.is_empty() {
+            return None;
+        }
+
+        // A marker must be a single column wide,
+        // otherwise the rails would no longer align
+        let marker = if char_width(marker, 0) == 1 {
+            marker
+        } else {
+            ':'
+        };
+
+        let track_count = self.state.tracks.len();
+        Some(Row::from_rails(track_count, None, |_| {
+            Glyph::Marker(marker)
+        }))
+    }
+
+    /// Pushes a row of rails of the initial tracks to `rows`,
+    /// preceded by their header, if not yet pushed.
+    ///
+    /// *[See `Options::render_initial_tracks`.][`Options::render_initial_tracks`]*
+    ///
+    /// [`Options::render_initial_tracks`]: struct.Options.html#structfield.render_initial_tracks
+    fn push_initial_row(&mut self, rows: &mut Vec<Row>) {
+        if !mem::take(&mut self.initial_row) {
+            return;
+        }
+
+        self.push_header(rows);
+
+        if !self.state.tracks.is_empty() {
+            let track_count = self.state.tracks.len();
+            let mut row = Row::from_rails(track_count, None, |_| Glyph::Rail);
+            ghost_rails(&mut row, &self.state.tracks, &self.ghost_tracks);
+
+            rows.push(row);
+        }
+    }
+
+    /// Pushes a header of the track ids to `rows`, if showing
+    /// track ids and the tracks changed since the last header.
+    ///
+    /// *[See `Options::show_track_ids`.][`Options::show_track_ids`]*
+    ///
+    /// [`Options::show_track_ids`]: struct.Options.html#structfield.show_track_ids
+    fn push_header(&mut self, rows: &mut Vec<Row>) {
+        if !self.opts.show_track_ids || (self.state.tracks == self.header_tracks) {
+            return;
+        }
+
+        if !self.state.tracks.is_empty() {
+            rows.push(Row::header(&self.state.tracks));
+        }
+
+        self.header_tracks.clone_from(&self.state.tracks);
+    }
+
+    /// Whether all rows are buffered until `finish`.
+    #[inline]
+    fn is_buffered(&self) -> bool {
+        (self.opts.direction == Direction::RightToLeft)
+            || self.opts.align_text
+            || self.opts.detached_gutter
+            || self.opts.align_detached_text
+    }
+
+    /// Write and drain the `rows`.
+    ///
+    /// If [`Options::trailing_newline`] is `false`, then the newline of
+    /// the last written row is deferred until another row is written.
+    ///
+    /// [`Options::trailing_newline`]: struct.Options.html#structfield.trailing_newline
+    fn write_rows<S: Sink>(&mut self, sink: &mut S, rows: &mut Vec<Row>) -> Result<(), S::Error> {
+        for row in rows.drain(..) {
+            let line_ending = self.opts.line_ending.as_str();
+
+            if self.pending_newline {
+                sink.write_str(line_ending)?;
+                self.pending_newline = false;
+            }
+
+            let text_column = if row.detached {
+                self.detached_column.or(self.text_column)
+            } else {
+                self.text_column
+            };
+
+            self.line.clear();
+            row.push_line(&mut self.line, &self.opts, text_column, self.gutter);
+
+            // The text of a row may span multiple lines, e.g. a label in a legend
+            if (self.opts.line_ending != LineEnding::Lf) && self.line.contains('\n') {
+                self.line = self.line.replace('\n', line_ending);
+            }
+
+            if self.opts.trailing_newline {
+                self.line.push_str(line_ending);
+            } else {
+                self.pending_newline = true;
+            }
+
+            sink.write_str(&self.line)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// A destination of the rendered lines of a [`RenderCore`].
+trait Sink {
+    type Error;
+
+    fn write_str(&mut self, s: &str) -> Result<(), Self::Error>;
+
+    /// Returns the error for a station on `track_id`, which does not exist.
+    ///
+    /// *[See `UnknownTrackPolicy::Error`.][`UnknownTrackPolicy::Error`]*
+    ///
+    /// [`UnknownTrackPolicy::Error`]: enum.UnknownTrackPolicy.html#variant.Error
+    fn unknown_track(track_id: TrackId) -> Self::Error;
+}
+
+#[inline]
+fn unknown_track_error(track_id: TrackId) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidInput,
+        format!("track {} does not exist", track_id),
+    )
+}
+
+/// Writes the rendered lines to an [`io::Write`].
+///
+/// [`io::Write`]: https://doc.rust-lang.org/stable/std/io/trait.Write.html
+struct IoSink<'w, W: Write>(&'w mut W);
+
+impl<W: Write> Sink for IoSink<'_, W> {
+    type Error = io::Error;
+
+    #[inline]
+    fn write_str(&mut self, s: &str) -> io::Result<()> {
+        self.0.write_all(s.as_bytes())
+    }
+
+    #[inline]
+    fn unknown_track(track_id: TrackId) -> io::Error {
+        unknown_track_error(track_id)
+    }
+}
+
+/// Writes the rendered lines to a [`fmt::Write`], without
+/// going through bytes.
+///
+/// [`fmt::Write`]: https://doc.rust-lang.org/stable/std/fmt/trait.Write.html
+struct FmtSink<'w, W: fmt::Write + ?Sized>(&'w mut W);
+
+impl<W: fmt::Write + ?Sized> Sink for FmtSink<'_, W> {
+    type Error = fmt::Error;
+
+    #[inline]
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.0.write_str(s)
+    }
+
+    #[inline]
+    fn unknown_track(_track_id: TrackId) -> fmt::Error {
+        fmt::Error
+    }
+}
+
+/// Writes the rendered lines to a [`String`], which never fails,
+/// unlike [`FmtSink`] the error of an unknown track is an [`io::Error`].
+///
+/// [`String`]: https://doc.rust-lang.org/stable/std/string/struct.String.html
+/// [`io::Error`]: https://doc.rust-lang.org/stable/std/io/struct.Error.html
+struct StringSink<'w>(&'w mut String);
+
+impl Sink for StringSink<'_> {
+    type Error = io::Error;
+
+    #[inline]
+    fn write_str(&mut self, s: &str) -> io::Result<()> {
+        self.0.push_str(s);
+        Ok(())
+    }
+
+    #[inline]
+    fn unknown_track(track_id: TrackId) -> io::Error {
+        unknown_track_error(track_id)
+    }
+}
+
+/// Counts the lines written to the wrapped [`Sink`].
+struct LineCountSink<S: Sink> {
+    sink: S,
+    lines: usize,
+}
+
+impl<S: Sink> Sink for LineCountSink<S> {
+    type Error = S::Error;
+
+    #[inline]
+    fn write_str(&mut self, s: &str) -> Result<(), S::Error> {
+        self.lines += s.matches('\n').count();
+        self.sink.write_str(s)
+    }
+
+    #[inline]
+    fn unknown_track(track_id: TrackId) -> S::Error {
+        S::unknown_track(track_id)
+    }
+}
+
+/// A single rendered row, produced by [`to_rows`].
+///
+/// A `Row` is a structured description of a line rendered by
+/// [`to_string`], such that other output formats can be produced
+/// without parsing the rendered text.
+///
+/// [`to_rows`]: fn.to_rows.html
+/// [`to_string`]: fn.to_string.html
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Row<'e> {
+    /// Even indices are rails, odd indices are the gaps between them.
+    pub(crate) cells: Vec<Glyph>,
+    /// Text placed after the rails, e.g. the text of a station.
+    pub(crate) text: Option<Cow<'e, str>>,
+    /// Whether the text is aligned with the text of other rows,
+    /// when aligning text. Labels in a legend are not aligned.
+    aligned: bool,
+    /// The `(cell index, track id)` pairs of a track id header,
+    /// which is rendered instead of the cells.
+    header: Vec<(usize, TrackId)>,
+    /// Whether the text is of a station whose track does not exist.
+    detached: bool,
+}
+
+impl<'e> Row<'e> {
+    /// Creates a row of `track_count` rails.
+    #[inline]
+    fn rails(track_count: usize, text: Option<&'e str>) -> Self {
+        Self::from_rails(track_count, text.map(Cow::Borrowed), |_| Glyph::Rail)
+    }
+
+    /// Returns `true` if the row only branches off the rightmost
+    /// rail, i.e. `| |\`, as rendered by splitting the rightmost track.
+    fn is_rightmost_split(&self) -> bool {
+        match self.cells.split_last() {
+            Some((&Glyph::DiagonalRight, rails)) => self.text.is_none() && is_rails(rails),
+            _ => false,
+        }
+    }
+
+    /// Returns `true` if the row is a station on the rightmost rail,
+    /// with as many rails as rendered below `split`.
+    fn is_rightmost_station(&self, split: &Self) -> bool {
+        match self.cells.split_last() {
+            Some((&Glyph::Station, rails)) | Some((&Glyph::Marker(_), rails)) => {
+                (self.cells.len() == split.cells.len() + 1) && is_rails(rails)
+            }
+            _ => false,
+        }
+    }
+
+    /// Creates a row of `track_count` rails, where `rail(i)` is
+    /// the glyph of the rail at column `i`.
+    fn from_rails<F>(track_count: usize, text: Option<Cow<'e, str>>, mut rail: F) -> Self
+    where
+        F: FnMut(usize) -> Glyph,
+    {
+        let mut cells = Vec::with_capacity(track_count * 2);
+
+        for i in 0..track_count {
+            if i > 0 {
+                cells.push(Glyph::Space);
+            }
+            cells.push(rail(i));
+        }
+
+        Self {
+            cells,
+            text,
+            aligned: true,
+            header: Vec::new(),
+            detached: false,
+        }
+    }
+
+    /// Returns the glyphs of the row, from left to right.
+    ///
+    /// Even indices are rails, and odd indices are the gaps between
+    /// them, i.e. the rail of the track at column `i` is at index `i * 2`.
+    /// Trailing [`Glyph::Space`]s are not included, unless the row
+    /// has text.
+    ///
+    /// [`Glyph::Space`]: enum.Glyph.html#variant.Space
+    #[inline]
+    pub fn cells(&self) -> &[Glyph] {
+        &self.cells
+    }
+
+    /// Returns the text placed after the rails, e.g. the text of
+    /// a station, an annotation, or a label in a legend.
+    ///
+    /// Text with multiple lines is split into multiple rows,
+    /// except for labels.
+    #[inline]
+    pub fn text(&self) -> Option<&str> {
+        self.text.as_deref()
+    }
+
+    /// Converts the `Row` into a `Row` which owns its text.
+    #[inline]
+    pub fn into_owned(self) -> Row<'static> {
+        Row {
+            cells: self.cells,
+            text: self.text.map(|text| Cow::Owned(text.into_owned())),
+            aligned: self.aligned,
+            header: self.header,
+            detached: self.detached,
+        }
+    }
+
+    /// Creates a header of the track ids of `tracks`,
+    /// placed above their rails.
+    fn header(tracks: &[TrackId]) -> Self {
+        let mut row = Self::from_rails(tracks.len(), None, |_| Glyph::Space);
+        row.aligned = false;
+        row.header = tracks
+            .iter()
+            .enumerate()
+            .map(|(column, &track_id)| (column * 2, track_id))
+            .collect();
+        row
+    }
+
+    /// Mirrors the cells horizontally, after padding them to `width`.
+    fn mirror(&mut self, width: usize) {
+        self.cells.resize(width, Glyph::Space);
+        self.cells.reverse();
+
+        for (i, _) in self.header.iter_mut() {
+            *i = width - 1 - *i;
+        }
+        self.header.reverse();
+
+        for cell in self.cells.iter_mut() {
+            *cell = match *cell {
+                Glyph::DiagonalLeft => Glyph::DiagonalRight,
+                Glyph::DiagonalRight => Glyph::DiagonalLeft,
+                cell => cell,
+            };
+        }
+    }
+}
+
+impl Row<'_> {
+    /// Renders the row as a single line, where each gap between
+    /// two rails is [`Options::rail_spacing`] characters wide.
+    ///
+    /// The text is separated from the rails by [`Options::text_gap`].
+    ///
+    /// [`Options::rail_spacing`]: struct.Options.html#structfield.rail_spacing
+    /// [`Options::text_gap`]: struct.Options.html#structfield.text_gap
+    pub(crate) fn to_line(&self, opts: &Options) -> String {
+        let mut line = String::with_capacity(self.cells.len() * (opts.rail_spacing.max(1) + 1));
+        self.push_line(&mut line, opts, None, None);
+        line
+    }
+
+    /// Appends the row to `line`, the same as [`to_line`], such that
+    /// the allocation of `line` can be reused between rows.
+    ///
+    /// If `text_column` is `Some`, then the text is placed at that
+    /// column instead, unless the row is not `aligned`.
+    ///
+    /// If `gutter` is `Some`, then the row is prefixed by a gutter of
+    /// that width, which contains the text if the row is `detached`.
+    ///
+    /// [`to_line`]: struct.Row.html#method.to_line
+    fn push_line(
+        &self,
+        line: &mut String,
+        opts: &Options,
+        text_column: Option<usize>,
+        gutter: Option<usize>,
+    ) {
+        // An empty line of text, e.g. between two lines of a station,
+        // renders as only rails, without any trailing whitespace
+        let mut text = self.text.as_deref().filter(|text| !text.is_empty());
+
+        if let Some(gutter) = gutter {
+            let start = line.len();
+
+            if self.detached {
+                if let Some(text) = text.take() {
+                    push_text(line, text, 0, opts.max_text_width);
+                }
+            }
+
+            let width = text_width(&line[start..]);
+            line.extend(iter::repeat_n(' ', gutter.saturating_sub(width)));
+        }
+
+        let start = line.len();
+
+        if !self.header.is_empty() {
+            self.push_header(line, opts.rail_spacing);
+            return;
+        }
+
+        self.push_rails(line, opts.rail_spacing);
+
+        match text {
+            Some(text) => {
+                match text_column.filter(|_| self.aligned) {
+                    Some(text_column) => {
+                        let width = text_width(&line[start..]);
+                        line.extend(iter::repeat_n(' ', text_column.saturating_sub(width)));
+                    }
+                    None if line.len() == start => {}
+                    None => line.extend(iter::repeat_n(' ', opts.text_gap())),
+                }
+
+                let column = text_width(&line[start..]);
+                push_text(line, text, column, opts.max_text_width);
+            }
+            None => line.truncate(line.trim_end().len()),
+        }
+    }
+
+    /// Appends the track ids of a header to `line`, each placed above
+    /// its rail, unless the previous id is too wide. Then the id is
+    /// instead separated from the previous id by a single space.
+    fn push_header(&self, line: &mut String, rail_spacing: usize) {
+        let start = line.len();
+
+        for &(i, track_id) in self.header.iter() {
+            let column = i / 2 * (rail_spacing.max(1) + 1);
+            let len = line.len() - start;
+
+            if len < column {
+                line.extend(iter::repeat_n(' ', column - len));
+            } else if len > 0 {
+                line.push(' ');
+            }
+
+            line.push_str(&track_id.to_string());
+        }
+    }
+
+    /// Appends the rails of the row to `line`, where each gap
+    /// between two rails is `rail_spacing` characters wide.
+    fn push_rails(&self, line: &mut String, rail_spacing: usize) {
+        let rail_spacing = rail_spacing.max(1);
+
+        for (i, &cell) in self.cells.iter().enumerate() {
+            if i % 2 == 0 {
+                line.push(cell.to_char());
+                continue;
+            }
+
+            // Diagonals are drawn next to the rail they leave, and the
+            // remaining gap is filled towards the rail they enter
+            match cell {
+                Glyph::DiagonalLeft => {
+                    line.extend(iter::repeat_n('_', rail_spacing - 1));
+                    line.push('/');
+                }
+                Glyph::DiagonalRight => {
+                    line.push('\\');
+                    line.extend(iter::repeat_n('_', rail_spacing - 1));
+                }
+                cell => line.extend(iter::repeat_n(cell.to_char(), rail_spacing)),
+            }
+        }
+    }
+}
+
+/// The kind of a single cell in a [`Row`].
+///
+/// [`Row`]: struct.Row.html
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum Glyph {
+    /// Nothing, rendered as `' '`.
+    Space,
+
+    /// A rail, rendered as `|`.
+    Rail,
+
+    /// A station on a rail, rendered as `*`.
+    Station,
+
+    /// A rail that stops, rendered as `"`.
+    Stop,
+
+    /// A rail moving to the left, e.g. when joining,
+    /// rendered as `/`.
+    DiagonalLeft,
+
+    /// A rail moving to the right, e.g. when splitting,
+    /// rendered as `\`.
+    DiagonalRight,
+
+    /// A rail moving more than a single column, rendered as `_`.
+    Horizontal,
+
+    /// A link between two rails, rendered as `.`.
+    Link,
+
+    /// A station with a custom marker, rendered as the `char`.
+    ///
+    /// *[See `Event::StationMarked` for more information.][`Event::StationMarked`]*
+    ///
+    /// [`Event::StationMarked`]: enum.Event.html#variant.StationMarked
+    Marker(char),
+}
+
+impl Glyph {
+    /// Returns the `char` used when rendering the glyph as text.
+    #[inline]
+    pub fn to_char(self) -> char {
+        match self {
+            Glyph::Space => ' ',
+            Glyph::Rail => '|',
+            Glyph::Station => '*',
+            Glyph::Stop => '"',
+            Glyph::DiagonalLeft => '/',
+            Glyph::DiagonalRight => '\\',
+            Glyph::Horizontal => '_',
+            Glyph::Link => '.',
+            Glyph::Marker(marker) => marker,
+        }
+    }
+}
+
+/// The state needed for rendering the next event.
+#[derive(Debug)]
+pub(crate) struct RenderState {
+    /// The current tracks, from left to right.
+    pub(crate) tracks: Vec<TrackId>,
+    /// The labels of the current tracks.
+    labels: HashMap<TrackId, String>,
+    /// Whether new tracks are placed at their position sorted by track id.
+    ///
+    /// *[See `Options::sort_tracks`.][`Options::sort_tracks`]*
+    ///
+    /// [`Options::sort_tracks`]: struct.Options.html#structfield.sort_tracks
+    sort_tracks: bool,
+}
+
+impl RenderState {
+    #[inline]
+    pub(crate) fn new() -> Self {
+        Self::with_tracks(vec![TrackId(0)])
+    }
+
+    #[inline]
+    pub(crate) fn with_tracks(tracks: Vec<TrackId>) -> Self {
+        Self {
+            tracks,
+            labels: HashMap::new(),
+            sort_tracks: false,
+        }
+    }
+}
+
+/// Render the rows of a single `event`, given the current `state`,
+/// and update `state` accordingly.
+pub(crate) fn render_event<'e>(rows: &mut Vec<Row<'e>>, state: &mut RenderState, event: &'e Event) {
+    let RenderState {
+        tracks,
+        labels,
+        sort_tracks,
+    } = state;
+
+    if *sort_tracks && render_sorted(rows, tracks, event) {
+        return;
+    }
+
+    use Event::*;
+    match event {
+        &StartTrack(track_id) => {
+            if !tracks.contains(&track_id) {
+                tracks.push(track_id);
+
+                rows.push(Row::rails(tracks.len(), None));
+            }
+        }
+
+        &StartTracks(track_ids) => {
+            let mut render = false;
+
+            for track_id in track_ids.iter() {
+                if !tracks.contains(track_id) {
+                    tracks.push(*track_id);
+
+                    render = true;
+                }
+            }
+
+            if render {
+                rows.push(Row::rails(tracks.len(), None));
+            }
+        }
+
+        &StartTrackAt(track_id, column_index) => {
+            start_track_at(rows, tracks, track_id, column_index)
+        }
+
+        &StopTrack(track_id) => stop_track(rows, tracks, track_id),
+
+        &StopTracks(track_ids) => stop_tracks(rows, tracks, |id| track_ids.contains(&id)),
+
+        StopTrackLabeled(track_id, label) => {
+            stop_tracks_labeled(rows, tracks, Some(label), |id| id == *track_id)
+        }
+
+        Station(track_id, station_name) => station(
+            rows,
+            tracks,
+            slice::from_ref(track_id),
+            station_name,
+            Glyph::Station,
+        ),
+
+        StationMarked(track_id, station_name, marker) => station(
+            rows,
+            tracks,
+            slice::from_ref(track_id),
+            station_name,
+            Glyph::Marker(*marker),
+        ),
+
+        StationMulti(track_ids, station_name) => {
+            station(rows, tracks, track_ids, station_name, Glyph::Station)
+        }
+
+        DetachedStation(station_name) => station(rows, tracks, &[], station_name, Glyph::Station),
+
+        Annotation(text) => {
+            for text in text.lines() {
+                rows.push(Row::rails(tracks.len(), Some(text)));
+            }
+        }
+
+        &SplitTrack(from_track_id, new_track_id) => {
+            split_track(rows, tracks, from_track_id, new_track_id)
+        }
+
+        SplitTrackN(from_track_id, new_track_ids) => {
+            split_tracks(rows, tracks, *from_track_id, new_track_ids)
+        }
+
+        SplitStation(from_track_id, new_track_id, station_name) => {
+            split_track(rows, tracks, *from_track_id, *new_track_id);
+            station(
+                rows,
+                tracks,
+                slice::from_ref(new_track_id),
+                station_name,
+                Glyph::Station,
+            );
+        }
+
+        &SplitTrackAt(from_track_id, new_track_id, neighbor_track_id) => {
+            if tracks.contains(&new_track_id) {
+                return;
+            }
+
+            let from_track_index = tracks.iter().position(|&id| id == from_track_id);
+            let neighbor_track_index = tracks.iter().position(|&id| id == neighbor_track_id);
+
+            match (from_track_index, neighbor_track_index) {
+                (Some(from_track_index), Some(neighbor_track_index)) => split_track_to(
+                    rows,
+                    tracks,
+                    from_track_index,
+                    new_track_id,
+                    neighbor_track_index + 1,
+                ),
+                (None, Some(neighbor_track_index)) => {
+                    tracks.insert(neighbor_track_index + 1, new_track_id);
+
+                    rows.push(Row::rails(tracks.len(), None));
+                }
+                (_, None) => split_track(rows, tracks, from_track_id, new_track_id),
+            }
+        }
+
+        &JoinTrack(from_track_id, to_track_id)
+        | &JoinTrackToward(from_track_id, to_track_id)
+        | &JoinTrackOrRename(from_track_id, to_track_id) => {
+            let from_track_index = tracks.iter().position(|&id| id == from_track_id);
+
+            if from_track_id == to_track_id {
+                stop_track(rows, tracks, from_track_id);
+                return;
+            }
+
+            if let Some(from_track_index) = from_track_index {
+                let to_track_index = tracks.iter().position(|&id| id == to_track_id);
+
+                if let Some(to_track_index) = to_track_index {
+                    if let JoinTrackToward(..) = event {
+                        merge_rails(rows, tracks.len(), &[from_track_index], to_track_index);
+                    } else {
+                        let left_index = from_track_index.min(to_track_index);
+                        let right_index = from_track_index.max(to_track_index);
+
+                        // The rails are always joined towards the leftmost rail
+                        merge_rails(rows, tracks.len(), &[right_index], left_index);
+                    }
+
+                    tracks.remove(from_track_index);
+                } else if let JoinTrackOrRename(..) = event {
+                    split_track(rows, tracks, from_track_id, to_track_id);
+                    stop_track(rows, tracks, from_track_id);
+
+                    if let Some(label) = labels.remove(&from_track_id) {
+                        labels.insert(to_track_id, label);
+                    }
+                } else {
+                    stop_track(rows, tracks, from_track_id);
+                }
+            }
+        }
+
+        &JoinTrackKeepSource(from_track_id, to_track_id) => {
+            if from_track_id == to_track_id {
+                stop_track(rows, tracks, to_track_id);
+                return;
+            }
+
+            let from_track_index = tracks.iter().position(|&id| id == from_track_id);
+            let to_track_index = tracks.iter().position(|&id| id == to_track_id);
+
+            match (from_track_index, to_track_index) {
+                (Some(from_track_index), Some(to_track_index)) => {
+                    let left_index = from_track_index.min(to_track_index);
+                    let right_index = from_track_index.max(to_track_index);
+
+                    // The rails are always joined towards the leftmost rail,
+                    // which then continues as `from_track_id`
+                    merge_rails(rows, tracks.len(), &[right_index], left_index);
+
+                    tracks[left_index] = from_track_id;
+                    tracks.remove(right_index);
+                }
+                (None, Some(_)) => stop_track(rows, tracks, to_track_id),
+                (_, None) => {}
+            }
+        }
+
+        &JoinTracks(from_track_ids, to_track_id) => {
+            let to_track_index = tracks.iter().position(|&id| id == to_track_id);
+
+            if to_track_index.is_none() || from_track_ids.contains(&to_track_id) {
+                stop_tracks(rows, tracks, |id| {
+                    (id != to_track_id) && from_track_ids.contains(&id)
+                });
+                return;
+            }
+
+            // Safe to use `unwrap` as `to_track_index` was just checked
+            let to_track_index = to_track_index.unwrap();
+
+            let from_track_indices = (0..tracks.len())
+                .filter(|&i| from_track_ids.contains(&tracks[i]))
+                .collect::<Vec<_>>();
+
+            if !from_track_indices.is_empty() {
+                merge_rails(rows, tracks.len(), &from_track_indices, to_track_index);
+
+                tracks.retain(|id| !from_track_ids.contains(id));
+            }
+        }
+
+        &Link(from_track_id, to_track_id) => {
+            let from_track_index = tracks.iter().position(|&id| id == from_track_id);
+            let to_track_index = tracks.iter().position(|&id| id == to_track_id);
+
+            if let (Some(from_track_index), Some(to_track_index)) =
+                (from_track_index, to_track_index)
+            {
+                if from_track_index == to_track_index {
+                    return;
+                }
+
+                let left_index = from_track_index.min(to_track_index);
+                let right_index = from_track_index.max(to_track_index);
+
+                let mut row = Row::rails(tracks.len(), None);
+                for gap in left_index..right_index {
+                    row.cells[gap * 2 + 1] = Glyph::Link;
+                }
+
+                rows.push(row);
+            }
+        }
+
+        &RenameTrack(old_track_id, new_track_id) => {
+            let old_track_index = tracks.iter().position(|&id| id == old_track_id);
+
+            if let Some(old_track_index) = old_track_index {
+                if !tracks.contains(&new_track_id) {
+                    tracks[old_track_index] = new_track_id;
+
+                    if let Some(label) = labels.remove(&old_track_id) {
+                        labels.insert(new_track_id, label);
+                    }
+                }
+            }
+        }
+
+        LabelTrack(track_id, label) => {
+            if tracks.contains(track_id) {
+                labels.insert(*track_id, label.to_string());
+            }
+        }
+
+        Legend => {
+            for (i, track_id) in tracks.iter().enumerate() {
+                if let Some(label) = labels.get(track_id) {
+                    let label = Cow::Owned(label.clone());
+
+                    let mut row = Row::from_rails(i, Some(label), |_| Glyph::Rail);
+                    row.aligned = false;
+
+                    rows.push(row);
+                }
+            }
+        }
+
+        NoEvent => {
+            rows.push(Row::rails(tracks.len(), None));
+        }
+    }
+
+    // Remove the labels of stopped tracks
+    labels.retain(|track_id, _| tracks.contains(track_id));
+}
+
+/// Renders the rows of a station, where the rails of `track_ids`
+/// are rendered as `glyph` in the first row.
+fn station<'e>(
+    rows: &mut Vec<Row<'e>>,
+    tracks: &[TrackId],
+    track_ids: &[TrackId],
+    station_name: &'e str,
+    glyph: Glyph,
+) {
+    let detached = !tracks.iter().any(|track_id| track_ids.contains(track_id));
+
+    for (i, station_name) in station_name.lines().enumerate() {
+        let mut row = Row::rails(tracks.len(), Some(station_name));
+        row.detached = detached;
+
+        if i == 0 {
+            for (track_index, track_id) in tracks.iter().enumerate() {
+                if track_ids.contains(track_id) {
+                    row.cells[track_index * 2] = glyph;
+                }
+            }
+        }
+
+        rows.push(row);
+    }
+}
+
+/// Renders `event` if it starts or splits tracks, such that the new
+/// tracks are placed at their position sorted by track id. Returns
+/// `false` if `event` does not start or split tracks.
+///
+/// *[See `Options::sort_tracks`.][`Options::sort_tracks`]*
+///
+/// [`Options::sort_tracks`]: struct.Options.html#structfield.sort_tracks
+fn render_sorted<'e>(rows: &mut Vec<Row<'e>>, tracks: &mut Vec<TrackId>, event: &'e Event) -> bool {
+    use Event::*;
+    match event {
+        &StartTrack(track_id) | &StartTrackAt(track_id, _) => {
+            start_tracks_sorted(rows, tracks, slice::from_ref(&track_id))
+        }
+
+        &StartTracks(track_ids) => start_tracks_sorted(rows, tracks, track_ids),
+
+        &SplitTrack(from_track_id, new_track_id)
+        | &SplitTrackAt(from_track_id, new_track_id, _) => {
+            split_track_sorted(rows, tracks, from_track_id, new_track_id)
+        }
+
+        SplitStation(from_track_id, new_track_id, station_name) => {
+            split_track_sorted(rows, tracks, *from_track_id, *new_track_id);
+            station(
+                rows,
+                tracks,
+                slice::from_ref(new_track_id),
+                station_name,
+                Glyph::Station,
+            );
+        }
+
+        SplitTrackN(from_track_id, new_track_ids) => {
+            let mut new_tracks = Vec::with_capacity(new_track_ids.len());
+            for &new_track_id in new_track_ids.iter() {
+                if !tracks.contains(&new_track_id) && !new_tracks.contains(&new_track_id) {
+                    new_tracks.push(new_track_id);
+                }
+            }
+            new_tracks.sort();
+
+            let from_track_index = tracks.iter().position(|id| id == from_track_id);
+
+            // The new tracks only fan out within a single row, if
+            // they are all sorted right after `from_track_id`
+            let fan = from_track_index.is_some_and(|from_track_index| {
+                new_tracks
+                    .iter()
+                    .all(|&id| sorted_index(tracks, id) == (from_track_index + 1))
+            });
+
+            if fan {
+                split_tracks(rows, tracks, *from_track_id, &new_tracks);
+            } else {
+                for new_track_id in new_tracks {
+                    split_track_sorted(rows, tracks, *from_track_id, new_track_id);
+                }
+            }
+        }
+
+        _ => return false,
+    }
+
+    true
+}
+
+/// Returns the index of the first track with a track id greater than
+/// `track_id`, i.e. where `track_id` is inserted to keep `tracks` sorted.
+#[inline]
+fn sorted_index(tracks: &[TrackId], track_id: TrackId) -> usize {
+    tracks
+        .iter()
+        .position(|&id| id > track_id)
+        .unwrap_or(tracks.len())
+}
+
+/// Starts the tracks of `track_ids` that do not exist, placing
+/// them at their position sorted by track id.
+fn start_tracks_sorted(rows: &mut Vec<Row>, tracks: &mut Vec<TrackId>, track_ids: &[TrackId]) {
+    let mut next = tracks.clone();
+    for &track_id in track_ids {
+        if !next.contains(&track_id) {
+            next.insert(sorted_index(&next, track_id), track_id);
+        }
+    }
+
+    if next.len() == tracks.len() {
+        return;
+    }
+
+    // The rails are pushed to the right to make space for the new tracks
+    let mut rails = tracks
+        .iter()
+        .enumerate()
+        .map(|(i, id)| {
+            // Safe to use `unwrap` as `next` contains all ids of `tracks`
+            let target = next.iter().position(|other| other == id).unwrap();
+            Rail::new(i, target, false)
+        })
+        .collect::<Vec<_>>();
+
+    render_rails(rows, &mut rails);
+
+    *tracks = next;
+
+    rows.push(Row::rails(tracks.len(), None));
+}
+
+/// Splits `new_track_id` from `from_track_id`, placing it at its
+/// position sorted by track id, unless `new_track_id` already exists.
+fn split_track_sorted(
+    rows: &mut Vec<Row>,
+    tracks: &mut Vec<TrackId>,
+    from_track_id: TrackId,
+    new_track_id: TrackId,
+) {
+    if tracks.contains(&new_track_id) {
+        return;
+    }
+
+    let index = sorted_index(tracks, new_track_id);
+
+    match tracks.iter().position(|&id| id == from_track_id) {
+        Some(from_track_index) => {
+            split_track_to(rows, tracks, from_track_index, new_track_id, index)
+        }
+        None => start_track_at(rows, tracks, new_track_id, index),
+    }
+}
+
+fn start_track_at(
+    rows: &mut Vec<Row>,
+    tracks: &mut Vec<TrackId>,
+    track_id: TrackId,
+    column_index: usize,
+) {
+    if tracks.contains(&track_id) {
+        return;
+    }
+
+    let column_index = column_index.min(tracks.len());
+
+    if column_index < tracks.len() {
+        // The rails at and after `column_index` are pushed to the right
+        let mut rails = (0..tracks.len())
+            .map(|i| {
+                let target = if i >= column_index { i + 1 } else { i };
+                Rail::new(i, target, false)
+            })
+            .collect::<Vec<_>>();
+
+        render_rails(rows, &mut rails);
+    }
+
+    tracks.insert(column_index, track_id);
+
+    rows.push(Row::rails(tracks.len(), None));
+}
+
+fn split_track(
+    rows: &mut Vec<Row>,
+    tracks: &mut Vec<TrackId>,
+    from_track_id: TrackId,
+    new_track_id: TrackId,
+) {
+    if !tracks.contains(&new_track_id) {
+        let from_track_index = tracks.iter().position(|&id| id == from_track_id);
+
+        if let Some(from_track_index) = from_track_index {
+            // The rails to the right of `from_track_id` are pushed to the right
+            let mut row = Row::from_rails(tracks.len(), None, |i| {
+                if i <= from_track_index {
+                    Glyph::Rail
+                } else {
+                    Glyph::Space
+                }
+            });
+            row.cells.resize(tracks.len() * 2, Glyph::Space);
+            for gap in from_track_index..tracks.len() {
+                row.cells[gap * 2 + 1] = Glyph::DiagonalRight;
+            }
+
+            rows.push(row);
+
+            tracks.insert(from_track_index + 1, new_track_id);
+        } else {
+            tracks.push(new_track_id);
+
+            rows.push(Row::rails(tracks.len(), None));
+        }
+    }
+}
+
+/// Splits `new_track_id` from the track at `from_track_index`, and
+/// places it at `index`, i.e. the index of the new track after the split.
+fn split_track_to(
+    rows: &mut Vec<Row>,
+    tracks: &mut Vec<TrackId>,
+    from_track_index: usize,
+    new_track_id: TrackId,
+    index: usize,
+) {
+    if index > from_track_index + 1 {
+        // The new rail moves from `from_track_id` to the right below the
+        // rails in between, while the rails at and after `index` are
+        // pushed to the right to make space for it
+        let mut rails = (0..tracks.len())
+            .map(|i| {
+                let target = if i >= index { i + 1 } else { i };
+                Rail::new(i, target, false)
+            })
+            .collect::<Vec<_>>();
+        rails.push(Rail::new(from_track_index, index, false));
+
+        render_rails(rows, &mut rails);
+
+        tracks.insert(index, new_track_id);
+    } else {
+        // The new rail first diverges to the right of `from_track_id`,
+        // after which it moves to the left below the rails in between
+        let from_track_id = tracks[from_track_index];
+        split_track(rows, tracks, from_track_id, new_track_id);
+
+        let mut next = tracks.clone();
+        next.retain(|&id| id != new_track_id);
+        next.insert(index, new_track_id);
+
+        let mut rails = tracks
+            .iter()
+            .enumerate()
+            .map(|(i, id)| {
+                // Safe to use `unwrap` as `next` contains the same ids as `tracks`
+                let target = next.iter().position(|other| other == id).unwrap();
+                Rail::new(i, target, false)
+            })
+            .collect::<Vec<_>>();
+
+        render_rails(rows, &mut rails);
+
+        *tracks = next;
+    }
+}
+
+fn split_tracks(
+    rows: &mut Vec<Row>,
+    tracks: &mut Vec<TrackId>,
+    from_track_id: TrackId,
+    new_track_ids: &[TrackId],
+) {
+    let mut new_tracks = Vec::with_capacity(new_track_ids.len());
+    for &new_track_id in new_track_ids {
+        if !tracks.contains(&new_track_id) && !new_tracks.contains(&new_track_id) {
+            new_tracks.push(new_track_id);
+        }
+    }
+
+    if new_tracks.is_empty() {
+        return;
+    }
+
+    let from_track_index = match tracks.iter().position(|&id| id == from_track_id) {
+        Some(from_track_index) => from_track_index,
+        None => {
+            tracks.extend(new_tracks);
+
+            rows.push(Row::rails(tracks.len(), None));
+            return;
+        }
+    };
+
+    let n = new_tracks.len();
+    let right_tracks = (from_track_index + 1)..tracks.len();
+
+    // The rails to the right of `from_track_id` are pushed one column
+    // per row, such that the last row has space for all new tracks
+    let first_shift = if right_tracks.is_empty() { n - 1 } else { 0 };
+
+    for shift in first_shift..n {
+        let mut row = Row::rails(from_track_index + 1, None);
+        row.cells.resize((tracks.len() + shift) * 2, Glyph::Space);
+
+        for column in right_tracks.clone() {
+            row.cells[(column + shift) * 2 + 1] = Glyph::DiagonalRight;
+        }
+
+        if shift + 1 == n {
+            // The new tracks fan out from `from_track_id`
+            row.cells[from_track_index * 2 + 1] = Glyph::DiagonalRight;
+
+            for column in (from_track_index + 1)..(from_track_index + n) {
+                row.cells[column * 2] = Glyph::Horizontal;
+                row.cells[column * 2 + 1] = Glyph::DiagonalRight;
+            }
+        }
+
+        rows.push(row);
+    }
+
+    tracks.splice(right_tracks.start..right_tracks.start, new_tracks);
+}
+
+#[inline]
+fn stop_track(rows: &mut Vec<Row>, tracks: &mut Vec<TrackId>, track_id: TrackId) {
+    stop_tracks(rows, tracks, |id| id == track_id)
+}
+
+/// Appends `text` to `line`, where `column` is the column `text`
+/// starts at. Tabs are expanded to spaces, `\r\n` is treated like `\n`,
+/// and other control characters are escaped, e.g. `\u{1b}`, such that
+/// they cannot corrupt the rendered graph.
+///
+/// Lines wider than `max_width` columns are truncated, ending with `…`.
+pub(crate) fn push_text(
+    line: &mut String,
+    text: &str,
+    mut column: usize,
+    max_width: Option<usize>,
+) {
+    let max_width = max_width.unwrap_or(usize::MAX);
+
+    // The end of `line`, where an ellipsis still fits
+    let mut fit = line.len();
+    let mut truncated = false;
+
+    let mut chars = text.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if c == '\n' {
+            line.push('\n');
+            column = 0;
+            fit = line.len();
+            truncated = false;
+            continue;
+        } else if truncated {
+            continue;
+        }
+
+        if column < max_width {
+            fit = line.len();
+        }
+
+        match c {
+            '\r' if chars.peek() == Some(&'\n') => {}
+            '\t' => {
+                let width = char_width(c, column);
+                line.extend(iter::repeat_n(' ', width));
+                column += width;
+            }
+            c if c.is_control() => {
+                for c in c.escape_default() {
+                    line.push(c);
+                    column += 1;
+                }
+            }
+            c => {
+                line.push(c);
+                column += char_width(c, column);
+            }
+        }
+
+        if column > max_width {
+            line.truncate(fit);
+            line.push('…');
+            truncated = true;
+        }
+    }
+}
+
+/// Returns `true` if `cells` are only rails separated by spaces.
+fn is_rails(cells: &[Glyph]) -> bool {
+    cells.iter().enumerate().all(|(i, &cell)| {
+        if i % 2 == 0 {
+            cell == Glyph::Rail
+        } else {
+            cell == Glyph::Space
+        }
+    })
+}
+
+/// Compacts each split of the rightmost track immediately followed
+/// by a station on the new track into a single row, i.e. `|\*`.
+///
+/// *[See `Options::compact_splits`.][`Options::compact_splits`]*
+///
+/// [`Options::compact_splits`]: struct.Options.html#structfield.compact_splits
+fn compact_splits(rows: &mut Vec<Row>) {
+    let mut i = 1;
+
+    while i < rows.len() {
+        if rows[i - 1].is_rightmost_split() && rows[i].is_rightmost_station(&rows[i - 1]) {
+            let station = rows.remove(i);
+            let split = &mut rows[i - 1];
+
+            split.cells.extend(station.cells.last());
+            split.text = station.text;
+        }
+
+        i += 1;
+    }
+}
+
+/// Stops all `tracks` for which `stop` returns `true`.
+#[inline]
+fn stop_tracks<F>(rows: &mut Vec<Row>, tracks: &mut Vec<TrackId>, stop: F)
+where
+    F: Fn(TrackId) -> bool,
+{
+    stop_tracks_labeled(rows, tracks, None, stop)
+}
+
+/// Stops all `tracks` for which `stop` returns `true`, rendering the
+/// first line of `label` on the row where the tracks end, and any
+/// remaining lines after the rails have been pulled to the left.
+fn stop_tracks_labeled<'e, F>(
+    rows: &mut Vec<Row<'e>>,
+    tracks: &mut Vec<TrackId>,
+    label: Option<&'e str>,
+    stop: F,
+) where
+    F: Fn(TrackId) -> bool,
+{
+    if !tracks.iter().any(|&id| stop(id)) {
+        return;
+    }
+
+    let mut lines = label.map(str::lines).into_iter().flatten();
+
+    rows.push(Row::from_rails(
+        tracks.len(),
+        lines.next().map(Cow::Borrowed),
+        |i| {
+            if stop(tracks[i]) {
+                Glyph::Stop
+            } else {
+                Glyph::Rail
+            }
+        },
+    ));
+
+    let columns = (0..tracks.len()).filter(|&i| !stop(tracks[i])).collect();
+    collapse_rails(rows, columns);
+
+    tracks.retain(|&id| !stop(id));
+
+    for line in lines {
+        rows.push(Row::rails(tracks.len(), Some(line)));
+    }
+}
+
+/// Renders the rows needed to merge the rails at `from_indices` into
+/// the rail at `to_index`, followed by pulling the remaining rails
+/// to the left, to fill the gaps left behind by the merged rails.
+///
+/// Rails to the right of all the merging rails, are pulled to the left
+/// while merging. All other rails wait until the merging is done.
+fn merge_rails(rows: &mut Vec<Row>, track_count: usize, from_indices: &[usize], to_index: usize) {
+    // Rails to the right of all joined rails are pulled to the left right
+    // away, unless a rail is joined towards the right, as they would then
+    // collide with the rail at `to_index`
+    let bound = if from_indices.iter().all(|&i| i > to_index) {
+        from_indices.iter().copied().fold(to_index, usize::max)
+    } else {
+        usize::MAX
+    };
+
+    let mut next_index = 0;
+    let mut rails = (0..track_count)
+        .map(|i| {
+            if from_indices.contains(&i) {
+                Rail::new(i, to_index, true)
+            } else {
+                let target = if i > bound { next_index } else { i };
+                next_index += 1;
+                Rail::new(i, target, false)
+            }
+        })
+        .collect::<Vec<_>>();
+
+    render_rails(rows, &mut rails);
+
+    let columns = rails
+        .iter()
+        .filter(|rail| !rail.merge)
+        .map(|rail| rail.column)
+        .collect();
+
+    collapse_rails(rows, columns)
+}
+
+/// Replaces the straight rails of the `ghost_tracks` in `row` with `:`.
+fn ghost_rails(row: &mut Row, tracks: &[TrackId], ghost_tracks: &HashSet<TrackId>) {
+    let straight = row.cells.iter().all(|cell| {
+        matches!(
+            cell,
+            Glyph::Space | Glyph::Rail | Glyph::Station | Glyph::Marker(_)
+        )
+    });
+
+    if !straight || (row.cells.len() + 1 != tracks.len() * 2) {
+        return;
+    }
+
+    for (cell, track_id) in row.cells.iter_mut().step_by(2).zip(tracks) {
+        if (*cell == Glyph::Rail) && ghost_tracks.contains(track_id) {
+            *cell = Glyph::Marker(':');
+        }
+    }
+}
+
+/// Replaces the rail of the remaining track of the join of `event` in
+/// `rows`, where the joined rail meets it, with the `marker`, given
+/// the tracks `before` the event.
+///
+/// *[See `Options::merge_marker`.][`Options::merge_marker`]*
+///
+/// [`Options::merge_marker`]: struct.Options.html#structfield.merge_marker
+fn replace_merge_marker(rows: &mut [Row], before: &[TrackId], event: &Event, marker: char) {
+    use Event::*;
+    let (from_track_id, to_track_id) = match *event {
+        JoinTrack(from_track_id, to_track_id)
+        | JoinTrackToward(from_track_id, to_track_id)
+        | JoinTrackOrRename(from_track_id, to_track_id)
+        | JoinTrackKeepSource(from_track_id, to_track_id) => (from_track_id, to_track_id),
+        _ => return,
+    };
+
+    // A marker must be a single column wide,
+    // otherwise the rails would no longer align
+    if (from_track_id == to_track_id) || (char_width(marker, 0) != 1) {
+        return;
+    }
+
+    let from_track_index = before.iter().position(|&id| id == from_track_id);
+    let to_track_index = before.iter().position(|&id| id == to_track_id);
+
+    let (from_index, to_index) = match (from_track_index, to_track_index) {
+        (Some(from_index), Some(to_index)) => match event {
+            JoinTrackToward(..) => (from_index, to_index),
+            // The rails are always joined towards the leftmost rail
+            _ => (from_index.max(to_index), from_index.min(to_index)),
+        },
+        _ => return,
+    };
+
+    // The joined rail meets the remaining rail through the gap next to it
+    let (gap, diagonal) = if from_index > to_index {
+        (to_index * 2 + 1, Glyph::DiagonalLeft)
+    } else {
+        (to_index * 2 - 1, Glyph::DiagonalRight)
+    };
+
+    let junction = rows.iter_mut().rev().find(|row| {
+        (row.cells.get(gap) == Some(&diagonal))
+            && (row.cells.get(to_index * 2) == Some(&Glyph::Rail))
+    });
+
+    if let Some(row) = junction {
+        row.cells[to_index * 2] = Glyph::Marker(marker);
+    }
+}
+
+/// Renders the rows needed to pull the rails at `columns` to the left,
+/// such that no gaps remain between them.
+fn collapse_rails(rows: &mut Vec<Row>, columns: Vec<usize>) {
+    let mut rails = columns
+        .into_iter()
+        .enumerate()
+        .map(|(i, column)| Rail::new(column, i, false))
+        .collect::<Vec<_>>();
+
+    render_rails(rows, &mut rails)
+}
+
+/// A rail moved by [`render_rails`].
+///
+/// [`render_rails`]: fn.render_rails.html
+struct Rail {
+    column: usize,
+    /// The column the rail is moving towards.
+    target: usize,
+    /// Whether the rail merges into the rail at `target`.
+    merge: bool,
+}
+
+impl Rail {
+    #[inline]
+    fn new(column: usize, target: usize, merge: bool) -> Self {
+        Self {
+            column,
+            target,
+            merge,
+        }
+    }
+}
+
+/// Renders rows until all `rails` have reached their `target`.
+///
+/// In each row every rail moves towards its `target`. The first step of
+/// a rail is drawn as a diagonal, and any further steps are drawn as `_`
+/// along the bottom of the row. However, a merging rail always ends with
+/// a diagonal, i.e. `|/` instead of `|_`. Rails never cross the path of
+/// another rail moving in the same direction, instead they wait. Except
+/// when merging rails share the same `target`, then a merging rail follows
+/// the path of the previous one, such that they all merge in a single row,
+/// e.g. `|/_/_/`.
+fn render_rails(rows: &mut Vec<Row>, rails: &mut [Rail]) {
+    while rails.iter().any(|rail| rail.column != rail.target) {
+        // Rails moving right may extend past the rightmost rail,
+        // so the row must be wide enough for their targets as well
+        //
+        // Safe to use `unwrap` as `rails` is not empty
+        let width = rails
+            .iter()
+            .map(|rail| rail.column.max(rail.target))
+            .max()
+            .unwrap()
+            + 1;
+
+        // Even indices are rails, odd indices are the gaps between them
+        let mut row = vec![Glyph::Space; width * 2];
+
+        for rail in rails.iter() {
+            if rail.column == rail.target {
+                row[rail.column * 2] = Glyph::Rail;
+            }
+        }
+
+        let mut order = (0..rails.len()).collect::<Vec<_>>();
+        order.sort_by_key(|&i| rails[i].column);
+
+        // Rails moving left, from left to right
+        let mut prev_column = None;
+        // The `target` of the previous rail moving left, if it merged
+        let mut prev_merged = None;
+
+        for &i in order.iter() {
+            let rail = &mut rails[i];
+
+            if rail.target >= rail.column {
+                continue;
+            }
+
+            if rail.merge && (prev_merged == Some(rail.target)) {
+                // Follow the path of the previous merging rail
+                row[(rail.column - 1) * 2 + 1] = Glyph::DiagonalLeft;
+                for cell in &mut row[(rail.target * 2 + 2)..((rail.column - 1) * 2 + 1)] {
+                    if *cell == Glyph::Space {
+                        *cell = Glyph::Horizontal;
+                    }
+                }
+
+                prev_column = Some(rail.column);
+                rail.column = rail.target;
+                continue;
+            }
+
+            let mut end = if rail.merge && (rail.column - rail.target) >= 2 {
+                rail.target + 1
+            } else {
+                rail.target
+            };
+            if let Some(prev_column) = prev_column {
+                end = end.max(prev_column);
+            }
+            let end = end.min(rail.column - 1);
+
+            row[(rail.column - 1) * 2 + 1] = Glyph::DiagonalLeft;
+            for gap in end..(rail.column - 1) {
+                row[gap * 2 + 1] = Glyph::Horizontal;
+            }
+            for column in (end + 1)..rail.column {
+                if row[column * 2] == Glyph::Space {
+                    row[column * 2] = Glyph::Horizontal;
+                }
+            }
+
+            prev_column = Some(rail.column);
+            prev_merged = if rail.merge && (end == rail.target) {
+                Some(rail.target)
+            } else {
+                None
+            };
+            rail.column = end;
+        }
+
+        // Rails moving right, from right to left
+        let mut next_column = None;
+
+        for &i in order.iter().rev() {
+            let rail = &mut rails[i];
+
+            if rail.target <= rail.column {
+                continue;
+            }
+
+            if row[rail.column * 2 + 1] != Glyph::Space {
+                // Wait for the crossing rail to pass
+                row[rail.column * 2] = Glyph::Rail;
+                continue;
+            }
+
+            let mut end = if rail.merge && (rail.target - rail.column) >= 2 {
+                rail.target - 1
+            } else {
+                rail.target
+            };
+            if let Some(next_column) = next_column {
+                end = end.min(next_column);
+            }
+            let end = end.max(rail.column + 1);
+
+            row[rail.column * 2 + 1] = Glyph::DiagonalRight;
+
+            let mut column = rail.column + 1;
+            while column < end && row[column * 2 + 1] == Glyph::Space {
+                row[column * 2 + 1] = Glyph::Horizontal;
+                if row[column * 2] == Glyph::Space {
+                    row[column * 2] = Glyph::Horizontal;
+                }
+                column += 1;
+            }
+
+            next_column = Some(rail.column);
+            rail.column = column;
+        }
+
+        // Remove the trailing gaps
+        while row.last() == Some(&Glyph::Space) {
+            row.pop();
+        }
+
+        rows.push(Row {
+            cells: row,
+            text: None,
+            aligned: true,
+            header: Vec::new(),
+            detached: false,
+        });
+    }
+}
+
+/// Write `&[`[`Event`]`]` to [`Vec<u8>`].
+/// Defines a default track with `track_id` of `0`.
+///
+/// *[See also `Metro::to_vec`.][`Metro::to_vec`]*
+///
+/// *See also [`to_string`] and [`to_writer`].*
+///
+/// [`to_writer`]: fn.to_writer.html
+/// [`to_string`]: fn.to_string.html
+///
+/// [`Event`]: enum.Event.html
+///
+/// [`Metro::to_vec`]: struct.Metro.html#method.to_vec
+///
+/// [`Vec<u8>`]: https://doc.rust-lang.org/stable/std/vec/struct.Vec.html
+#[inline]
+pub fn to_vec(events: &[Event]) -> io::Result<Vec<u8>> {
+    to_vec_with(events, &Options::default())
+}
+
+/// Write `&[`[`Event`]`]` to [`Vec<u8>`], using [`Options`].
+/// Defines the tracks of [`Options::initial_tracks`], by default
+/// a track with `track_id` of `0`.
+///
+/// *[See also `Metro::to_vec_with`.][`Metro::to_vec_with`]*
+///
+/// *See also [`to_string_with`] and [`to_writer_with`].*
+///
+/// [`to_writer_with`]: fn.to_writer_with.html
+/// [`to_string_with`]: fn.to_string_with.html
+///
+/// [`Event`]: enum.Event.html
+/// [`Options`]: struct.Options.html
+/// [`Options::initial_tracks`]: struct.Options.html#structfield.initial_tracks
+///
+/// [`Metro::to_vec_with`]: struct.Metro.html#method.to_vec_with
+///
+/// [`Vec<u8>`]: https://doc.rust-lang.org/stable/std/vec/struct.Vec.html
+#[inline]
+pub fn to_vec_with(events: &[Event], opts: &Options) -> io::Result<Vec<u8>> {
+    let mut vec = Vec::with_capacity(estimate_capacity(events, opts));
+    to_writer_with(&mut vec, events, opts)?;
+    Ok(vec)
+}
+
+/// Estimates the number of bytes needed for rendering `events`,
+/// to avoid reallocating repeatedly while writing the rows.
+///
+/// It assumes that each [`Event`] produces a single row, plus a
+/// row per additional line of text, where the width of each row
+/// is derived from the approximate number of current tracks.
+///
+/// [`Event`]: enum.Event.html
+fn estimate_capacity(events: &[Event], opts: &Options) -> usize {
+    let rail_width = opts.rail_spacing.max(1) + 1;
+
+    let mut track_count = 1usize;
+    let mut capacity = 0;
+
+    for event in events {
+        use Event::*;
+        let text = match event {
+            StartTrack(_) | StartTrackAt(..) | SplitTrack(..) | SplitTrackAt(..) => {
+                track_count += 1;
+                None
+            }
+            StartTracks(track_ids) => {
+                track_count += track_ids.len();
+                None
+            }
+            SplitTrackN(_, track_ids) => {
+                track_count += track_ids.len();
+                None
+            }
+            StopTrack(_)
+            | JoinTrack(..)
+            | JoinTrackToward(..)
+            | JoinTrackKeepSource(..)
+            | JoinTrackOrRename(..) => {
+                track_count = track_count.saturating_sub(1);
+                None
+            }
+            StopTracks(track_ids) | JoinTracks(track_ids, _) => {
+                track_count = track_count.saturating_sub(track_ids.len());
+                None
+            }
+            StopTrackLabeled(_, text) => {
+                track_count = track_count.saturating_sub(1);
+                Some(text)
+            }
+            SplitStation(_, _, text) => {
+                track_count += 1;
+                Some(text)
+            }
+            Station(_, text)
+            | StationMarked(_, text, _)
+            | StationMulti(_, text)
+            | DetachedStation(text)
+            | Annotation(text)
+            | LabelTrack(_, text) => Some(text),
+            Link(..) | RenameTrack(..) | Legend | NoEvent => None,
+        };
+
+        let row_width = track_count * rail_width + 1;
+
+        capacity += match text {
+            Some(text) => text.lines().count().max(1) * row_width + text.len(),
+            None => row_width,
+        };
+    }
+
+    capacity
+}
+
+/// Write `&[`[`Event`]`]` to [`String`].
+/// Defines a default track with `track_id` of `0`.
+///
+/// *[See also `Metro::to_string`.][`Metro::to_string`]*
+///
+/// *See also [`to_vec`] and [`to_writer`].*
+///
+/// [`to_writer`]: fn.to_writer.html
+/// [`to_vec`]: fn.to_vec.html
+///
+/// [`Event`]: enum.Event.html
+///
+/// [`Metro::to_string`]: struct.Metro.html#method.to_string
+///
+/// [`String`]: https://doc.rust-lang.org/stable/std/string/struct.String.html
+#[inline]
+pub fn to_string(events: &[Event]) -> io::Result<String> {
+    to_string_with(events, &Options::default())
+}
+
+/// Write `&[`[`Event`]`]` to [`String`], using [`Options`].
+/// Defines the tracks of [`Options::initial_tracks`], by default
+/// a track with `track_id` of `0`.
+///
+/// *[See also `Metro::to_string_with`.][`Metro::to_string_with`]*
+///
+/// *See also [`to_vec_with`] and [`to_writer_with`].*
+///
+/// [`to_writer_with`]: fn.to_writer_with.html
+/// [`to_vec_with`]: fn.to_vec_with.html
+///
+/// [`Event`]: enum.Event.html
+/// [`Options`]: struct.Options.html
+/// [`Options::initial_tracks`]: struct.Options.html#structfield.initial_tracks
+///
+/// [`Metro::to_string_with`]: struct.Metro.html#method.to_string_with
+///
+/// [`String`]: https://doc.rust-lang.org/stable/std/string/struct.String.html
+///
+/// # Example
+///
+/// ```
+/// use metro::{Direction, Event, Options, TrackId};
+///
+/// let events = [
+///     Event::station(0, "Station 1"),
+///     Event::SplitTrack(TrackId(0), TrackId(1)),
+///     Event::station(1, "Station 2"),
+///     Event::JoinTrack(TrackId(1), TrackId(0)),
+///     Event::station(0, "Station 3"),
+/// ];
+///
+/// let opts = Options {
+///     direction: Direction::RightToLeft,
+///     ..Options::default()
+/// };
+///
+/// let string = metro::to_string_with(&events, &opts).unwrap();
+///
+/// assert_eq!(
+///     string,
+///     "  * Station 1\n /|\n* | Station 2\n \\|\n  * Station 3\n"
+/// );
+/// ```
+#[inline]
+pub fn to_string_with(events: &[Event], opts: &Options) -> io::Result<String> {
+    let mut string = String::with_capacity(estimate_capacity(events, opts));
+    let mut sink = StringSink(&mut string);
+    let mut core = RenderCore::new(opts.clone());
+
+    for event in events {
+        core.push(&mut sink, event)?;
+    }
+
+    core.finish(&mut sink)?;
+
+    Ok(string)
+}
+
+/// Write `&[`[`Event`]`]` to [`String`], preceded by the `title`
+/// and a rule of `-` underlining it.
+/// Defines a default track with `track_id` of `0`.
+///
+/// The rule is as wide as the widest line of the rendered events,
+/// i.e. the width returned by [`measure`], or the `title` if it
+/// is wider.
+///
+/// [`measure`]: fn.measure.html
+///
+/// [`Event`]: enum.Event.html
+///
+/// [`String`]: https://doc.rust-lang.org/stable/std/string/struct.String.html
+///
+/// # Example
+///
+/// ```
+/// use metro::{Event, TrackId};
+///
+/// let events = [
+///     Event::station(0, "Station 1"),
+///     Event::SplitTrack(TrackId(0), TrackId(1)),
+///     Event::station(1, "Station 2"),
+/// ];
+///
+/// let string = metro::to_string_titled("Line 1", &events).unwrap();
+///
+/// assert_eq!(
+///     string,
+///     "Line 1\n-------------\n* Station 1\n|\\\n| * Station 2\n"
+/// );
+/// ```
+pub fn to_string_titled(title: &str, events: &[Event]) -> io::Result<String> {
+    let graph = to_string(events)?;
+    let width = text_width(title).max(text_width(&graph));
+
+    let mut string = String::with_capacity(title.len() + width + graph.len() + 2);
+    string.push_str(title);
+    string.push('\n');
+    string.extend(iter::repeat_n('-', width));
+    string.push('\n');
+    string.push_str(&graph);
+
+    Ok(string)
+}
+
+/// Render `&[`[`Event`]`]` into [`Row`]s, i.e. a structured description
+/// of each rendered line, instead of text.
+/// Defines a default track with `track_id` of `0`.
+///
+/// Each [`Row`] corresponds to a line produced by [`to_string`], and
+/// [`Glyph::to_char`] gives the `char` of each cell. The rows are always
+/// left-to-right, i.e. [`Options`] are not applied.
+///
+/// *See also [`to_string`] and [`lines`].*
+///
+/// [`to_string`]: fn.to_string.html
+/// [`lines`]: fn.lines.html
+///
+/// [`Event`]: enum.Event.html
+/// [`Row`]: struct.Row.html
+/// [`Glyph::to_char`]: enum.Glyph.html#method.to_char
+/// [`Options`]: struct.Options.html
+///
+/// # Example
+///
+/// ```
+/// use metro::{Event, Glyph, TrackId};
+///
+/// let events = [
+///     Event::station(0, "Station 1"),
+///     Event::SplitTrack(TrackId(0), TrackId(1)),
+/// ];
+///
+/// let rows = metro::to_rows(&events);
+///
+/// assert_eq!(rows[0].cells(), [Glyph::Station]);
+/// assert_eq!(rows[0].text(), Some("Station 1"));
+///
+/// assert_eq!(rows[1].cells(), [Glyph::Rail, Glyph::DiagonalRight]);
+/// assert_eq!(rows[1].text(), None);
+/// ```
+pub fn to_rows<'a>(events: &'a [Event]) -> Vec<Row<'a>> {
+    let mut state = RenderState::new();
+    let mut rows = Vec::new();
+
+    for event in events {
+        render_event(&mut rows, &mut state, event);
+    }
+
+    rows
+}
+
+/// Returns an iterator over the rendered lines of `&[`[`Event`]`]`.
+/// Defines a default track with `track_id` of `0`.
+///
+/// The lines are rendered lazily, one [`Event`] at a time, so only
+/// the lines that are consumed are rendered. The yielded lines do
+/// not include the line ending.
+///
+/// Note that a single [`Event`] can produce multiple lines, e.g. a
+/// `Station` with multiple lines of text, or a `JoinTrack`.
+///
+/// *See also [`to_string`] and [`to_writer`].*
+///
+/// [`to_writer`]: fn.to_writer.html
+/// [`to_string`]: fn.to_string.html
+///
+/// [`Event`]: enum.Event.html
+///
+/// # Example
+///
+/// ```
+/// use metro::Event::*;
+/// use metro::TrackId;
+///
+/// let events = [
+///     StartTracks(&[TrackId(0), TrackId(1), TrackId(2)]),
+///     JoinTrack(TrackId(1), TrackId(0)),
+///     NoEvent,
+/// ];
+///
+/// let lines = metro::lines(&events).collect::<Vec<_>>();
+///
+/// assert_eq!(lines, ["| | |", "|/ /", "| |"]);
+/// ```
+#[inline]
+pub fn lines<'a>(events: &'a [Event<'a>]) -> Lines<'a> {
+    Lines {
+        events: events.iter(),
+        state: RenderState::new(),
+        rows: Vec::new(),
+        lines: VecDeque::new(),
+    }
+}
+
+/// An iterator over the rendered lines of `&[`[`Event`]`]`.
+///
+/// This `struct` is created by [`lines`].
+///
+/// *[See `lines` for more information.][`lines`]*
+///
+/// [`lines`]: fn.lines.html
+/// [`Event`]: enum.Event.html
+#[derive(Debug)]
+pub struct Lines<'a> {
+    events: slice::Iter<'a, Event<'a>>,
+    state: RenderState,
+    /// Reused buffer for rendering a single event.
+    rows: Vec<Row<'a>>,
+    /// Rendered lines of the last event, which have not been yielded yet.
+    lines: VecDeque<String>,
+}
+
+impl Iterator for Lines<'_> {
+    type Item = String;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.lines.is_empty() {
+            let event = self.events.next()?;
+
+            render_event(&mut self.rows, &mut self.state, event);
+
+            for row in self.rows.drain(..) {
+                // Labels can contain multiple lines
+                let row = row.to_line(&Options::default());
+                self.lines.extend(row.lines().map(String::from));
+            }
+        }
+
+        self.lines.pop_front()
+    }
+}
+
+/// Returns the `(width, height)` of the rendered `&[`[`Event`]`]`,
+/// without allocating the rendered output.
+/// Defines a default track with `track_id` of `0`.
+///
+/// The `width` is the display width of the longest line, and the
+/// `height` is the number of lines. The line ending is not included
+/// in the `width`. Tabs advance to the next multiple of 8 columns.
+///
+/// By default every other [`char`] is counted as a single column.
+/// Enable the `unicode-width` feature to count wide characters,
+/// e.g. `"こんにちは"`, as two columns, and zero-width characters
+/// as zero columns.
+///
+/// [`Event`]: enum.Event.html
+///
+/// [`char`]: https://doc.rust-lang.org/stable/std/primitive.char.html
+///
+/// # Example
+///
+/// ```
+/// use metro::Event::*;
+/// use metro::TrackId;
+///
+/// let events = [
+///     StartTracks(&[TrackId(0), TrackId(1), TrackId(2)]),
+///     JoinTrack(TrackId(1), TrackId(0)),
+///     NoEvent,
+/// ];
+///
+/// // | | |
+/// // |/ /
+/// // | |
+/// assert_eq!(metro::measure(&events), (5, 3));
+/// ```
+pub fn measure(events: &[Event]) -> (usize, usize) {
+    let mut measure = MeasureWriter::default();
+
+    // Writing to a `MeasureWriter` never fails, so it is safe to `unwrap`
+    to_writer(&mut measure, events).unwrap();
+
+    (measure.width, measure.height)
+}
+
+/// Measures the written lines instead of storing them.
+#[derive(Default)]
+struct MeasureWriter {
+    width: usize,
+    height: usize,
+    /// Width of the current line.
+    current: usize,
+}
+
+impl Write for MeasureWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        // Metro only writes whole `str`s, so `buf` is always valid UTF-8
+        let s =
+            str::from_utf8(buf).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+
+        for c in s.chars() {
+            if c == '\n' {
+                self.width = self.width.max(self.current);
+                self.height += 1;
+                self.current = 0;
+            } else {
+                self.current += char_width(c, self.current);
+            }
+        }
+
+        Ok(buf.len())
+    }
+
+    #[inline]
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/*
+/// `Error` is an error that can be returned by
+/// [`to_string`], [`to_vec`], and [`to_writer`].
+///
+/// [`to_writer`]: fn.to_writer.html
+/// [`to_vec`]: fn.to_vec.html
+/// [`to_string`]: fn.to_string.html
+#[derive(Debug)]
+pub enum Error {
+    /// [See `std::io::Error`][io::Error].
+    ///
+    /// [io::Error]: https://doc.rust-lang.org/std/io/struct.Error.html
+    IoError(io::Error),
+
+    /// [See `std::string::FromUtf8Error`][FromUtf8Error].
+    ///
+    /// [FromUtf8Error]: https://doc.rust-lang.org/std/string/struct.FromUtf8Error.html
+    FromUtf8Error(FromUtf8Error),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        use Error::*;
+        match self {
+            IoError(err) => err.fmt(fmt),
+            FromUtf8Error(err) => err.fmt(fmt),
+        }
+    }
+}
+
+impl error::Error for Error {
+    #[inline]
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        use Error::*;
+        match self {
+            IoError(ref err) => Some(err),
+            FromUtf8Error(ref err) => Some(err),
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    #[inline]
+    fn from(err: io::Error) -> Self {
+        Self::IoError(err)
+    }
+}
+
+impl From<FromUtf8Error> for Error {
+    #[inline]
+    fn from(err: FromUtf8Error) -> Self {
+        Self::FromUtf8Error(err)
+    }
+}
+// */
+
+#[cfg(test)]
+mod tests {
+    use crate::TrackId;
+    use std::borrow::Cow;
+    use std::io::Write;
+
+    use super::Event::{self, *};
+    use super::{
+        lines, measure, to_fmt_writer_with, to_rows, to_string, to_string_with, to_vec_with,
+        to_writer, to_writer_with, EventKind, Renderer,
+    };
+    use crate::{Direction, LineEnding, Options, UnknownTrackPolicy};
+
+    #[test]
+    fn start_track() {
+        let events = [StartTrack(TrackId(1))];
+        let string = to_string(&events).unwrap();
+
+        assert_eq!(string, "| |\n");
+    }
+
+    #[test]
+    fn start_track_already_exists() {
+        let events = [StartTrack(TrackId(0))];
+        let string = to_string(&events).unwrap();
+
+        assert_eq!(string, "");
+    }
+
+    #[test]
+    fn start_track_already_exists2() {
+        #[rustfmt::skip]
+        let events = [
+            StartTrack(TrackId(1)),
+            StartTrack(TrackId(2)),
+            StartTrack(TrackId(1)),
+            StartTrack(TrackId(2)),
+        ];
+        let string = to_string(&events).unwrap();
+
+        assert_eq!(string, "| |\n| | |\n");
+    }
+
+    #[test]
+    fn start_track_default() {
+        let events = [StartTrack(TrackId(0))];
+        let string = to_string(&events).unwrap();
+
+        assert_eq!(string, "");
+    }
+
+    #[test]
+    fn event_start_track_default2() {
+        let events = [StartTrack(TrackId(1))];
+        let string = to_string(&events).unwrap();
+
+        assert_eq!(string, "| |\n");
+    }
+
+    #[test]
+    fn event_start_tracks() {
+        let events = [StartTracks(&[TrackId(1), TrackId(2), TrackId(3)])];
+        let string = to_string(&events).unwrap();
+
+        assert_eq!(string, "| | | |\n");
+    }
+
+    #[test]
+    fn start_tracks_some_already_exist() {
+        let events = [StartTracks(&[TrackId(0), TrackId(1), TrackId(2)])];
+        let string = to_string(&events).unwrap();
+
+        assert_eq!(string, "| | |\n");
+    }
+
+    #[test]
+    fn start_tracks_all_already_exist() {
+        let events = [StartTracks(&[TrackId(0), TrackId(0), TrackId(0)])];
+        let string = to_string(&events).unwrap();
+
+        assert_eq!(string, "");
+    }
+
+    #[test]
+    fn stop_track_labeled() {
+        let events = [
+            StartTracks(&[TrackId(0), TrackId(1), TrackId(2)]),
+            Event::stop_track_labeled(1, "Deleted\nYesterday"),
+            Event::stop_track_labeled(5, "Unknown"),
+        ];
+        let string = to_string(&events).unwrap();
+
+        assert_eq!(
+            string,
+            r#"| | |
+| " | Deleted
+|  /
+| | Yesterday
+"#
+        );
+    }
+
+    #[test]
+    fn stop_track() {
+        let events = [StopTrack(TrackId(0))];
+        let string = to_string(&events).unwrap();
+
+        assert_eq!(string, "\"\n");
+    }
+
+    #[test]
+    fn stop_track_does_not_exist() {
+        let events = [StopTrack(TrackId(1))];
+        let string = to_string(&events).unwrap();
+
+        assert_eq!(string, "");
+    }
+
+    #[test]
+    fn stop_track_left() {
+        #[rustfmt::skip]
+        let events = [
+            StartTracks(&[TrackId(0), TrackId(1), TrackId(2), TrackId(3), TrackId(4)]),
+            StopTrack(TrackId(0)),
+            NoEvent,
+        ];
+        let string = to_string(&events).unwrap();
+
+        assert_eq!(
+            string,
+            r#"| | | | |
+" | | | |
+ / / / /
+| | | |
+"#
+        );
+    }
+
+    #[test]
+    fn stop_track_middle() {
+        #[rustfmt::skip]
+        let events = [
+            StartTracks(&[TrackId(0), TrackId(1), TrackId(2), TrackId(3), TrackId(4)]),
+            StopTrack(TrackId(2)),
+            NoEvent,
+        ];
+        let string = to_string(&events).unwrap();
+
+        assert_eq!(
+            string,
+            r#"| | | | |
+| | " | |
+| |  / /
+| | | |
+"#
+        );
+    }
+
+    #[test]
+    fn stop_track_right() {
+        #[rustfmt::skip]
+        let events = [
+            StartTracks(&[TrackId(0), TrackId(1), TrackId(2), TrackId(3), TrackId(4)]),
+            StopTrack(TrackId(4)),
+            NoEvent,
+        ];
+        let string = to_string(&events).unwrap();
+
+        assert_eq!(
+            string,
+            r#"| | | | |
+| | | | "
+| | | |
+"#
+        );
+    }
+
+    #[test]
+    fn stop_tracks() {
+        #[rustfmt::skip]
+        let events = [
+            StartTracks(&[TrackId(0), TrackId(1), TrackId(2), TrackId(3), TrackId(4)]),
+            StopTracks(&[TrackId(1), TrackId(3)]),
+            NoEvent,
+        ];
+        let string = to_string(&events).unwrap();
+
+        assert_eq!(
+            string,
+            r#"| | | | |
+| " | " |
+|  / __/
+| | |
+"#
+        );
+    }
+
+    #[test]
+    fn stop_tracks_adjacent() {
+        #[rustfmt::skip]
+        let events = [
+            StartTracks(&[TrackId(0), TrackId(1), TrackId(2), TrackId(3), TrackId(4)]),
+            StopTracks(&[TrackId(1), TrackId(2)]),
+            NoEvent,
+        ];
+        let string = to_string(&events).unwrap();
+
+        assert_eq!(
+            string,
+            r#"| | | | |
+| " " | |
+|  __/ /
+| |  /
+| | |
+"#
+        );
+    }
+
+    #[test]
+    fn stop_tracks_right() {
+        #[rustfmt::skip]
+        let events = [
+            StartTracks(&[TrackId(0), TrackId(1), TrackId(2), TrackId(3), TrackId(4)]),
+            StopTracks(&[TrackId(3), TrackId(4)]),
+            NoEvent,
+        ];
+        let string = to_string(&events).unwrap();
+
+        assert_eq!(string, "| | | | |\n| | | \" \"\n| | |\n");
+    }
+
+    #[test]
+    fn stop_tracks_does_not_exist() {
+        let events1 = [
+            StartTracks(&[TrackId(0), TrackId(1), TrackId(2)]),
+            StopTracks(&[TrackId(1), TrackId(5), TrackId(6)]),
+        ];
+        let events2 = [
+            StartTracks(&[TrackId(0), TrackId(1), TrackId(2)]),
+            StopTrack(TrackId(1)),
+        ];
+
+        let string1 = to_string(&events1).unwrap();
+        let string2 = to_string(&events2).unwrap();
+
+        assert_eq!(string1, string2);
+
+        let events = [StopTracks(&[TrackId(5), TrackId(6)])];
+        let string = to_string(&events).unwrap();
+
+        assert_eq!(string, "");
+    }
+
+    #[test]
+    fn station() {
+        let events = [
+            StartTracks(&[TrackId(0), TrackId(1), TrackId(2)]),
+            Station(TrackId(0), Cow::Borrowed("Station 1")),
+            Station(TrackId(1), Cow::Borrowed("Station 2")),
+            Station(TrackId(2), Cow::Borrowed("Station 3")),
+            Station(TrackId(0), Cow::Borrowed("Station 4")),
+            Station(TrackId(1), Cow::Borrowed("Station 5")),
+            Station(TrackId(2), Cow::Borrowed("Station 6")),
+        ];
+        let string = to_string(&events).unwrap();
+
+        assert_eq!(
+            string,
+            r#"| | |
+* | | Station 1
+| * | Station 2
+| | * Station 3
+* | | Station 4
+| * | Station 5
+| | * Station 6
+"#
+        );
+    }
+
+    #[test]
+    fn station_blank_lines() {
+        let events = [
+            StartTrack(TrackId(1)),
+            Event::station(0, "Line 1\n\nLine 3"),
+            Event::detached_station("Line 1\n\r\nLine 3"),
+            Event::stop_track_labeled(1, "Line 1\n\nLine 3"),
+        ];
+        let string = to_string(&events).unwrap();
+
+        assert_eq!(
+            string,
+            r#"| |
+* | Line 1
+| |
+| | Line 3
+| | Line 1
+| |
+| | Line 3
+| " Line 1
+|
+| Line 3
+"#
+        );
+
+        let opts = Options {
+            detached_gutter: true,
+            ..Options::default()
+        };
+        let string = to_string_with(&events[..3], &opts).unwrap();
+
+        assert_eq!(
+            string,
+            r#"       | |
+       * | Line 1
+       | |
+       | | Line 3
+Line 1 | |
+       | |
+Line 3 | |
+"#
+        );
+    }
+
+    #[test]
+    fn station_non_existing_track() {
+        let events = [
+            StartTracks(&[TrackId(0), TrackId(1), TrackId(2)]),
+            Station(TrackId(0), Cow::Borrowed("Station 1")),
+            Station(TrackId(1), Cow::Borrowed("Station 2")),
+            Station(TrackId(2), Cow::Borrowed("Station 3")),
+            Station(TrackId(3), Cow::Borrowed("Station 4")),
+            Station(TrackId(4), Cow::Borrowed("Station 5")),
+            Station(TrackId(5), Cow::Borrowed("Station 6")),
+            Station(TrackId(usize::MAX), Cow::Borrowed("Station 7")),
+        ];
+        let string = to_string(&events).unwrap();
+
+        assert_eq!(
+            string,
+            r#"| | |
+* | | Station 1
+| * | Station 2
+| | * Station 3
+| | | Station 4
+| | | Station 5
+| | | Station 6
+| | | Station 7
+"#
+        );
+    }
+
+    #[test]
+    fn station_multiple_lines() {
+        let events = [
+            StartTracks(&[TrackId(0), TrackId(1), TrackId(2)]),
+            Station(TrackId(0), Cow::Borrowed("Foo 0\nBar 0\r\nBaz 0")),
+            Station(TrackId(1), Cow::Borrowed("Foo 1\nBar 1\r\nBaz 1")),
+            Station(TrackId(2), Cow::Borrowed("Foo 2\nBar 2\r\nBaz 2")),
+            Station(TrackId(3), Cow::Borrowed("Foo 3\nBar 3\r\nBaz 3")),
+            Station(TrackId(4), Cow::Borrowed("Foo 4\nBar 4\r\nBaz 4")),
+            Station(TrackId(5), Cow::Borrowed("Foo 5\nBar 5\r\nBaz 5")),
+            Station(
+                TrackId(usize::MAX),
+                Cow::Borrowed("Foo MAX\nBar MAX\r\nBaz MAX"),
+            ),
+        ];
+        let string = to_string(&events).unwrap();
+
+        assert_eq!(
+            string,
+            r#"| | |
+* | | Foo 0
+| | | Bar 0
+| | | Baz 0
+| * | Foo 1
+| | | Bar 1
+| | | Baz 1
+| | * Foo 2
+| | | Bar 2
+| | | Baz 2
+| | | Foo 3
+| | | Bar 3
+| | | Baz 3
+| | | Foo 4
+| | | Bar 4
+| | | Baz 4
+| | | Foo 5
+| | | Bar 5
+| | | Baz 5
+| | | Foo MAX
+| | | Bar MAX
+| | | Baz MAX
+"#
+        );
+    }
+
+    #[test]
+    fn split_track() {
+        let events = [
+            SplitTrack(TrackId(0), TrackId(1)),
+            NoEvent,
+            SplitTrack(TrackId(0), TrackId(2)),
+            SplitTrack(TrackId(1), TrackId(3)),
+            SplitTrack(TrackId(3), TrackId(4)),
+        ];
+        let string = to_string(&events).unwrap();
+
+        assert_eq!(
+            string,
+            r#"|\
+| |
+|\ \
+| | |\
+| | | |\
+"#
+        );
+    }
+
+    #[test]
+    fn split_track_non_existing_from_track() {
+        let events1 = [
+            SplitTrack(TrackId(1), TrackId(2)),
+            SplitTrack(TrackId(3), TrackId(4)),
+            Station(TrackId(2), Cow::Borrowed("2")),
+            Station(TrackId(4), Cow::Borrowed("4")),
+        ];
+        let events2 = [
+            StartTrack(TrackId(2)),
+            StartTrack(TrackId(4)),
+            Station(TrackId(2), Cow::Borrowed("2")),
+            Station(TrackId(4), Cow::Borrowed("4")),
+        ];
+
+        let string1 = to_string(&events1).unwrap();
+        let string2 = to_string(&events2).unwrap();
+
+        assert_eq!(string1, string2);
+    }
+
+    #[test]
+    fn split_track_already_existing_new_track() {
+        let events1 = [
+            StartTracks(&[TrackId(0), TrackId(1), TrackId(2)]),
+            SplitTrack(TrackId(0), TrackId(1)),
+            SplitTrack(TrackId(0), TrackId(2)),
+            SplitTrack(TrackId(3), TrackId(4)),
+            Station(TrackId(0), Cow::Borrowed("0")),
+            Station(TrackId(1), Cow::Borrowed("1")),
+            Station(TrackId(2), Cow::Borrowed("2")),
+            Station(TrackId(3), Cow::Borrowed("3")),
+            Station(TrackId(4), Cow::Borrowed("4")),
+        ];
+        let events2 = [
+            StartTracks(&[TrackId(0), TrackId(1), TrackId(2)]),
+            StartTrack(TrackId(4)),
+            Station(TrackId(0), Cow::Borrowed("0")),
+            Station(TrackId(1), Cow::Borrowed("1")),
+            Station(TrackId(2), Cow::Borrowed("2")),
+            Station(TrackId(3), Cow::Borrowed("3")),
+            Station(TrackId(4), Cow::Borrowed("4")),
+        ];
+
+        let string1 = to_string(&events1).unwrap();
+        let string2 = to_string(&events2).unwrap();
+
+        assert_eq!(string1, string2);
+    }
+
+    #[test]
+    fn split_track_same_from_and_new_track() {
+        let events = [SplitTrack(TrackId(0), TrackId(0))];
+        let string = to_string(&events).unwrap();
+
+        assert_eq!(string, "");
+
+        #[rustfmt::skip]
+        let events1 = [
+            StartTracks(&[TrackId(0), TrackId(1), TrackId(2)]),
+            SplitTrack(TrackId(1), TrackId(1)),
+            SplitTrack(TrackId(0), TrackId(2)),
+        ];
+        let events2 = [StartTracks(&[TrackId(0), TrackId(1), TrackId(2)])];
+
+        let string1 = to_string(&events1).unwrap();
+        let string2 = to_string(&events2).unwrap();
+
+        assert_eq!(string1, string2);
+    }
+
+    #[test]
+    fn join_track_zero_between() {
+        #[rustfmt::skip]
+        let events = [
+            StartTracks(&[TrackId(0), TrackId(1), TrackId(2)]),
+            JoinTrack(TrackId(1), TrackId(0)),
+            NoEvent,
+        ];
+        let string = to_string(&events).unwrap();
+
+        assert_eq!(
+            string,
+            r#"| | |
+|/ /
+| |
+"#
+        );
+    }
+
+    #[test]
+    fn join_track_one_between() {
+        #[rustfmt::skip]
+        let events = [
+            StartTracks(&[TrackId(0), TrackId(1), TrackId(2)]),
+            JoinTrack(TrackId(2), TrackId(0)),
+            NoEvent,
+        ];
+        let string = to_string(&events).unwrap();
+
+        assert_eq!(
+            string,
+            r#"| | |
+| |/
+|/|
+| |
+"#
+        );
+    }
+
+    #[test]
+    fn join_track_two_between() {
+        #[rustfmt::skip]
+        let events1 = [
+            StartTracks(&[TrackId(0), TrackId(1), TrackId(2), TrackId(3), TrackId(4)]),
+            JoinTrack(TrackId(3), TrackId(0)),
+            NoEvent,
+        ];
+        #[rustfmt::skip]
+        let events2 = [
+            StartTracks(&[TrackId(0), TrackId(1), TrackId(2), TrackId(3), TrackId(4)]),
+            JoinTrack(TrackId(0), TrackId(3)),
+            NoEvent,
+        ];
+
+        for events in [&events1, &events2].iter() {
+            let string = to_string(&events[..]).unwrap();
+
+            assert_eq!(
+                string,
+                r#"| | | | |
+| |_|/ /
+|/| | |
+| | | |
+"#
+            );
+        }
+    }
+
+    #[test]
+    fn join_track_toward_right_two_between() {
+        #[rustfmt::skip]
+        let events = [
+            StartTracks(&[TrackId(0), TrackId(1), TrackId(2), TrackId(3), TrackId(4)]),
+            JoinTrackToward(TrackId(1), TrackId(4)),
+            NoEvent,
+        ];
+        let string = to_string(&events).unwrap();
+
+        assert_eq!(
+            string,
+            r#"| | | | |
+|  \|_| |
+|   | |\|
+|  / / /
+| | | |
+"#
+        );
+    }
+
+    #[test]
+    fn join_track_many_between() {
+        #[rustfmt::skip]
+        let events = [
+            StartTracks(&[TrackId(0), TrackId(1), TrackId(2), TrackId(3), TrackId(4)]),
+            JoinTrack(TrackId(4), TrackId(0)),
+            NoEvent,
+        ];
+        let string = to_string(&events).unwrap();
+
+        assert_eq!(
+            string,
+            r#"| | | | |
+| |_|_|/
+|/| | |
+| | | |
+"#
+        );
+    }
+
+    #[test]
+    fn join_track_always_leftmost() {
+        let events1 = [
+            StartTracks(&[
+                TrackId(0),
+                TrackId(1),
+                TrackId(2),
+                TrackId(3),
+                TrackId(4),
+                TrackId(5),
+                TrackId(6),
+                TrackId(7),
+                TrackId(8),
+                TrackId(9),
+            ]),
+            JoinTrack(TrackId(4), TrackId(1)),
+            JoinTrack(TrackId(5), TrackId(0)),
+            JoinTrack(TrackId(8), TrackId(7)),
+            JoinTrack(TrackId(9), TrackId(6)),
+            NoEvent,
+        ];
+
+        let events2 = events1
+            .iter()
+            .cloned()
+            .map(|event| match event {
+                JoinTrack(from, to) => JoinTrack(to, from),
+                _ => event,
+            })
+            .collect::<Vec<_>>();
+
+        let string1 = to_string(&events1).unwrap();
+        let string2 = to_string(&events2).unwrap();
+
+        // Note these only visually looks the same,
+        // the actual deleted track is not the same.
+        assert_eq!(string1, string2);
+    }
+
+    #[test]
+    fn join_tracks_three() {
+        #[rustfmt::skip]
+        let events = [
+            StartTracks(&[TrackId(0), TrackId(1), TrackId(2), TrackId(3)]),
+            JoinTracks(&[TrackId(1), TrackId(2), TrackId(3)], TrackId(0)),
+            NoEvent,
+        ];
+        let string = to_string(&events).unwrap();
+
+        assert_eq!(
+            string,
+            r#"| | | |
+|/_/_/
+|
+"#
+        );
+    }
+
+    #[test]
+    fn join_tracks_four() {
+        #[rustfmt::skip]
+        let events = [
+            StartTracks(&[TrackId(0), TrackId(1), TrackId(2), TrackId(3), TrackId(4)]),
+            JoinTracks(&[TrackId(1), TrackId(2), TrackId(3), TrackId(4)], TrackId(0)),
+            NoEvent,
+        ];
+        let string = to_string(&events).unwrap();
+
+        assert_eq!(
+            string,
+            r#"| | | | |
+|/_/_/_/
+|
+"#
+        );
+    }
+
+    #[test]
+    fn join_tracks_four_with_one_between() {
+        #[rustfmt::skip]
+        let events = [
+            StartTracks(&[TrackId(0), TrackId(1), TrackId(2), TrackId(3), TrackId(4), TrackId(5)]),
+            JoinTracks(&[TrackId(1), TrackId(3), TrackId(4), TrackId(5)], TrackId(0)),
+            NoEvent,
+        ];
+        let string = to_string(&events).unwrap();
+
+        assert_eq!(
+            string,
+            r#"| | | | | |
+|/__|/_/_/
+|  /
+| |
+"#
+        );
+    }
+
+    #[test]
+    fn join_tracks_toward_target() {
+        #[rustfmt::skip]
+        let events = [
+            StartTracks(&[TrackId(0), TrackId(1), TrackId(2), TrackId(3), TrackId(4)]),
+            JoinTracks(&[TrackId(0), TrackId(4)], TrackId(2)),
+            NoEvent,
+        ];
+        let string = to_string(&events).unwrap();
+
+        assert_eq!(
+            string,
+            r#"| | | | |
+ \| | |/
+  |\|/|
+ / / /
+| | |
+"#
+        );
+    }
+
+    #[test]
+    fn join_tracks_many_between() {
+        #[rustfmt::skip]
+        let events = [
+            StartTracks(&[TrackId(0), TrackId(1), TrackId(2), TrackId(3), TrackId(4), TrackId(5)]),
+            JoinTracks(&[TrackId(4)], TrackId(0)),
+            NoEvent,
+        ];
+        #[rustfmt::skip]
+        let events2 = [
+            StartTracks(&[TrackId(0), TrackId(1), TrackId(2), TrackId(3), TrackId(4), TrackId(5)]),
+            JoinTrack(TrackId(4), TrackId(0)),
+            NoEvent,
+        ];
+
+        let string1 = to_string(&events).unwrap();
+        let string2 = to_string(&events2).unwrap();
+
+        assert_eq!(string1, string2);
+    }
+
+    #[test]
+    fn join_tracks_non_existing_from_tracks() {
+        let events1 = [
+            StartTracks(&[TrackId(0), TrackId(1), TrackId(2)]),
+            JoinTracks(&[TrackId(2), TrackId(5), TrackId(6)], TrackId(0)),
+            JoinTracks(&[TrackId(7), TrackId(8)], TrackId(1)),
+        ];
+        let events2 = [
+            StartTracks(&[TrackId(0), TrackId(1), TrackId(2)]),
+            JoinTrack(TrackId(2), TrackId(0)),
+        ];
+
+        let string1 = to_string(&events1).unwrap();
+        let string2 = to_string(&events2).unwrap();
+
+        assert_eq!(string1, string2);
+    }
+
+    #[test]
+    fn join_tracks_non_existing_to_track() {
+        let events1 = [
+            StartTracks(&[TrackId(0), TrackId(1), TrackId(2), TrackId(3)]),
+            JoinTracks(&[TrackId(1), TrackId(3)], TrackId(5)),
+        ];
+        let events2 = [
+            StartTracks(&[TrackId(0), TrackId(1), TrackId(2), TrackId(3)]),
+            StopTracks(&[TrackId(1), TrackId(3)]),
+        ];
+
+        let string1 = to_string(&events1).unwrap();
+        let string2 = to_string(&events2).unwrap();
+
+        assert_eq!(string1, string2);
+    }
+
+    #[test]
+    fn join_tracks_to_track_in_from_tracks() {
+        let events1 = [
+            StartTracks(&[TrackId(0), TrackId(1), TrackId(2), TrackId(3)]),
+            JoinTracks(&[TrackId(1), TrackId(2), TrackId(3)], TrackId(2)),
+        ];
+        let events2 = [
+            StartTracks(&[TrackId(0), TrackId(1), TrackId(2), TrackId(3)]),
+            StopTracks(&[TrackId(1), TrackId(3)]),
+        ];
+
+        let string1 = to_string(&events1).unwrap();
+        let string2 = to_string(&events2).unwrap();
+
+        assert_eq!(string1, string2);
+    }
+
+    // The above `join_track` tests are all `existing_from_track_existing_to_track`
+
+    #[test]
+    fn join_track_non_existing_from_track_existing_to_track() {
+        let events1 = [
+            StartTracks(&[TrackId(0), TrackId(1), TrackId(2), TrackId(3), TrackId(4)]),
+            JoinTrack(TrackId(5), TrackId(0)),
+            JoinTrack(TrackId(10), TrackId(1)),
+        ];
+        let events2 = [StartTracks(&[
+            TrackId(0),
+            TrackId(1),
+            TrackId(2),
+            TrackId(3),
+            TrackId(4),
+        ])];
+
+        let string1 = to_string(&events1).unwrap();
+        let string2 = to_string(&events2).unwrap();
+
+        assert_eq!(string1, string2);
+    }
+
+    #[test]
+    fn join_track_non_existing_from_track_non_existing_to_track() {
+        let events1 = [
+            StartTracks(&[TrackId(0), TrackId(1), TrackId(2), TrackId(3), TrackId(4)]),
+            JoinTrack(TrackId(5), TrackId(6)),
+            JoinTrack(TrackId(10), TrackId(11)),
+        ];
+        let events2 = [StartTracks(&[
+            TrackId(0),
+            TrackId(1),
+            TrackId(2),
+            TrackId(3),
+            TrackId(4),
+        ])];
+
+        let string1 = to_string(&events1).unwrap();
+        let string2 = to_string(&events2).unwrap();
+
+        assert_eq!(string1, string2);
+    }
+
+    #[test]
+    fn join_track_existing_from_track_non_existing_to_track() {
+        let events1 = [
+            StartTracks(&[TrackId(0), TrackId(1), TrackId(2), TrackId(3), TrackId(4)]),
+            JoinTrack(TrackId(0), TrackId(5)),
+            JoinTrack(TrackId(2), TrackId(10)),
+        ];
+        #[rustfmt::skip]
+        let events2 = [
+            StartTracks(&[TrackId(0), TrackId(1), TrackId(2), TrackId(3), TrackId(4)]),
+            StopTrack(TrackId(0)),
+            StopTrack(TrackId(2)),
+        ];
+
+        let string1 = to_string(&events1).unwrap();
+        let string2 = to_string(&events2).unwrap();
+
+        assert_eq!(string1, string2);
+    }
+
+    #[test]
+    fn join_track_same_from_and_to_track() {
+        let events1 = [
+            StartTracks(&[TrackId(0), TrackId(1), TrackId(2), TrackId(3), TrackId(4)]),
+            JoinTrack(TrackId(0), TrackId(0)),
+            JoinTrack(TrackId(2), TrackId(2)),
+            JoinTrack(TrackId(10), TrackId(10)),
+        ];
+        #[rustfmt::skip]
+        let events2 = [
+            StartTracks(&[TrackId(0), TrackId(1), TrackId(2), TrackId(3), TrackId(4)]),
+            StopTrack(TrackId(0)),
+            StopTrack(TrackId(2)),
+        ];
+
+        let string1 = to_string(&events1).unwrap();
+        let string2 = to_string(&events2).unwrap();
+
+        assert_eq!(string1, string2);
+    }
+
+    #[test]
+    fn no_event() {
+        let events = [NoEvent, NoEvent, NoEvent];
+        let string = to_string(&events).unwrap();
+
+        assert_eq!(string, "|\n|\n|\n");
+
+        #[rustfmt::skip]
+        let events = [
+            StartTracks(&[TrackId(0), TrackId(1), TrackId(2)]),
+            NoEvent,
+            NoEvent,
+            NoEvent,
+        ];
+        let string = to_string(&events).unwrap();
+
+        assert_eq!(string, "| | |\n| | |\n| | |\n| | |\n");
+    }
+
+    #[test]
+    fn lines_equal_to_string() {
+        let events = [
+            StartTracks(&[TrackId(0), TrackId(1), TrackId(2), TrackId(3), TrackId(4)]),
+            Station(TrackId(2), Cow::Borrowed("Station 1\nWith multiple lines")),
+            SplitTrack(TrackId(1), TrackId(5)),
+            JoinTrack(TrackId(4), TrackId(0)),
+            StopTracks(&[TrackId(1), TrackId(3)]),
+            JoinTracks(&[TrackId(2), TrackId(5)], TrackId(0)),
+            NoEvent,
+        ];
+
+        let string = to_string(&events).unwrap();
+
+        let mut lines = lines(&events).collect::<Vec<_>>().join("\n");
+        lines.push('\n');
+
+        assert_eq!(lines, string);
+    }
+
+    #[test]
+    fn lines_lazy() {
+        let events = [
+            StartTracks(&[TrackId(0), TrackId(1), TrackId(2)]),
+            JoinTrack(TrackId(1), TrackId(0)),
+            StopTrack(TrackId(5)),
+            NoEvent,
+        ];
+
+        let mut lines = lines(&events);
+
+        assert_eq!(lines.next().as_deref(), Some("| | |"));
+        assert_eq!(lines.next().as_deref(), Some("|/ /"));
+        assert_eq!(lines.next().as_deref(), Some("| |"));
+        assert_eq!(lines.next(), None);
+        assert_eq!(lines.next(), None);
+
+        assert_eq!(super::lines(&[]).next(), None);
+    }
+
+    #[test]
+    fn measure_equal_to_string() {
+        let events = [
+            StartTracks(&[TrackId(0), TrackId(1), TrackId(2), TrackId(3), TrackId(4)]),
+            Station(TrackId(2), Cow::Borrowed("Station 1\nWith multiple lines")),
+            SplitTrack(TrackId(1), TrackId(5)),
+            JoinTrack(TrackId(4), TrackId(0)),
+            Station(TrackId(9), Cow::Borrowed("Détached")),
+            StopTracks(&[TrackId(1), TrackId(3)]),
+            JoinTracks(&[TrackId(2), TrackId(5)], TrackId(0)),
+            NoEvent,
+        ];
+
+        let string = to_string(&events).unwrap();
+
+        let width = string.lines().map(|line| line.chars().count()).max();
+        let height = string.lines().count();
+
+        assert_eq!(measure(&events), (width.unwrap(), height));
+        assert_eq!(measure(&[]), (0, 0));
+    }
+
+    #[test]
+    fn station_marked() {
+        let events = [
+            StartTracks(&[TrackId(0), TrackId(1), TrackId(2)]),
+            Event::station(1, "Station 1"),
+            Event::station_marked(1, "Station 2\nWith multiple lines", '@'),
+            Event::station_marked(9, "Station 3", '@'),
+        ];
+
+        assert_eq!(
+            to_string(&events).unwrap(),
+            r#"| | |
+| * | Station 1
+| @ | Station 2
+| | | With multiple lines
+| | | Station 3
+"#
+        );
+    }
+
+    #[test]
+    fn rows_equal_to_string() {
+        let events = [
+            StartTracks(&[TrackId(0), TrackId(1), TrackId(2), TrackId(3), TrackId(4)]),
+            Station(TrackId(2), Cow::Borrowed("Station 1\nWith multiple lines")),
+            SplitTrack(TrackId(1), TrackId(5)),
+            SplitTrackAt(TrackId(0), TrackId(6), TrackId(3)),
+            JoinTrack(TrackId(4), TrackId(0)),
+            Link(TrackId(0), TrackId(2)),
+            Station(TrackId(9), Cow::Borrowed("Detached")),
+            StopTracks(&[TrackId(1), TrackId(3)]),
+            JoinTracks(&[TrackId(2), TrackId(5)], TrackId(0)),
+            NoEvent,
+        ];
+
+        let string = to_string(&events).unwrap();
+
+        let rows = to_rows(&events);
+        let lines = rows.iter().map(|row| {
+            let cells = row.cells().iter().map(|cell| cell.to_char());
+            let cells = cells.collect::<String>();
+
+            match row.text() {
+                Some(text) if cells.is_empty() => text.to_string(),
+                Some(text) => format!("{} {}", cells, text),
+                None => cells.trim_end().to_string(),
+            }
+        });
+
+        assert!(lines.eq(string.lines()));
+    }
+
+    #[test]
+    fn measure_display_width() {
+        let events = [Event::station(0, "こんにちは"), Event::station(0, "a\tb")];
+
+        #[cfg(feature = "unicode-width")]
+        assert_eq!(measure(&events), (12, 2));
+        #[cfg(not(feature = "unicode-width"))]
+        assert_eq!(measure(&events), (9, 2));
+    }
+
+    #[test]
+    fn legend() {
+        let events = [
+            StartTracks(&[TrackId(0), TrackId(1), TrackId(2), TrackId(3)]),
+            LabelTrack(TrackId(0), Cow::Borrowed("main")),
+            LabelTrack(TrackId(1), Cow::Borrowed("release")),
+            LabelTrack(TrackId(3), Cow::Borrowed("feature/x")),
+            Legend,
+            NoEvent,
+        ];
+        let string = to_string(&events).unwrap();
+
+        assert_eq!(
+            string,
+            r#"| | | |
+main
+| release
+| | | feature/x
+| | | |
+"#
+        );
+    }
+
+    #[test]
+    fn legend_relabel_and_stop() {
+        let events = [
+            StartTracks(&[TrackId(0), TrackId(1), TrackId(2)]),
+            LabelTrack(TrackId(1), Cow::Borrowed("release")),
+            LabelTrack(TrackId(2), Cow::Borrowed("feature/x")),
+            LabelTrack(TrackId(2), Cow::Borrowed("feature/y")),
+            LabelTrack(TrackId(5), Cow::Borrowed("does not exist")),
+            StopTrack(TrackId(1)),
+            Legend,
+            StartTrack(TrackId(1)),
+            StartTrack(TrackId(5)),
+            Legend,
+        ];
+        let string = to_string(&events).unwrap();
+
+        assert_eq!(
+            string,
+            r#"| | |
+| " |
+|  /
+| feature/y
+| | |
+| | | |
+| feature/y
+"#
+        );
+    }
+
+    #[test]
+    fn legend_no_labels() {
+        let events = [StartTracks(&[TrackId(0), TrackId(1), TrackId(2)]), Legend];
+        let string = to_string(&events).unwrap();
+
+        assert_eq!(string, "| | |\n");
+    }
+
+    #[test]
+    fn right_to_left_split_track() {
+        let events = [
+            SplitTrack(TrackId(0), TrackId(1)),
+            NoEvent,
+            SplitTrack(TrackId(0), TrackId(2)),
+            SplitTrack(TrackId(1), TrackId(3)),
+            SplitTrack(TrackId(3), TrackId(4)),
+        ];
+        let opts = Options {
+            direction: Direction::RightToLeft,
+            ..Options::default()
+        };
+        let string = to_string_with(&events, &opts).unwrap();
+
+        assert_eq!(
+            string,
+            r#"       /|
+      | |
+     / /|
+   /| | |
+ /| | | |
+"#
+        );
+    }
+
+    #[test]
+    fn right_to_left_join_track() {
+        let events = [
+            StartTracks(&[TrackId(0), TrackId(1), TrackId(2), TrackId(3), TrackId(4)]),
+            Station(TrackId(1), Cow::Borrowed("Station 1")),
+            JoinTrack(TrackId(3), TrackId(0)),
+            StopTrack(TrackId(2)),
+        ];
+        let opts = Options {
+            direction: Direction::RightToLeft,
+            ..Options::default()
+        };
+        let string = to_string_with(&events, &opts).unwrap();
+
+        assert_eq!(
+            string,
+            r#"| | | | |
+| | | * | Station 1
+ \ \|_| |
+  | | |\|
+  | " | |
+   \  | |
+"#
+        );
+    }
+
+    #[test]
+    fn join_track_keep_source() {
+        for (from_track_id, to_track_id) in [(TrackId(0), TrackId(2)), (TrackId(2), TrackId(0))] {
+            let events = [
+                StartTracks(&[TrackId(0), TrackId(1), TrackId(2)]),
+                JoinTrackKeepSource(from_track_id, to_track_id),
+                Event::station(from_track_id, "Station 1"),
+                Event::station(to_track_id, "Station 2"),
+            ];
+            let string = to_string(&events).unwrap();
+
+            // The remaining leftmost rail carries `from_track_id`
+            assert_eq!(
+                string,
+                r#"| | |
+| |/
+|/|
+* | Station 1
+| | Station 2
+"#
+            );
+        }
+
+        let events = [
+            StartTracks(&[TrackId(0), TrackId(1)]),
+            JoinTrackKeepSource(TrackId(2), TrackId(1)),
+            NoEvent,
+        ];
+        assert_eq!(to_string(&events).unwrap(), "| |\n| \"\n|\n");
+
+        let events = [
+            StartTracks(&[TrackId(0), TrackId(1)]),
+            JoinTrackKeepSource(TrackId(1), TrackId(2)),
+            NoEvent,
+        ];
+        assert_eq!(to_string(&events).unwrap(), "| |\n| |\n");
+    }
+
+    #[test]
+    fn text_gap() {
+        let events = [
+            Event::station(0, "Station 1"),
+            SplitTrack(TrackId(0), TrackId(1)),
+            Event::station(1, "Station 2"),
+            Event::annotation("Annotation"),
+        ];
+        let opts = Options {
+            text_gap: Some(3),
+            ..Options::default()
+        };
+        let string = to_string_with(&events, &opts).unwrap();
+
+        assert_eq!(
+            string,
+            r#"*   Station 1
+|\
+| *   Station 2
+| |   Annotation
+"#
+        );
+    }
+
+    #[test]
+    fn align_text() {
+        let events = [
+            Event::station(0, "Station 1"),
+            SplitTrack(TrackId(0), TrackId(1)),
+            Event::label_track(0, "main"),
+            Event::label_track(1, "feature"),
+            Event::station(1, "Station 2"),
+            SplitTrack(TrackId(1), TrackId(2)),
+            Event::station(2, "Station 3\nWith multiple lines"),
+            JoinTrack(TrackId(2), TrackId(0)),
+            Event::station(0, "Station 4"),
+            Legend,
+        ];
+        let opts = Options {
+            align_text: true,
+            ..Options::default()
+        };
+        let string = to_string_with(&events, &opts).unwrap();
+
+        assert_eq!(
+            string,
+            r#"*     Station 1
+|\
+| *   Station 2
+| |\
+| | * Station 3
+| | | With multiple lines
+| |/
+|/|
+* |   Station 4
+main
+| feature
+"#
+        );
+    }
+
+    #[test]
+    fn compact_splits() {
+        let events = [
+            Event::station(0, "Station 1"),
+            SplitTrack(TrackId(0), TrackId(1)),
+            Event::station(1, "Station 2"),
+            SplitTrack(TrackId(1), TrackId(2)),
+            Event::station_marked(2, "Station 3", 'o'),
+            SplitTrack(TrackId(0), TrackId(3)),
+            Event::station(3, "Station 4"),
+            SplitTrack(TrackId(2), TrackId(4)),
+            NoEvent,
+            SplitTrack(TrackId(4), TrackId(5)),
+        ];
+        let opts = Options {
+            compact_splits: true,
+            ..Options::default()
+        };
+        let string = to_string_with(&events, &opts).unwrap();
+
+        assert_eq!(
+            string,
+            r#"* Station 1
+|\* Station 2
+| |\o Station 3
+|\ \ \
+| * | | Station 4
+| | | |\
+| | | | |
+| | | | |\
+"#
+        );
+
+        let opts = Options {
+            direction: Direction::RightToLeft,
+            ..opts
+        };
+        let string = to_string_with(&events, &opts).unwrap();
+
+        assert_eq!(
+            string,
+            r#"          * Station 1
+        */| Station 2
+      o/| | Station 3
+     / / /|
+    | | * | Station 4
+   /| | | |
+  | | | | |
+ /| | | | |
+"#
+        );
+    }
+
+    #[test]
+    fn show_track_ids() {
+        let events = [
+            Event::station(0, "Station 1"),
+            SplitTrack(TrackId(0), TrackId(12)),
+            Event::station(12, "Station 2"),
+            Event::label_track(12, "Label"),
+            SplitTrack(TrackId(0), TrackId(3)),
+            JoinTrack(TrackId(12), TrackId(0)),
+            StopTrack(TrackId(0)),
+            StopTrack(TrackId(3)),
+        ];
+        let opts = Options {
+            show_track_ids: true,
+            ..Options::default()
+        };
+        let string = to_string_with(&events, &opts).unwrap();
+
+        assert_eq!(
+            string,
+            r#"0
+* Station 1
+|\
+0 12
+| * Station 2
+|\ \
+0 3 12
+| |/
+|/|
+0 3
+" |
+ /
+3
+"
+"#
+        );
+
+        let opts = Options {
+            rail_spacing: 2,
+            direction: Direction::RightToLeft,
+            ..opts
+        };
+        let string = to_string_with(&events, &opts).unwrap();
+
+        assert_eq!(
+            string,
+            r#"      0
+      *  Station 1
+    _/|
+   12 0
+   *  |  Station 2
+ _/ _/|
+12 3  0
+ \_|  |
+   |\_|
+   3  0
+   |  "
+    \_
+      3
+      "
+"#
+        );
+    }
+
+    #[test]
+    fn render_delta() {
+        let events = [
+            Event::station(0, "Station 1"),
+            SplitTrack(TrackId(0), TrackId(1)),
+            Event::station(1, "Station 2"),
+            Event::label_track(1, "Label"),
+            JoinTrack(TrackId(1), TrackId(0)),
+            Legend,
+        ];
+
+        for i in 0..=events.len() {
+            let prev = to_string(&events[..i]).unwrap();
+            let next = to_string(&events).unwrap();
+
+            let mut delta = Vec::new();
+            super::render_delta(&events[..i], &events, &mut delta).unwrap();
+
+            assert_eq!(prev + &String::from_utf8(delta).unwrap(), next);
+        }
+    }
+
+    #[test]
+    fn to_string_range() {
+        let events = [
+            Event::station(0, "Station 1"),
+            SplitTrack(TrackId(0), TrackId(1)),
+            Event::station(1, "Station 2"),
+            Event::label_track(1, "Label"),
+            JoinTrack(TrackId(1), TrackId(0)),
+            Legend,
+        ];
+        let string = to_string(&events).unwrap();
+
+        for start in 0..=events.len() {
+            for end in start..=events.len() {
+                let prefix = to_string(&events[..start]).unwrap();
+                let range = super::to_string_range(&events, start..end);
+                let suffix = super::to_string_range(&events, end..usize::MAX);
+
+                assert_eq!(prefix + &range + &suffix, string);
+            }
+        }
+
+        #[allow(clippy::reversed_empty_ranges)]
+        let reversed = super::to_string_range(&events, 4..2);
+        assert_eq!(reversed, "");
+    }
+
+    #[test]
+    fn to_writer_traced() {
+        let events = [
+            Event::station(0, "Station 1\nWith multiple lines"),
+            SplitTrack(TrackId(0), TrackId(1)),
+            Event::label_track(1, "Label"),
+            JoinTrack(TrackId(1), TrackId(0)),
+            StopTrack(TrackId(5)),
+            Legend,
+        ];
+
+        let mut output = Vec::new();
+        let trace = super::to_writer_traced(&mut output, &events).unwrap();
+
+        let expected = crate::event_row_ranges(&events)
+            .into_iter()
+            .enumerate()
+            .collect::<Vec<_>>();
+
+        assert_eq!(trace, expected);
+        assert_eq!(output, super::to_vec(&events).unwrap());
+    }
+
+    #[test]
+    fn control_characters() {
+        let events = [
+            StartTracks(&[TrackId(0), TrackId(1)]),
+            Event::station(1, "\x1b[31mRed\r\nCarriage\rReturn"),
+            Event::station(0, "Tab\tStop"),
+            Event::label_track(0, "Label\r\nWith\tTab"),
+            Legend,
+        ];
+        let string = to_string(&events).unwrap();
+
+        assert_eq!(
+            string,
+            r#"| |
+| * \u{1b}[31mRed
+| | Carriage\rReturn
+* | Tab Stop
+Label
+With    Tab
+"#
+        );
+    }
+
+    #[test]
+    fn dyn_writer() {
+        let events = [
+            Event::station(0, "Station 1"),
+            SplitTrack(TrackId(0), TrackId(1)),
+        ];
+
+        let mut buf = Vec::new();
+        {
+            let writer: &mut dyn Write = &mut buf;
+            to_writer(&mut *writer, &events).unwrap();
+            to_writer_with(writer, &events, &Options::default()).unwrap();
+        }
+
+        let string = String::from_utf8(buf).unwrap();
+        assert_eq!(string, "* Station 1\n|\\\n".repeat(2));
+    }
+
+    #[test]
+    fn station_lines() {
+        let lines = vec![String::from("Hello"), String::from("World")];
+        let events = [
+            StartTracks(&[TrackId(0), TrackId(1)]),
+            Event::station_lines(1, &lines),
+        ];
+        let string = to_string(&events).unwrap();
+
+        assert_eq!(string, "| |\n| * Hello\n| | World\n");
+    }
+
+    #[test]
+    fn max_text_width() {
+        let events = [
+            Event::station(0, "Station 1"),
+            SplitTrack(TrackId(0), TrackId(1)),
+            Event::station(1, "Station 2"),
+            Event::station(1, "12345678\n123456789"),
+            Event::station(1, "\t\t"),
+            Event::label_track(0, "Label with\nmultiple lines"),
+            Legend,
+        ];
+        let opts = Options {
+            max_text_width: Some(12),
+            ..Options::default()
+        };
+        let string = to_string_with(&events, &opts).unwrap();
+
+        assert_eq!(
+            string,
+            r#"* Station 1
+|\
+| * Station…
+| * 12345678
+| | 1234567…
+| *     …
+Label with
+multiple li…
+"#
+        );
+    }
+
+    #[test]
+    fn detached_gutter() {
+        let events = [
+            Event::station(0, "Station 1"),
+            Event::station(5, "Note"),
+            SplitTrack(TrackId(0), TrackId(1)),
+            Event::detached_station("Longer\nnote"),
+            Event::station(1, "Station 2"),
+            Event::annotation("Annotation"),
+        ];
+        let opts = Options {
+            detached_gutter: true,
+            ..Options::default()
+        };
+        let string = to_string_with(&events, &opts).unwrap();
+
+        assert_eq!(
+            string,
+            r#"       * Station 1
+Note   |
+       |\
+Longer | |
+note   | |
+       | * Station 2
+       | | Annotation
+"#
+        );
+
+        // Without detached stations, there is no gutter
+        let events = [Event::station(0, "Station 1")];
+        let string = to_string_with(&events, &opts).unwrap();
+
+        assert_eq!(string, "* Station 1\n");
+    }
+
+    #[test]
+    fn align_detached_text() {
+        let events = [
+            Event::station(0, "Station 1"),
+            Event::station(5, "Note 1"),
+            SplitTrack(TrackId(0), TrackId(1)),
+            SplitTrack(TrackId(1), TrackId(2)),
+            Event::detached_station("Note 2\nLine 2"),
+            Event::station(2, "Station 2"),
+        ];
+        let opts = Options {
+            align_detached_text: true,
+            ..Options::default()
+        };
+        let string = to_string_with(&events, &opts).unwrap();
+
+        assert_eq!(
+            string,
+            r#"* Station 1
+|     Note 1
+|\
+| |\
+| | | Note 2
+| | | Line 2
+| | * Station 2
+"#
+        );
+
+        // The gutter takes precedence
+        let opts = Options {
+            align_detached_text: true,
+            detached_gutter: true,
+            ..Options::default()
+        };
+        assert_eq!(
+            to_string_with(&events, &opts).unwrap(),
+            to_string_with(
+                &events,
+                &Options {
+                    detached_gutter: true,
+                    ..Options::default()
+                }
+            )
+            .unwrap()
+        );
+    }
+
+    #[test]
+    fn to_fmt_writer_matches_to_writer() {
+        let events = [
+            Event::station(0, "Station 1"),
+            SplitTrack(TrackId(0), TrackId(1)),
+            Event::station(1, "Station 2"),
+            JoinTrack(TrackId(1), TrackId(0)),
+            Event::station(0, "Station 3"),
+        ];
+
+        for direction in [Direction::LeftToRight, Direction::RightToLeft] {
+            let opts = Options {
+                direction,
+                trailing_newline: false,
+                ..Options::default()
+            };
+
+            let mut string = String::new();
+            to_fmt_writer_with(&mut string, &events, &opts).unwrap();
+
+            let vec = to_vec_with(&events, &opts).unwrap();
+
+            assert_eq!(string.as_bytes(), vec.as_slice());
+        }
+    }
+
+    #[test]
+    fn initial_tracks() {
+        let opts = Options {
+            initial_tracks: vec![],
+            ..Options::default()
+        };
+
+        assert_eq!(to_string_with(&[], &opts).unwrap(), "");
+        assert_eq!(to_string(&[StartTrack(TrackId(0))]).unwrap(), "");
+        assert_eq!(
+            to_string_with(&[StartTrack(TrackId(0))], &opts).unwrap(),
+            "|\n"
+        );
+
+        let events = [
+            StartTrack(TrackId(1)),
+            Event::station(1, "Station 1"),
+            Event::station(0, "Station 2"),
+        ];
+        let string = to_string_with(&events, &opts).unwrap();
+
+        assert_eq!(string, "|\n* Station 1\n| Station 2\n");
+
+        let opts = Options {
+            initial_tracks: vec![TrackId(2), TrackId(1)],
+            ..Options::default()
+        };
+        let events = [Event::station(1, "Station 1")];
+        let string = to_string_with(&events, &opts).unwrap();
+
+        assert_eq!(string, "| * Station 1\n");
+    }
+
+    #[test]
+    fn line_ending_crlf() {
+        let events = [
+            Event::station(0, "Station 1\nWith multiple lines"),
+            SplitTrack(TrackId(0), TrackId(1)),
+            Event::label_track(0, "Label 1\nWith multiple lines"),
+            Legend,
+            StopTrackLabeled(TrackId(1), "End\nWith multiple lines".into()),
+            JoinTrack(TrackId(0), TrackId(2)),
+        ];
+
+        let expected = to_string(&events).unwrap().replace('\n', "\r\n");
+
+        for trailing_newline in [true, false] {
+            let opts = Options {
+                line_ending: LineEnding::Crlf,
+                trailing_newline,
+                ..Options::default()
+            };
+            let string = to_string_with(&events, &opts).unwrap();
+
+            if trailing_newline {
+                assert_eq!(string, expected);
+            } else {
+                assert_eq!(string, expected.trim_end_matches("\r\n"));
+            }
+        }
+    }
+
+    #[test]
+    fn continuation_marker() {
+        let events = [
+            Event::station(0, "Station 1"),
+            SplitTrack(TrackId(0), TrackId(1)),
+            Event::station(1, "Station 2"),
+        ];
+
+        let opts = Options {
+            continuation_marker: Some('⋮'),
+            ..Options::default()
+        };
+        assert_eq!(
+            to_string_with(&events, &opts).unwrap(),
+            "* Station 1\n|\\\n| * Station 2\n⋮ ⋮\n"
+        );
+
+        let opts = Options {
+            continuation_marker: Some('⋮'),
+            direction: Direction::RightToLeft,
+            ..Options::default()
+        };
+        assert_eq!(
+            to_string_with(&events, &opts).unwrap(),
+            "  * Station 1\n /|\n* | Station 2\n⋮ ⋮\n"
+        );
+
+        let opts = Options {
+            continuation_marker: Some('⋮'),
+            ..Options::default()
+        };
+        assert_eq!(
+            to_string_with(&[StopTrack(TrackId(0))], &opts).unwrap(),
+            to_string(&[StopTrack(TrackId(0))]).unwrap()
+        );
+    }
+
+    #[test]
+    fn kind() {
+        assert_eq!(StartTrack(TrackId(1)).kind(), EventKind::StartTrack);
+        assert_eq!(Event::station(0, "Station").kind(), EventKind::Station);
+        assert_eq!(
+            Event::split_track_n(0, vec![TrackId(1)]).kind(),
+            EventKind::SplitTrackN
+        );
+        assert_eq!(Legend.kind(), EventKind::Legend);
+        assert_eq!(NoEvent.kind(), EventKind::NoEvent);
+    }
+
+    #[test]
+    fn to_string_titled() {
+        assert_eq!(
+            super::to_string_titled("Title", &[]).unwrap(),
+            "Title\n-----\n"
+        );
+
+        let events = [
+            Event::station(0, "Station 1"),
+            StartTrack(TrackId(1)),
+            StartTrack(TrackId(2)),
+            Event::station(2, "Station 2"),
+        ];
+        let string = super::to_string_titled("Title", &events).unwrap();
+        let (width, height) = measure(&events);
+
+        assert_eq!(string.lines().nth(1), Some("-".repeat(width).as_str()));
+        assert_eq!(string.lines().count(), height + 2);
+    }
+
+    #[test]
+    fn render_initial_tracks() {
+        let opts = Options {
+            render_initial_tracks: true,
+            ..Options::default()
+        };
+        assert_eq!(to_string_with(&[], &opts).unwrap(), "|\n");
+        assert_eq!(
+            to_string_with(&[Event::station(0, "Station 1")], &opts).unwrap(),
+            "|\n* Station 1\n"
+        );
+
+        let opts = Options {
+            render_initial_tracks: true,
+            initial_tracks: vec![TrackId(0), TrackId(1)],
+            show_track_ids: true,
+            direction: Direction::RightToLeft,
+            ..Options::default()
+        };
+        assert_eq!(
+            to_string_with(&[Event::station(0, "Station 1")], &opts).unwrap(),
+            "1 0\n| |\n| * Station 1\n"
+        );
 
-                        writeln!(&mut writer, "{}", line)?;
-                    }
-                }
-            }
+        let opts = Options {
+            render_initial_tracks: true,
+            initial_tracks: vec![],
+            ..Options::default()
+        };
+        assert_eq!(to_string_with(&[], &opts).unwrap(), "");
+    }
 
-            &JoinTrack(from_track_id, to_track_id) => {
-                let from_track_index = tracks.iter().position(|&id| id == from_track_id);
+    #[test]
+    fn merge_marker() {
+        let opts = Options {
+            initial_tracks: vec![TrackId(0), TrackId(1), TrackId(2), TrackId(3)],
+            merge_marker: Some('M'),
+            ..Options::default()
+        };
 
-                if from_track_id == to_track_id {
-                    stop_track(&mut writer, &mut tracks, from_track_id)?;
-                    continue;
-                }
+        let render = |event: Event, opts: &Options| to_string_with(&[event], opts).unwrap();
 
-                if let Some(from_track_index) = from_track_index {
-                    let to_track_index = tracks.iter().position(|&id| id == to_track_id);
+        assert_eq!(render(JoinTrack(TrackId(1), TrackId(0)), &opts), "M/ / /\n");
+        assert_eq!(
+            render(JoinTrack(TrackId(3), TrackId(0)), &opts),
+            "| |_|/\nM/| |\n"
+        );
+        assert_eq!(
+            render(JoinTrack(TrackId(0), TrackId(3)), &opts),
+            "| |_|/\nM/| |\n"
+        );
+        assert_eq!(
+            render(JoinTrackToward(TrackId(0), TrackId(1)), &opts),
+            " \\M | |\n / / /\n"
+        );
+        assert_eq!(
+            render(JoinTrackToward(TrackId(0), TrackId(3)), &opts),
+            " \\|_| |\n  | |\\M\n / / /\n"
+        );
+        assert_eq!(
+            render(JoinTrackKeepSource(TrackId(2), TrackId(1)), &opts),
+            "| M/ /\n"
+        );
+        assert_eq!(
+            render(JoinTrack(TrackId(1), TrackId(5)), &opts),
+            "| \" | |\n|  / /\n"
+        );
 
-                    if let Some(to_track_index) = to_track_index {
-                        let left_index = from_track_index.min(to_track_index);
-                        let right_index = from_track_index.max(to_track_index);
+        let opts = Options {
+            direction: Direction::RightToLeft,
+            rail_spacing: 2,
+            ..opts
+        };
+        assert_eq!(
+            render(JoinTrack(TrackId(2), TrackId(0)), &opts),
+            " \\_ \\_|  |\n   |  |\\_M\n"
+        );
+    }
 
-                        if (right_index - left_index) == 1 {
-                            let line = (0..tracks.len())
-                                .filter_map(|i| {
-                                    if i > right_index {
-                                        Some("/")
-                                    } else if i == left_index {
-                                        Some("|/")
-                                    } else if i != right_index {
-                                        Some("|")
-                                    } else {
-                                        None
-                                    }
-                                })
-                                .collect::<Vec<_>>()
-                                .join(" ");
-
-                            writeln!(&mut writer, "{}", line)?;
-                        } else {
-                            let line = (0..tracks.len())
-                                .filter_map(|i| {
-                                    if i > right_index {
-                                        Some(" /")
-                                    } else if i == right_index {
-                                        None
-                                    } else if i >= (right_index - 1) {
-                                        Some("|/")
-                                    } else if i > left_index {
-                                        Some("|_")
-                                    } else {
-                                        Some("| ")
-                                    }
-                                })
-                                .collect::<Vec<_>>()
-                                .concat();
-
-                            writeln!(&mut writer, "{}", line)?;
-
-                            let track_count = tracks.len() - 1;
-                            let line = (0..track_count)
-                                .map(|i| {
-                                    if i == left_index {
-                                        "|/"
-                                    } else if i == (track_count - 1) {
-                                        "|"
-                                    } else {
-                                        "| "
-                                    }
-                                })
-                                .collect::<Vec<_>>()
-                                .concat();
-
-                            writeln!(&mut writer, "{}", line)?;
-                        }
-
-                        tracks.remove(from_track_index);
-                    } else {
-                        stop_track(&mut writer, &mut tracks, from_track_id)?;
-                    }
-                }
-            }
+    #[test]
+    fn ghost_tracks() {
+        let opts = Options {
+            ghost_tracks: vec![TrackId(1), TrackId(2)].into_iter().collect(),
+            ..Options::default()
+        };
 
-            NoEvent => {
-                let line = iter::repeat("|")
-                    .take(tracks.len())
-                    .collect::<Vec<_>>()
-                    .join(" ");
+        let events = [
+            Event::station(0, "Station 1"),
+            SplitTrack(TrackId(0), TrackId(1)),
+            NoEvent,
+            Event::station(0, "Station 2"),
+            Event::station(1, "Station 3"),
+            Event::station(0, "Station 4"),
+        ];
+        assert_eq!(
+            to_string_with(&events, &opts).unwrap(),
+            "* Station 1\n|\\\n| :\n* : Station 2\n| * Station 3\n* | Station 4\n"
+        );
 
-                writeln!(&mut writer, "{}", line)?;
-            }
-        }
+        // A stopped ghost track is no longer a ghost track
+        let events = [
+            StartTrack(TrackId(2)),
+            StopTrack(TrackId(2)),
+            StartTrack(TrackId(2)),
+        ];
+        assert_eq!(to_string_with(&events, &opts).unwrap(), "| :\n| \"\n| |\n");
     }
 
-    Ok(())
-}
+    #[test]
+    fn unknown_track_policy() {
+        let events = [
+            Event::station(0, "Station 1"),
+            Event::station(5, "Station 2"),
+            Event::detached_station("Note"),
+            StationMulti(&[TrackId(0), TrackId(6), TrackId(5)], "Station 3".into()),
+        ];
 
-fn stop_track<W: Write>(mut writer: W, tracks: &mut Vec<usize>, track_id: usize) -> io::Result<()> {
-    if let Some(index) = tracks.iter().position(|&id| id == track_id) {
-        let line = (0..tracks.len())
-            .map(|i| if i == index { "\"" } else { "|" })
-            .collect::<Vec<_>>()
-            .join(" ");
+        let opts = Options {
+            unknown_track_policy: UnknownTrackPolicy::Ignore,
+            ..Options::default()
+        };
+        assert_eq!(
+            to_string_with(&events, &opts).unwrap(),
+            to_string(&events).unwrap()
+        );
 
-        writeln!(&mut writer, "{}", line)?;
+        let opts = Options {
+            unknown_track_policy: UnknownTrackPolicy::AutoStart,
+            ..Options::default()
+        };
+        assert_eq!(
+            to_string_with(&events, &opts).unwrap(),
+            "* Station 1\n| |\n| * Station 2\n| | Note\n| | |\n* * * Station 3\n"
+        );
 
-        if index != (tracks.len() - 1) {
-            let line = (0..tracks.len())
-                .map(|i| {
-                    use std::cmp::Ordering::*;
-                    match i.cmp(&index) {
-                        Greater => "/",
-                        Equal => "",
-                        Less => "|",
-                    }
-                })
-                .collect::<Vec<_>>()
-                .join(" ");
+        let opts = Options {
+            unknown_track_policy: UnknownTrackPolicy::Error,
+            ..Options::default()
+        };
+        let err = to_string_with(&events, &opts).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+        assert_eq!(err.to_string(), "track 5 does not exist");
 
-            writeln!(&mut writer, "{}", line)?;
-        }
+        let mut output = Vec::new();
+        let err = to_writer_with(&mut output, &events, &opts).unwrap_err();
+        assert_eq!(err.to_string(), "track 5 does not exist");
+        assert_eq!(output, b"* Station 1\n");
+
+        let mut string = String::new();
+        assert!(to_fmt_writer_with(&mut string, &events, &opts).is_err());
 
-        tracks.remove(index);
+        // Detached stations are never an error
+        assert_eq!(
+            to_string_with(&events[2..3], &opts).unwrap(),
+            to_string(&events[2..3]).unwrap()
+        );
     }
 
-    Ok(())
-}
+    #[test]
+    fn sort_tracks() {
+        let opts = Options {
+            sort_tracks: true,
+            ..Options::default()
+        };
 
-/// Write `&[`[`Event`]`]` to [`Vec<u8>`].
-/// Defines a default track with `track_id` of `0`.
-///
-/// *[See also `Metro::to_vec`.][`Metro::to_vec`]*
-///
-/// *See also [`to_string`] and [`to_writer`].*
-///
-/// [`to_writer`]: fn.to_writer.html
-/// [`to_string`]: fn.to_string.html
-///
-/// [`Event`]: enum.Event.html
-///
-/// [`Metro::to_vec`]: struct.Metro.html#method.to_vec
-///
-/// [`Vec<u8>`]: https://doc.rust-lang.org/stable/std/vec/struct.Vec.html
-#[inline]
-pub fn to_vec(events: &[Event]) -> io::Result<Vec<u8>> {
-    let mut vec = Vec::new();
-    to_writer(&mut vec, events)?;
-    Ok(vec)
-}
+        let events = [
+            StartTrack(TrackId(3)),
+            StartTrack(TrackId(1)),
+            StartTrack(TrackId(2)),
+            Event::station(0, "Station 1"),
+            Event::station(1, "Station 2"),
+            Event::station(2, "Station 3"),
+            Event::station(3, "Station 4"),
+        ];
+        let string = to_string_with(&events, &opts).unwrap();
 
-/// Write `&[`[`Event`]`]` to [`String`].
-/// Defines a default track with `track_id` of `0`.
-///
-/// *[See also `Metro::to_string`.][`Metro::to_string`]*
-///
-/// *See also [`to_vec`] and [`to_writer`].*
-///
-/// [`to_writer`]: fn.to_writer.html
-/// [`to_vec`]: fn.to_vec.html
-///
-/// [`Event`]: enum.Event.html
-///
-/// [`Metro::to_string`]: struct.Metro.html#method.to_string
-///
-/// [`String`]: https://doc.rust-lang.org/stable/std/string/struct.String.html
-#[inline]
-pub fn to_string(events: &[Event]) -> io::Result<String> {
-    let vec = to_vec(events)?;
-    // Ok(String::from_utf8(vec)?)
-    // Metro only writes `str`s and `String`s to the `vec`
-    // which are always valid UTF-8, so this is safe.
-    #[allow(unsafe_code)]
-    unsafe {
-        Ok(String::from_utf8_unchecked(vec))
+        assert_eq!(
+            string,
+            r#"| |
+|  \
+| | |
+| |  \
+| | | |
+* | | | Station 1
+| * | | Station 2
+| | * | Station 3
+| | | * Station 4
+"#
+        );
+
+        let events = [
+            StartTracks(&[TrackId(3), TrackId(1), TrackId(2)]),
+            Event::station(0, "Station 1"),
+        ];
+        let string = to_string_with(&events, &opts).unwrap();
+
+        assert_eq!(string, "| | | |\n* | | | Station 1\n");
     }
-}
 
-/*
-/// `Error` is an error that can be returned by
-/// [`to_string`], [`to_vec`], and [`to_writer`].
-///
-/// [`to_writer`]: fn.to_writer.html
-/// [`to_vec`]: fn.to_vec.html
-/// [`to_string`]: fn.to_string.html
-#[derive(Debug)]
-pub enum Error {
-    /// [See `std::io::Error`][io::Error].
-    ///
-    /// [io::Error]: https://doc.rust-lang.org/std/io/struct.Error.html
-    IoError(io::Error),
+    #[test]
+    fn sort_tracks_split() {
+        let opts = Options {
+            sort_tracks: true,
+            initial_tracks: vec![TrackId(3), TrackId(0), TrackId(1)],
+            ..Options::default()
+        };
 
-    /// [See `std::string::FromUtf8Error`][FromUtf8Error].
-    ///
-    /// [FromUtf8Error]: https://doc.rust-lang.org/std/string/struct.FromUtf8Error.html
-    FromUtf8Error(FromUtf8Error),
-}
+        let events = [
+            SplitTrack(TrackId(0), TrackId(2)),
+            Event::split_station(1, 4, "Station 1"),
+            Event::split_track_n(3, vec![TrackId(6), TrackId(5)]),
+            Event::split_track_n(6, vec![TrackId(8), TrackId(7)]),
+            Event::station(5, "Station 2"),
+        ];
+        let string = to_string_with(&events, &opts).unwrap();
 
-impl fmt::Display for Error {
-    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
-        use Error::*;
-        match self {
-            IoError(err) => err.fmt(fmt),
-            FromUtf8Error(err) => err.fmt(fmt),
-        }
+        assert_eq!(
+            string,
+            r#"|\|_ \
+| |\|_|_
+| | | | * Station 1
+| | | |\|_
+| | | |\|_|_
+| | | | | | |\_\
+| | | | | * | | | Station 2
+"#
+        );
     }
-}
 
-impl error::Error for Error {
-    #[inline]
-    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
-        use Error::*;
-        match self {
-            IoError(ref err) => Some(err),
-            FromUtf8Error(ref err) => Some(err),
-        }
-    }
-}
+    #[test]
+    fn sort_tracks_start_before_existing() {
+        let opts = Options {
+            sort_tracks: true,
+            ..Options::default()
+        };
 
-impl From<io::Error> for Error {
-    #[inline]
-    fn from(err: io::Error) -> Self {
-        Self::IoError(err)
-    }
-}
+        let events = [
+            Event::split_track_n(0, vec![TrackId(3)]),
+            StartTracks(&[TrackId(1), TrackId(2)]),
+            Event::station(3, "Station 1"),
+        ];
+        let string = to_string_with(&events, &opts).unwrap();
 
-impl From<FromUtf8Error> for Error {
-    #[inline]
-    fn from(err: FromUtf8Error) -> Self {
-        Self::FromUtf8Error(err)
-    }
-}
-// */
+        assert_eq!(
+            string,
+            r#"|\
+|  \__
+| | | |
+| | | * Station 1
+"#
+        );
 
-#[cfg(test)]
-mod tests {
-    use std::borrow::Cow;
+        let events = [
+            Event::split_track_n(0, vec![TrackId(1), TrackId(5)]),
+            StartTracks(&[TrackId(2), TrackId(4)]),
+            Event::station(5, "Station 1"),
+        ];
+        let string = to_string_with(&events, &opts).unwrap();
 
-    use super::to_string;
-    use super::Event::*;
+        assert_eq!(
+            string,
+            r#"|\_\
+| |  \__
+| | | | |
+| | | | * Station 1
+"#
+        );
 
-    #[test]
-    fn start_track() {
-        let events = [StartTrack(1)];
-        let string = to_string(&events).unwrap();
+        let events = [
+            Event::split_track_n(0, vec![TrackId(5)]),
+            StartTracks(&[TrackId(1), TrackId(2), TrackId(3)]),
+            Event::station(5, "Station 1"),
+        ];
+        let string = to_string_with(&events, &opts).unwrap();
 
-        assert_eq!(string, "| |\n");
+        assert_eq!(
+            string,
+            r#"|\
+|  \____
+| | | | |
+| | | | * Station 1
+"#
+        );
     }
 
     #[test]
-    fn start_track_already_exists() {
-        let events = [StartTrack(0)];
-        let string = to_string(&events).unwrap();
+    fn show_track_age() {
+        let opts = Options {
+            show_track_age: true,
+            ..Options::default()
+        };
 
-        assert_eq!(string, "");
+        let events = [
+            Event::station(0, "Station 1"),
+            SplitTrack(TrackId(0), TrackId(1)),
+            Event::station(1, "Station 2\nWith multiple lines"),
+            Event::split_station(1, 2, "Station 3"),
+            StationMulti(&[TrackId(1), TrackId(2)], "Station 4".into()),
+            Event::detached_station("Detached"),
+            StopTrack(TrackId(1)),
+            StartTrack(TrackId(1)),
+            Event::station(1, "Station 5"),
+        ];
+        let string = to_string_with(&events, &opts).unwrap();
+
+        assert_eq!(
+            string,
+            r#"* Station 1 (age 0)
+|\
+| * Station 2 (age 1)
+| | With multiple lines
+| |\
+| | * Station 3 (age 1)
+| * * Station 4 (age 5)
+| | | Detached
+| " |
+|  /
+| | |
+| | * Station 5 (age 1)
+"#
+        );
     }
 
     #[test]
-    fn start_track_already_exists2() {
-        #[rustfmt::skip]
+    fn station_markers() {
+        let opts = Options {
+            station_markers: vec![(TrackId(1), '◆'), (TrackId(2), 'o')]
+                .into_iter()
+                .collect(),
+            ..Options::default()
+        };
+
         let events = [
-            StartTrack(1),
-            StartTrack(2),
-            StartTrack(1),
-            StartTrack(2),
+            StartTracks(&[TrackId(1), TrackId(2)]),
+            Event::station(0, "Station 1"),
+            Event::station(1, "Station 2"),
+            Event::station_marked(2, "Station 3", '@'),
+            StationMulti(&[TrackId(0), TrackId(1), TrackId(2)], "Station 4".into()),
         ];
-        let string = to_string(&events).unwrap();
+        let string = to_string_with(&events, &opts).unwrap();
 
-        assert_eq!(string, "| |\n| | |\n");
+        let expected = "\
+| | |
+* | | Station 1
+| ◆ | Station 2
+| | @ Station 3
+* ◆ o Station 4
+";
+        assert_eq!(string, expected);
+
+        let opts = Options {
+            direction: Direction::RightToLeft,
+            ..opts
+        };
+        let string = to_string_with(&events, &opts).unwrap();
+
+        assert!(string.ends_with("o ◆ * Station 4\n"));
     }
 
+    #[cfg(feature = "unicode-width")]
     #[test]
-    fn start_track_default() {
-        let events = [StartTrack(0)];
-        let string = to_string(&events).unwrap();
-
-        assert_eq!(string, "");
+    fn station_markers_wide() {
+        let opts = Options {
+            station_markers: vec![(TrackId(0), '🚉')].into_iter().collect(),
+            ..Options::default()
+        };
+        let string = to_string_with(&[Event::station(0, "Station 1")], &opts).unwrap();
+
+        assert_eq!(string, "* Station 1\n");
     }
 
+    #[cfg(feature = "unicode-width")]
     #[test]
-    fn event_start_track_default2() {
-        let events = [StartTrack(1)];
-        let string = to_string(&events).unwrap();
-
-        assert_eq!(string, "| |\n");
+    fn max_text_width_wide() {
+        let events = [Event::station(0, "こんにちは")];
+        let opts = Options {
+            max_text_width: Some(10),
+            ..Options::default()
+        };
+        let string = to_string_with(&events, &opts).unwrap();
+
+        assert_eq!(string, "* こんに…\n");
     }
 
     #[test]
-    fn event_start_tracks() {
-        let events = [StartTracks(&[1, 2, 3])];
+    fn split_track_at() {
+        #[rustfmt::skip]
+        let events = [
+            StartTracks(&[TrackId(0), TrackId(1), TrackId(2), TrackId(3)]),
+            SplitTrackAt(TrackId(0), TrackId(4), TrackId(2)),
+            NoEvent,
+        ];
         let string = to_string(&events).unwrap();
 
-        assert_eq!(string, "| | | |\n");
+        assert_eq!(
+            string,
+            r#"| | | |
+|\|_|_ \
+| | | | |
+"#
+        );
     }
 
     #[test]
-    fn start_tracks_some_already_exist() {
-        let events = [StartTracks(&[0, 1, 2])];
+    fn split_track_n() {
+        let events = [
+            Event::station(0, "Station 1"),
+            Event::split_track_n(0, &[TrackId(1), TrackId(2), TrackId(3)][..]),
+            Event::station(2, "Station 2"),
+        ];
         let string = to_string(&events).unwrap();
 
-        assert_eq!(string, "| | |\n");
+        assert_eq!(
+            string,
+            r#"* Station 1
+|\_\_\
+| | * | Station 2
+"#
+        );
     }
 
     #[test]
-    fn start_tracks_all_already_exist() {
-        let events = [StartTracks(&[0, 0, 0])];
-        let string = to_string(&events).unwrap();
+    fn split_station() {
+        let events = [
+            StartTracks(&[TrackId(0), TrackId(1), TrackId(2)]),
+            Event::split_station(1, 3, "Station 1\nWith multiple lines"),
+            Event::split_station(5, 4, "Station 2"),
+            Event::split_station(0, 3, "Station 3"),
+        ];
+        let expected = [
+            StartTracks(&[TrackId(0), TrackId(1), TrackId(2)]),
+            SplitTrack(TrackId(1), TrackId(3)),
+            Event::station(3, "Station 1\nWith multiple lines"),
+            SplitTrack(TrackId(5), TrackId(4)),
+            Event::station(4, "Station 2"),
+            SplitTrack(TrackId(0), TrackId(3)),
+            Event::station(3, "Station 3"),
+        ];
+        assert_eq!(to_string(&events).unwrap(), to_string(&expected).unwrap());
 
-        assert_eq!(string, "");
+        let opts = Options {
+            station_markers: vec![(TrackId(3), '◆')].into_iter().collect(),
+            ..Options::default()
+        };
+        let string = to_string_with(&events[..2], &opts).unwrap();
+
+        assert_eq!(
+            string,
+            r#"| | |
+| |\ \
+| | ◆ | Station 1
+| | | | With multiple lines
+"#
+        );
     }
 
     #[test]
-    fn stop_track() {
-        let events = [StopTrack(0)];
+    fn split_track_n_push() {
+        let events = [
+            StartTracks(&[TrackId(0), TrackId(1), TrackId(2)]),
+            Event::split_track_n(1, vec![TrackId(3), TrackId(0), TrackId(4), TrackId(3)]),
+            Event::station(2, "Station 1"),
+        ];
         let string = to_string(&events).unwrap();
 
-        assert_eq!(string, "\"\n");
+        assert_eq!(
+            string,
+            r#"| | |
+| |  \
+| |\_\ \
+| | | | * Station 1
+"#
+        );
     }
 
     #[test]
-    fn stop_track_does_not_exist() {
-        let events = [StopTrack(1)];
-        let string = to_string(&events).unwrap();
+    fn split_track_n_non_existing_from_track() {
+        let events1 = [Event::split_track_n(5, &[TrackId(1), TrackId(2)][..])];
+        let events2 = [StartTracks(&[TrackId(1), TrackId(2)])];
 
-        assert_eq!(string, "");
+        assert_eq!(to_string(&events1).unwrap(), to_string(&events2).unwrap());
+
+        let events1 = [Event::split_track_n(0, &[TrackId(1)][..])];
+        let events2 = [SplitTrack(TrackId(0), TrackId(1))];
+
+        assert_eq!(to_string(&events1).unwrap(), to_string(&events2).unwrap());
     }
 
     #[test]
-    fn stop_track_left() {
+    fn split_track_at_left() {
         #[rustfmt::skip]
         let events = [
-            StartTracks(&[0, 1, 2, 3, 4]),
-            StopTrack(0),
+            StartTracks(&[TrackId(0), TrackId(1), TrackId(2), TrackId(3)]),
+            SplitTrackAt(TrackId(3), TrackId(4), TrackId(0)),
             NoEvent,
         ];
         let string = to_string(&events).unwrap();
 
         assert_eq!(
             string,
-            r#"| | | | |
-" | | | |
- / / / /
-| | | |
+            r#"| | | |
+| | | |\
+| |_|_|/
+| |\ \ \
+| | | | |
 "#
         );
     }
 
     #[test]
-    fn stop_track_middle() {
-        #[rustfmt::skip]
-        let events = [
-            StartTracks(&[0, 1, 2, 3, 4]),
-            StopTrack(2),
+    fn split_track_at_non_existing_neighbor_track() {
+        let events1 = [
+            StartTracks(&[TrackId(0), TrackId(1), TrackId(2)]),
+            SplitTrackAt(TrackId(1), TrackId(4), TrackId(5)),
+            NoEvent,
+        ];
+        let events2 = [
+            StartTracks(&[TrackId(0), TrackId(1), TrackId(2)]),
+            SplitTrack(TrackId(1), TrackId(4)),
             NoEvent,
         ];
-        let string = to_string(&events).unwrap();
 
-        assert_eq!(
-            string,
-            r#"| | | | |
-| | " | |
-| |  / /
-| | | |
-"#
-        );
+        let string1 = to_string(&events1).unwrap();
+        let string2 = to_string(&events2).unwrap();
+
+        assert_eq!(string1, string2);
+
+        let events1 = [
+            StartTracks(&[TrackId(0), TrackId(1), TrackId(2)]),
+            SplitTrackAt(TrackId(1), TrackId(4), TrackId(1)),
+            NoEvent,
+        ];
+
+        let string1 = to_string(&events1).unwrap();
+
+        assert_eq!(string1, string2);
     }
 
     #[test]
-    fn stop_track_right() {
+    fn split_track_at_non_existing_from_track() {
         #[rustfmt::skip]
         let events = [
-            StartTracks(&[0, 1, 2, 3, 4]),
-            StopTrack(4),
-            NoEvent,
+            StartTracks(&[TrackId(0), TrackId(1), TrackId(2)]),
+            SplitTrackAt(TrackId(5), TrackId(4), TrackId(0)),
+            Station(TrackId(4), Cow::Borrowed("Station 1")),
         ];
         let string = to_string(&events).unwrap();
 
         assert_eq!(
             string,
-            r#"| | | | |
-| | | | "
+            r#"| | |
 | | | |
+| * | | Station 1
 "#
         );
     }
 
     #[test]
-    fn station() {
-        let events = [
-            StartTracks(&[0, 1, 2]),
-            Station(0, Cow::Borrowed("Station 1")),
-            Station(1, Cow::Borrowed("Station 2")),
-            Station(2, Cow::Borrowed("Station 3")),
-            Station(0, Cow::Borrowed("Station 4")),
-            Station(1, Cow::Borrowed("Station 5")),
-            Station(2, Cow::Borrowed("Station 6")),
+    fn split_track_at_already_existing_new_track() {
+        let events1 = [
+            StartTracks(&[TrackId(0), TrackId(1), TrackId(2)]),
+            SplitTrackAt(TrackId(0), TrackId(2), TrackId(1)),
         ];
-        let string = to_string(&events).unwrap();
+        let events2 = [StartTracks(&[TrackId(0), TrackId(1), TrackId(2)])];
 
-        assert_eq!(
-            string,
-            r#"| | |
-* | | Station 1
-| * | Station 2
-| | * Station 3
-* | | Station 4
-| * | Station 5
-| | * Station 6
-"#
-        );
+        let string1 = to_string(&events1).unwrap();
+        let string2 = to_string(&events2).unwrap();
+
+        assert_eq!(string1, string2);
     }
 
     #[test]
-    fn station_non_existing_track() {
+    fn stop_track_no_trailing_whitespace() {
+        fn assert_trimmed(events: &[Event]) {
+            let string = to_string(events).unwrap();
+
+            for line in string.lines() {
+                assert_eq!(line.trim_end(), line, "{:?}", events);
+            }
+        }
+
+        let tracks = [0, 1, 2, 3, 4, 5].map(TrackId);
+
+        for count in 1..=tracks.len() {
+            for i in 0..count {
+                assert_trimmed(&[StartTracks(&tracks[..count]), StopTrack(tracks[i]), NoEvent]);
+
+                for j in i..count {
+                    assert_trimmed(&[
+                        StartTracks(&tracks[..count]),
+                        StopTracks(&[tracks[i], tracks[j]]),
+                        NoEvent,
+                    ]);
+                    assert_trimmed(&[
+                        StartTracks(&tracks[..count]),
+                        JoinTrack(tracks[j], tracks[i]),
+                        NoEvent,
+                    ]);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn rail_spacing() {
         let events = [
-            StartTracks(&[0, 1, 2]),
-            Station(0, Cow::Borrowed("Station 1")),
-            Station(1, Cow::Borrowed("Station 2")),
-            Station(2, Cow::Borrowed("Station 3")),
-            Station(3, Cow::Borrowed("Station 4")),
-            Station(4, Cow::Borrowed("Station 5")),
-            Station(5, Cow::Borrowed("Station 6")),
-            Station(std::usize::MAX, Cow::Borrowed("Station 7")),
+            StartTracks(&[TrackId(0), TrackId(1), TrackId(2), TrackId(3)]),
+            Station(TrackId(1), Cow::Borrowed("Station 1")),
+            SplitTrack(TrackId(1), TrackId(4)),
+            JoinTrack(TrackId(3), TrackId(0)),
+            StopTrack(TrackId(2)),
+            NoEvent,
         ];
-        let string = to_string(&events).unwrap();
+        let opts = Options {
+            rail_spacing: 3,
+            ..Options::default()
+        };
+        let string = to_string_with(&events, &opts).unwrap();
 
         assert_eq!(
             string,
-            r#"| | |
-* | | Station 1
-| * | Station 2
-| | * Station 3
-| | | Station 4
-| | | Station 5
-| | | Station 6
-| | | Station 7
+            r#"|   |   |   |
+|   *   |   |   Station 1
+|   |\__ \__ \__
+|   |___|___|__/
+|__/|   |   |
+|   |   |   "
+|   |   |
 "#
         );
     }
 
     #[test]
-    fn station_multiple_lines() {
+    fn rail_spacing_legend() {
         let events = [
-            StartTracks(&[0, 1, 2]),
-            Station(0, Cow::Borrowed("Foo 0\nBar 0\r\nBaz 0")),
-            Station(1, Cow::Borrowed("Foo 1\nBar 1\r\nBaz 1")),
-            Station(2, Cow::Borrowed("Foo 2\nBar 2\r\nBaz 2")),
-            Station(3, Cow::Borrowed("Foo 3\nBar 3\r\nBaz 3")),
-            Station(4, Cow::Borrowed("Foo 4\nBar 4\r\nBaz 4")),
-            Station(5, Cow::Borrowed("Foo 5\nBar 5\r\nBaz 5")),
-            Station(
-                std::usize::MAX,
-                Cow::Borrowed("Foo MAX\nBar MAX\r\nBaz MAX"),
-            ),
+            StartTracks(&[TrackId(0), TrackId(1), TrackId(2)]),
+            LabelTrack(TrackId(0), Cow::Borrowed("main")),
+            LabelTrack(TrackId(2), Cow::Borrowed("feature/x")),
+            Legend,
         ];
-        let string = to_string(&events).unwrap();
+        let opts = Options {
+            rail_spacing: 2,
+            ..Options::default()
+        };
+        let string = to_string_with(&events, &opts).unwrap();
 
         assert_eq!(
             string,
-            r#"| | |
-* | | Foo 0
-| | | Bar 0
-| | | Baz 0
-| * | Foo 1
-| | | Bar 1
-| | | Baz 1
-| | * Foo 2
-| | | Bar 2
-| | | Baz 2
-| | | Foo 3
-| | | Bar 3
-| | | Baz 3
-| | | Foo 4
-| | | Bar 4
-| | | Baz 4
-| | | Foo 5
-| | | Bar 5
-| | | Baz 5
-| | | Foo MAX
-| | | Bar MAX
-| | | Baz MAX
+            r#"|  |  |
+main
+|  |  feature/x
 "#
         );
     }
 
     #[test]
-    fn split_track() {
+    fn rail_spacing_one() {
         let events = [
-            SplitTrack(0, 1),
+            StartTracks(&[TrackId(0), TrackId(1), TrackId(2), TrackId(3)]),
+            SplitTrack(TrackId(1), TrackId(4)),
+            JoinTrack(TrackId(3), TrackId(0)),
+            StopTrack(TrackId(2)),
             NoEvent,
-            SplitTrack(0, 2),
-            SplitTrack(1, 3),
-            SplitTrack(3, 4),
         ];
-        let string = to_string(&events).unwrap();
+        let opts = Options {
+            rail_spacing: 1,
+            ..Options::default()
+        };
 
         assert_eq!(
-            string,
-            r#"|\
-| |
-|\ \
-| | |\
-| | | |\
-"#
+            to_string_with(&events, &opts).unwrap(),
+            to_string(&events).unwrap()
         );
     }
 
     #[test]
-    fn split_track_non_existing_from_track() {
+    fn join_track_toward_left() {
         let events1 = [
-            SplitTrack(1, 2),
-            SplitTrack(3, 4),
-            Station(2, Cow::Borrowed("2")),
-            Station(4, Cow::Borrowed("4")),
+            StartTracks(&[TrackId(0), TrackId(1), TrackId(2), TrackId(3)]),
+            JoinTrackToward(TrackId(2), TrackId(0)),
+            NoEvent,
         ];
         let events2 = [
-            StartTrack(2),
-            StartTrack(4),
-            Station(2, Cow::Borrowed("2")),
-            Station(4, Cow::Borrowed("4")),
+            StartTracks(&[TrackId(0), TrackId(1), TrackId(2), TrackId(3)]),
+            JoinTrack(TrackId(2), TrackId(0)),
+            NoEvent,
         ];
 
         let string1 = to_string(&events1).unwrap();
@@ -873,80 +6444,84 @@ mod tests {
     }
 
     #[test]
-    fn split_track_already_existing_new_track() {
-        let events1 = [
-            StartTracks(&[0, 1, 2]),
-            SplitTrack(0, 1),
-            SplitTrack(0, 2),
-            SplitTrack(3, 4),
-            Station(0, Cow::Borrowed("0")),
-            Station(1, Cow::Borrowed("1")),
-            Station(2, Cow::Borrowed("2")),
-            Station(3, Cow::Borrowed("3")),
-            Station(4, Cow::Borrowed("4")),
-        ];
-        let events2 = [
-            StartTracks(&[0, 1, 2]),
-            StartTrack(4),
-            Station(0, Cow::Borrowed("0")),
-            Station(1, Cow::Borrowed("1")),
-            Station(2, Cow::Borrowed("2")),
-            Station(3, Cow::Borrowed("3")),
-            Station(4, Cow::Borrowed("4")),
+    fn join_track_toward_right() {
+        let events = [
+            StartTracks(&[TrackId(0), TrackId(1), TrackId(2), TrackId(3)]),
+            JoinTrackToward(TrackId(0), TrackId(2)),
+            Event::station(2, "Station 1"),
+            JoinTrackToward(TrackId(1), TrackId(3)),
+            Event::station(3, "Station 2"),
         ];
+        let string = to_string(&events).unwrap();
 
-        let string1 = to_string(&events1).unwrap();
-        let string2 = to_string(&events2).unwrap();
-
-        assert_eq!(string1, string2);
+        assert_eq!(
+            string,
+            r#"| | | |
+ \| | |
+  |\| |
+ / / /
+| * | Station 1
+ \| |
+  |\|
+ / /
+| * Station 2
+"#
+        );
     }
 
     #[test]
-    fn split_track_same_from_and_new_track() {
-        let events = [SplitTrack(0, 0)];
-        let string = to_string(&events).unwrap();
+    fn event_eq() {
+        use std::collections::HashSet;
 
-        assert_eq!(string, "");
+        assert_eq!(
+            Event::station(0, "Station"),
+            Station(TrackId(0), Cow::Owned("Station".to_string()))
+        );
+        assert_ne!(Event::station(0, "Station"), Event::station(1, "Station"));
+        assert_ne!(
+            JoinTrack(TrackId(1), TrackId(0)),
+            JoinTrackToward(TrackId(1), TrackId(0))
+        );
 
-        #[rustfmt::skip]
-        let events1 = [
-            StartTracks(&[0, 1, 2]),
-            SplitTrack(1, 1),
-            SplitTrack(0, 2),
+        let events = [
+            NoEvent,
+            StartTracks(&[TrackId(1), TrackId(2)]),
+            NoEvent,
+            StartTracks(&[TrackId(1), TrackId(2)]),
         ];
-        let events2 = [StartTracks(&[0, 1, 2])];
+        let unique = events.iter().collect::<HashSet<_>>();
 
-        let string1 = to_string(&events1).unwrap();
-        let string2 = to_string(&events2).unwrap();
-
-        assert_eq!(string1, string2);
+        assert_eq!(unique.len(), 2);
     }
 
     #[test]
-    fn join_track_zero_between() {
-        #[rustfmt::skip]
+    fn link() {
         let events = [
-            StartTracks(&[0, 1, 2]),
-            JoinTrack(1, 0),
+            StartTracks(&[TrackId(0), TrackId(1), TrackId(2), TrackId(3)]),
+            Link(TrackId(2), TrackId(0)),
+            Link(TrackId(2), TrackId(3)),
+            Link(TrackId(1), TrackId(1)),
+            Link(TrackId(1), TrackId(4)),
             NoEvent,
         ];
         let string = to_string(&events).unwrap();
 
         assert_eq!(
             string,
-            r#"| | |
-|/ /
-| |
+            r#"| | | |
+|.|.| |
+| | |.|
+| | | |
 "#
         );
     }
 
     #[test]
-    fn join_track_one_between() {
-        #[rustfmt::skip]
+    fn station_multi() {
         let events = [
-            StartTracks(&[0, 1, 2]),
-            JoinTrack(2, 0),
+            StartTracks(&[TrackId(0), TrackId(1), TrackId(2)]),
+            Event::station_multi(&[TrackId(2), TrackId(0), TrackId(5)], "Station 1\nTags"),
+            Event::station_multi(&[TrackId(5)], "Station 2"),
             NoEvent,
         ];
         let string = to_string(&events).unwrap();
@@ -954,150 +6529,201 @@ mod tests {
         assert_eq!(
             string,
             r#"| | |
-| |/
-|/|
-| |
+* | * Station 1
+| | | Tags
+| | | Station 2
+| | |
 "#
         );
     }
 
     #[test]
-    fn join_track_many_between() {
-        #[rustfmt::skip]
+    fn rename_track() {
         let events = [
-            StartTracks(&[0, 1, 2, 3, 4]),
-            JoinTrack(4, 0),
-            NoEvent,
+            StartTracks(&[TrackId(0), TrackId(1), TrackId(2)]),
+            Event::label_track(1, "release"),
+            RenameTrack(TrackId(1), TrackId(5)),
+            RenameTrack(TrackId(2), TrackId(5)),
+            RenameTrack(TrackId(3), TrackId(6)),
+            Event::station(5, "Station 1"),
+            Event::station(1, "Station 2"),
+            StartTrack(TrackId(1)),
+            Legend,
         ];
         let string = to_string(&events).unwrap();
 
         assert_eq!(
             string,
-            r#"| | | | |
-| |_|_|/
-|/| | |
+            r#"| | |
+| * | Station 1
+| | | Station 2
 | | | |
+| release
 "#
         );
     }
 
     #[test]
-    fn join_track_always_leftmost() {
-        let events1 = [
-            StartTracks(&[0, 1, 2, 3, 4, 5, 6, 7, 8, 9]),
-            JoinTrack(4, 1),
-            JoinTrack(5, 0),
-            JoinTrack(8, 7),
-            JoinTrack(9, 6),
-            NoEvent,
+    fn join_track_or_rename() {
+        let events = [
+            StartTracks(&[TrackId(0), TrackId(1), TrackId(2)]),
+            Event::label_track(1, "release"),
+            JoinTrackOrRename(TrackId(1), TrackId(5)),
+            Event::station(5, "Station 1"),
+            Event::station(1, "Station 2"),
+            JoinTrackOrRename(TrackId(2), TrackId(5)),
+            JoinTrackOrRename(TrackId(3), TrackId(6)),
+            Legend,
         ];
+        let string = to_string(&events).unwrap();
 
-        let events2 = events1
-            .iter()
-            .cloned()
-            .map(|event| match event {
-                JoinTrack(from, to) => JoinTrack(to, from),
-                _ => event,
-            })
-            .collect::<Vec<_>>();
+        assert_eq!(
+            string,
+            r#"| | |
+| |\ \
+| " | |
+|  / /
+| * | Station 1
+| | | Station 2
+| |/
+| release
+"#
+        );
+    }
 
-        let string1 = to_string(&events1).unwrap();
-        let string2 = to_string(&events2).unwrap();
+    #[test]
+    fn start_track_at() {
+        let events = [
+            StartTracks(&[TrackId(0), TrackId(1), TrackId(2)]),
+            StartTrackAt(TrackId(3), 1),
+            Event::station(3, "Station 1"),
+            StartTrackAt(TrackId(4), 0),
+            StartTrackAt(TrackId(5), 100),
+            StartTrackAt(TrackId(1), 0),
+            Event::station(5, "Station 2"),
+        ];
+        let string = to_string(&events).unwrap();
 
-        // Note these only visually looks the same,
-        // the actual deleted track is not the same.
-        assert_eq!(string1, string2);
+        assert_eq!(
+            string,
+            r#"| | |
+|  \ \
+| | | |
+| * | | Station 1
+ \ \ \ \
+| | | | |
+| | | | | |
+| | | | | * Station 2
+"#
+        );
     }
 
-    // The above `join_track` tests are all `existing_from_track_existing_to_track`
-
     #[test]
-    fn join_track_non_existing_from_track_existing_to_track() {
-        let events1 = [
-            StartTracks(&[0, 1, 2, 3, 4]),
-            JoinTrack(5, 0),
-            JoinTrack(10, 1),
+    fn no_trailing_newline() {
+        let events = [
+            StartTracks(&[TrackId(0), TrackId(1), TrackId(2)]),
+            Event::station(1, "Station 1\nLine 2"),
+            JoinTrack(TrackId(2), TrackId(0)),
         ];
-        let events2 = [StartTracks(&[0, 1, 2, 3, 4])];
 
-        let string1 = to_string(&events1).unwrap();
-        let string2 = to_string(&events2).unwrap();
+        for direction in [Direction::LeftToRight, Direction::RightToLeft] {
+            let opts = Options {
+                direction,
+                ..Options::default()
+            };
+            let string = to_string_with(&events, &opts).unwrap();
+
+            let opts = Options {
+                trailing_newline: false,
+                ..opts
+            };
+            let trimmed = to_string_with(&events, &opts).unwrap();
+
+            assert_eq!(trimmed, string.strip_suffix('\n').unwrap());
+            assert!(trimmed.contains('\n'));
+        }
 
-        assert_eq!(string1, string2);
+        let opts = Options {
+            trailing_newline: false,
+            ..Options::default()
+        };
+        assert_eq!(to_string_with(&[], &opts).unwrap(), "");
     }
 
     #[test]
-    fn join_track_non_existing_from_track_non_existing_to_track() {
-        let events1 = [
-            StartTracks(&[0, 1, 2, 3, 4]),
-            JoinTrack(5, 6),
-            JoinTrack(10, 11),
+    fn renderer() {
+        let events = [
+            Event::station(0, "Station 1"),
+            SplitTrack(TrackId(0), TrackId(1)),
+            LabelTrack(TrackId(1), Cow::Borrowed("Track 1")),
+            Event::station(1, "Station 2"),
+            Legend,
+            JoinTrack(TrackId(1), TrackId(0)),
+            Event::station(0, "Station 3"),
         ];
-        let events2 = [StartTracks(&[0, 1, 2, 3, 4])];
 
-        let string1 = to_string(&events1).unwrap();
-        let string2 = to_string(&events2).unwrap();
+        for direction in [Direction::LeftToRight, Direction::RightToLeft] {
+            let opts = Options {
+                direction,
+                ..Options::default()
+            };
 
-        assert_eq!(string1, string2);
+            let mut renderer = Renderer::with_options(Vec::new(), opts.clone());
+
+            for event in &events {
+                // The events only need to outlive each `push`
+                let event = event.clone();
+                renderer.push(&event).unwrap();
+            }
+
+            let string = String::from_utf8(renderer.finish().unwrap()).unwrap();
+
+            assert_eq!(string, to_string_with(&events, &opts).unwrap());
+        }
     }
 
     #[test]
-    fn join_track_existing_from_track_non_existing_to_track() {
-        let events1 = [
-            StartTracks(&[0, 1, 2, 3, 4]),
-            JoinTrack(0, 5),
-            JoinTrack(2, 10),
-        ];
-        #[rustfmt::skip]
-        let events2 = [
-            StartTracks(&[0, 1, 2, 3, 4]),
-            StopTrack(0),
-            StopTrack(2),
+    fn estimate_capacity() {
+        let events = [
+            Event::station(0, "Station 1"),
+            SplitTrack(TrackId(0), TrackId(1)),
+            Event::station(1, "Station 2\nStation 3"),
+            Event::station(0, "Station 4"),
+            JoinTrack(TrackId(1), TrackId(0)),
+            Event::station(0, "Station 5"),
         ];
 
-        let string1 = to_string(&events1).unwrap();
-        let string2 = to_string(&events2).unwrap();
+        let vec = super::to_vec(&events).unwrap();
+        let capacity = super::estimate_capacity(&events, &Options::default());
 
-        assert_eq!(string1, string2);
+        assert!(capacity >= vec.len());
+        assert!(capacity <= vec.len() * 2);
     }
 
     #[test]
-    fn join_track_same_from_and_to_track() {
+    fn annotation() {
         let events1 = [
-            StartTracks(&[0, 1, 2, 3, 4]),
-            JoinTrack(0, 0),
-            JoinTrack(2, 2),
-            JoinTrack(10, 10),
+            StartTracks(&[TrackId(0), TrackId(1), TrackId(2)]),
+            Annotation(Cow::Borrowed("Hello\nWorld")),
+            NoEvent,
         ];
-        #[rustfmt::skip]
         let events2 = [
-            StartTracks(&[0, 1, 2, 3, 4]),
-            StopTrack(0),
-            StopTrack(2),
+            StartTracks(&[TrackId(0), TrackId(1), TrackId(2)]),
+            DetachedStation(Cow::Borrowed("Hello\nWorld")),
+            NoEvent,
         ];
 
         let string1 = to_string(&events1).unwrap();
         let string2 = to_string(&events2).unwrap();
 
         assert_eq!(string1, string2);
-    }
-
-    #[test]
-    fn no_event() {
-        let events = [NoEvent, NoEvent, NoEvent];
-        let string = to_string(&events).unwrap();
-
-        assert_eq!(string, "|\n|\n|\n");
-
-        #[rustfmt::skip]
-        let events = [
-            StartTracks(&[0, 1, 2]),
-            NoEvent,
-            NoEvent,
-            NoEvent,
-        ];
-        let string = to_string(&events).unwrap();
-
-        assert_eq!(string, "| | |\n| | |\n| | |\n| | |\n");
+        assert_eq!(
+            string1,
+            r#"| | |
+| | | Hello
+| | | World
+| | |
+"#
+        );
     }
 }
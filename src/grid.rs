@@ -0,0 +1,740 @@
+use std::ops::Range;
+
+use crate::events::{lines, render_event, Event, Glyph, RenderState, Row};
+use crate::track_id::TrackId;
+
+/// Render `&[`[`Event`]`]` into a grid of `char` cells, e.g. for
+/// drawing the graph into the buffer of a cell-based TUI.
+/// Defines a default track with `track_id` of `0`.
+///
+/// Each row of the grid is a line produced by [`to_string`], without
+/// the line ending. All rows are right-padded with spaces to the width
+/// of the widest row, such that the grid is rectangular.
+///
+/// Note that each `char` is a single cell, so wide characters in
+/// station text, e.g. `"こんにちは"`, still only occupy a single cell.
+///
+/// [`Event`]: enum.Event.html
+/// [`to_string`]: fn.to_string.html
+///
+/// # Example
+///
+/// ```
+/// use metro::{Event, TrackId};
+///
+/// let events = [
+///     Event::station(0, "Station 1"),
+///     Event::SplitTrack(TrackId(0), TrackId(1)),
+/// ];
+///
+/// let grid = metro::to_grid(&events);
+///
+/// assert_eq!(grid.len(), 2);
+/// assert_eq!(grid[0].iter().collect::<String>(), "* Station 1");
+/// assert_eq!(grid[1].iter().collect::<String>(), "|\\         ");
+/// ```
+pub fn to_grid(events: &[Event]) -> Vec<Vec<char>> {
+    let mut grid = lines(events)
+        .map(|line| line.chars().collect::<Vec<_>>())
+        .collect::<Vec<_>>();
+
+    let width = grid.iter().map(Vec::len).max().unwrap_or(0);
+
+    for row in grid.iter_mut() {
+        row.resize(width, ' ');
+    }
+
+    grid
+}
+
+/// A single cell of a grid produced by [`to_styled_grid`].
+///
+/// [`to_styled_grid`]: fn.to_styled_grid.html
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct Cell {
+    /// The `char` rendered in the cell.
+    pub c: char,
+    /// The id of the track the cell belongs to, if any.
+    ///
+    /// Diagonals belong to the track moving between the columns,
+    /// e.g. the `/` of a join belongs to the track being joined.
+    pub track_id: Option<TrackId>,
+    /// What the cell contains.
+    pub kind: CellKind,
+    /// Whether the cell is within the highlighted rows of
+    /// [`to_styled_grid_highlighted`].
+    ///
+    /// [`to_styled_grid_highlighted`]: fn.to_styled_grid_highlighted.html
+    pub highlighted: bool,
+}
+
+/// What a [`Cell`] contains.
+///
+/// [`Cell`]: struct.Cell.html
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum CellKind {
+    /// A part of the rails, e.g. a rail, a station or a diagonal.
+    /// Padding and the gap before the text are [`Glyph::Space`].
+    ///
+    /// [`Glyph::Space`]: enum.Glyph.html#variant.Space
+    Glyph(Glyph),
+    /// A `char` of text, e.g. of a station or a label.
+    Text,
+}
+
+impl Cell {
+    const SPACE: Self = Self {
+        c: ' ',
+        track_id: None,
+        kind: CellKind::Glyph(Glyph::Space),
+        highlighted: false,
+    };
+}
+
+/// Render `&[`[`Event`]`]` into a grid of [`Cell`]s, which in addition
+/// to the `char` of [`to_grid`] carries the track id and kind of each
+/// cell, e.g. for coloring each track differently.
+/// Defines a default track with `track_id` of `0`.
+///
+/// Like [`to_grid`], all rows are right-padded to the width of
+/// the widest row, such that the grid is rectangular.
+///
+/// [`Event`]: enum.Event.html
+/// [`Cell`]: struct.Cell.html
+/// [`to_grid`]: fn.to_grid.html
+///
+/// # Example
+///
+/// ```
+/// use metro::{CellKind, Event, Glyph, TrackId};
+///
+/// let events = [
+///     Event::station(0, "Station 1"),
+///     Event::SplitTrack(TrackId(0), TrackId(1)),
+/// ];
+///
+/// let grid = metro::to_styled_grid(&events);
+///
+/// assert_eq!(grid[0][0].kind, CellKind::Glyph(Glyph::Station));
+/// assert_eq!(grid[0][2].kind, CellKind::Text);
+///
+/// assert_eq!(grid[1][1].c, '\\');
+/// assert_eq!(grid[1][1].track_id, Some(TrackId(1)));
+/// ```
+pub fn to_styled_grid(events: &[Event]) -> Vec<Vec<Cell>> {
+    let mut grid = Vec::new();
+
+    for (row, tracks) in track_rows(events) {
+        let mut cells = row
+            .cells
+            .iter()
+            .zip(tracks)
+            .map(|(&glyph, track_id)| Cell {
+                c: glyph.to_char(),
+                track_id: track_id.filter(|_| glyph != Glyph::Space),
+                kind: CellKind::Glyph(glyph),
+                highlighted: false,
+            })
+            .collect::<Vec<_>>();
+
+        let text = row.text.as_deref().unwrap_or_default();
+        let mut text_lines = text.lines();
+
+        if let Some(text_line) = text_lines.next() {
+            cells.push(Cell::SPACE);
+            cells.extend(text_line.chars().map(text_cell));
+        }
+
+        grid.push(cells);
+
+        for text_line in text_lines {
+            grid.push(text_line.chars().map(text_cell).collect());
+        }
+    }
+
+    let width = grid.iter().map(Vec::len).max().unwrap_or(0);
+
+    for row in grid.iter_mut() {
+        row.resize(width, Cell::SPACE);
+    }
+
+    grid
+}
+
+fn text_cell(c: char) -> Cell {
+    Cell {
+        c,
+        track_id: None,
+        kind: CellKind::Text,
+        highlighted: false,
+    }
+}
+
+/// Render `&[`[`Event`]`]` into a grid of [`Cell`]s like [`to_styled_grid`],
+/// where the cells of all rows produced by the events within the range
+/// `highlight` of event indices are [`highlighted`], e.g. for shading
+/// the commits of a pull request.
+/// Defines a default track with `track_id` of `0`.
+///
+/// *[See `event_row_ranges` for the rows produced by each event.][`event_row_ranges`]*
+///
+/// [`Event`]: enum.Event.html
+/// [`Cell`]: struct.Cell.html
+/// [`to_styled_grid`]: fn.to_styled_grid.html
+/// [`highlighted`]: struct.Cell.html#structfield.highlighted
+/// [`event_row_ranges`]: fn.event_row_ranges.html
+///
+/// # Example
+///
+/// ```
+/// use metro::{Event, TrackId};
+///
+/// let events = [
+///     Event::station(0, "Station 1"),
+///     Event::SplitTrack(TrackId(0), TrackId(1)),
+///     Event::station(1, "Station 2"),
+///     Event::JoinTrack(TrackId(1), TrackId(0)),
+/// ];
+///
+/// let grid = metro::to_styled_grid_highlighted(&events, 1..3);
+///
+/// let highlighted = grid
+///     .iter()
+///     .map(|row| row.iter().all(|cell| cell.highlighted))
+///     .collect::<Vec<_>>();
+///
+/// // * Station 1
+/// // |\
+/// // | * Station 2
+/// // |/
+/// assert_eq!(highlighted, [false, true, true, false]);
+/// ```
+pub fn to_styled_grid_highlighted(events: &[Event], highlight: Range<usize>) -> Vec<Vec<Cell>> {
+    let mut grid = to_styled_grid(events);
+
+    let ranges = event_row_ranges(events);
+    let row_count = grid.len();
+
+    let start = ranges
+        .get(highlight.start)
+        .map_or(row_count, |rows| rows.start);
+    let end = match highlight.end.checked_sub(1) {
+        Some(last) => ranges.get(last).map_or(row_count, |rows| rows.end),
+        None => 0,
+    };
+
+    for row in grid.iter_mut().take(end).skip(start) {
+        for cell in row.iter_mut() {
+            cell.highlighted = true;
+        }
+    }
+
+    grid
+}
+
+/// Returns the range of rows produced by each of the `&[`[`Event`]`]`,
+/// i.e. the indices of the lines produced by [`to_string`], and the
+/// rows of [`to_grid`]. Events that produce no rows have an empty range.
+/// Defines a default track with `track_id` of `0`.
+///
+/// As events do not map one-to-one to rows, e.g. joins and stations
+/// with multiple lines produce multiple rows, this translates event
+/// indices to row indices.
+///
+/// [`Event`]: enum.Event.html
+/// [`to_string`]: fn.to_string.html
+/// [`to_grid`]: fn.to_grid.html
+///
+/// # Example
+///
+/// ```
+/// use metro::{Event, TrackId};
+///
+/// let events = [
+///     Event::station(0, "Station 1\nWith multiple lines"),
+///     Event::StartTrack(TrackId(0)),
+///     Event::JoinTrack(TrackId(0), TrackId(1)),
+/// ];
+///
+/// // * Station 1
+/// // | With multiple lines
+/// // "
+/// assert_eq!(metro::event_row_ranges(&events), [0..2, 2..2, 2..3]);
+/// ```
+pub fn event_row_ranges(events: &[Event]) -> Vec<Range<usize>> {
+    let mut state = RenderState::new();
+    let mut rows = Vec::new();
+
+    let mut ranges = Vec::with_capacity(events.len());
+    let mut start = 0;
+
+    for event in events {
+        render_event(&mut rows, &mut state, event);
+
+        // The text of a row may span multiple lines, e.g. a label in a legend
+        let end = start
+            + rows
+                .iter()
+                .map(|row| {
+                    row.text
+                        .as_deref()
+                        .map_or(1, |text| text.lines().count().max(1))
+                })
+                .sum::<usize>();
+
+        ranges.push(start..end);
+        start = end;
+
+        rows.clear();
+    }
+
+    ranges
+}
+
+/// Render `&[`[`Event`]`]` into the track id of each column of each
+/// line produced by [`to_string`], e.g. for aligning extra columns of
+/// annotations against the graph.
+/// Defines a default track with `track_id` of `0`.
+///
+/// Each row contains the track ids of the rails in column order,
+/// from left to right. A column is `None` if it has no rail in that
+/// line, e.g. while the rail is moving along a diagonal. Lines
+/// continuing the text of a multi-line station contain no columns.
+///
+/// [`Event`]: enum.Event.html
+/// [`to_string`]: fn.to_string.html
+///
+/// # Example
+///
+/// ```
+/// use metro::{Event, TrackId};
+///
+/// let events = [
+///     Event::station(0, "Station 1"),
+///     Event::SplitTrack(TrackId(0), TrackId(1)),
+///     Event::station(1, "Station 2"),
+///     Event::JoinTrack(TrackId(1), TrackId(0)),
+/// ];
+///
+/// let columns = metro::columns_per_row(&events);
+///
+/// // * Station 1
+/// // |\
+/// // | * Station 2
+/// // |/
+/// assert_eq!(columns.len(), 4);
+/// assert_eq!(columns[0], [Some(TrackId(0))]);
+/// assert_eq!(columns[1], [Some(TrackId(0))]);
+/// assert_eq!(columns[2], [Some(TrackId(0)), Some(TrackId(1))]);
+/// assert_eq!(columns[3], [Some(TrackId(0))]);
+/// ```
+pub fn columns_per_row(events: &[Event]) -> Vec<Vec<Option<TrackId>>> {
+    let mut columns = Vec::new();
+
+    for (row, tracks) in track_rows(events) {
+        columns.push(
+            row.cells
+                .iter()
+                .zip(tracks)
+                .step_by(2)
+                .map(|(&glyph, track_id)| match glyph {
+                    Glyph::Rail | Glyph::Station | Glyph::Stop | Glyph::Marker(_) => track_id,
+                    _ => None,
+                })
+                .collect(),
+        );
+
+        let text_lines = row.text.as_deref().map_or(0, |text| text.lines().count());
+        for _ in 1..text_lines {
+            columns.push(Vec::new());
+        }
+    }
+
+    columns
+}
+
+/// Renders `events` into rows, along with the track id of each cell
+/// of each row.
+pub(crate) fn track_rows<'a>(events: &'a [Event]) -> Vec<(Row<'a>, Vec<Option<TrackId>>)> {
+    let mut state = RenderState::new();
+    let mut rows = Vec::new();
+    let mut row_tracks = Vec::new();
+
+    for event in events {
+        let tracks = state.tracks.clone();
+        let start = rows.len();
+
+        render_event(&mut rows, &mut state, event);
+
+        // A rail branching off another rail belongs to a new track
+        let new_tracks = state
+            .tracks
+            .iter()
+            .filter(|id| !tracks.contains(id))
+            .copied()
+            .collect::<Vec<_>>();
+
+        let mut rails = tracks
+            .iter()
+            .enumerate()
+            .map(|(column, &track_id)| TrackedRail::new(track_id, column, false))
+            .collect::<Vec<_>>();
+
+        for (i, row) in rows.iter().enumerate().skip(start) {
+            // The rails of the last row of an event are the updated tracks
+            let last = if i + 1 == rows.len() {
+                Some(state.tracks.as_slice())
+            } else {
+                None
+            };
+
+            row_tracks.push(cell_tracks(row, &mut rails, &new_tracks, last));
+        }
+    }
+
+    rows.into_iter().zip(row_tracks).collect()
+}
+
+/// A rail followed through the rows of an event by [`cell_tracks`].
+///
+/// [`cell_tracks`]: fn.cell_tracks.html
+struct TrackedRail {
+    track_id: TrackId,
+    column: usize,
+    /// Whether the rail moved in the previous row.
+    moving: bool,
+}
+
+impl TrackedRail {
+    #[inline]
+    fn new(track_id: TrackId, column: usize, moving: bool) -> Self {
+        Self {
+            track_id,
+            column,
+            moving,
+        }
+    }
+}
+
+/// Returns the track id of each cell of `row`, and moves `rails` to
+/// their columns below `row`. If `row` is the last row of an event,
+/// then `last` are the tracks after the event.
+///
+/// Rails branching off another rail belong to the `new_tracks`
+/// of the event, in order, e.g. the fan of a `SplitTrackN`.
+///
+/// Diagonals and `_` belong to the rail moving along them. Multiple
+/// rails can momentarily share a column, e.g. when a joining rail
+/// passes below another rail, in which case a diagonal leaving the
+/// column belongs to the moving rail, and a rail to the other.
+fn cell_tracks(
+    row: &Row,
+    rails: &mut Vec<TrackedRail>,
+    new_tracks: &[TrackId],
+    last: Option<&[TrackId]>,
+) -> Vec<Option<TrackId>> {
+    let cells = &row.cells;
+    let mut branches = 0;
+    let mut ids = vec![None; cells.len()];
+    let mut taken = vec![false; rails.len()];
+    let mut next = Vec::with_capacity(rails.len());
+
+    let mut take = |column: usize, moving: bool| {
+        let found = (0..rails.len())
+            .filter(|&i| !taken[i] && rails[i].column == column)
+            .min_by_key(|&i| rails[i].moving != moving)?;
+        taken[found] = true;
+        Some(rails[found].track_id)
+    };
+
+    for gap in 0..(cells.len() / 2) {
+        match cells[gap * 2 + 1] {
+            Glyph::DiagonalLeft => {
+                let track_id = take(gap + 1, true);
+                ids[gap * 2 + 1] = track_id;
+
+                let mut column = gap;
+                while column > 0 && cells[column * 2 - 1] == Glyph::Horizontal {
+                    ids[column * 2 - 1] = track_id;
+                    if cells[column * 2] == Glyph::Horizontal {
+                        ids[column * 2] = track_id;
+                    }
+                    column -= 1;
+                }
+
+                if let Some(track_id) = track_id {
+                    next.push(TrackedRail::new(track_id, column, true));
+                }
+            }
+            Glyph::DiagonalRight => {
+                let track_id = if !new_tracks.is_empty() && (cells[gap * 2] != Glyph::Space) {
+                    let new_track = new_tracks[branches.min(new_tracks.len() - 1)];
+                    branches += 1;
+
+                    // The `_` before the diagonal of a fan leads to the new track
+                    if cells[gap * 2] == Glyph::Horizontal {
+                        ids[gap * 2] = Some(new_track);
+                    }
+
+                    Some(new_track)
+                } else {
+                    take(gap, true)
+                };
+                ids[gap * 2 + 1] = track_id;
+
+                let mut column = gap + 1;
+                while cells.get(column * 2 + 1) == Some(&Glyph::Horizontal) {
+                    ids[column * 2 + 1] = track_id;
+                    if cells[column * 2] == Glyph::Horizontal {
+                        ids[column * 2] = track_id;
+                    }
+                    column += 1;
+                }
+
+                if let Some(track_id) = track_id {
+                    next.push(TrackedRail::new(track_id, column, true));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    for (column, &cell) in cells.iter().step_by(2).enumerate() {
+        match cell {
+            Glyph::Rail | Glyph::Station | Glyph::Stop | Glyph::Marker(_) => {}
+            _ => continue,
+        }
+
+        // A stopped track is no longer among the updated tracks
+        let track_id = match last {
+            Some(tracks) if cell != Glyph::Stop => tracks.get(column).copied(),
+            _ => take(column, false),
+        };
+        ids[column * 2] = track_id;
+
+        if let (Some(track_id), false) = (track_id, cell == Glyph::Stop) {
+            next.push(TrackedRail::new(track_id, column, false));
+        }
+    }
+
+    *rails = next;
+    ids
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        columns_per_row, event_row_ranges, to_grid, to_styled_grid, to_styled_grid_highlighted,
+        CellKind,
+    };
+    use crate::to_string;
+    use crate::Event::{self, *};
+    use crate::Glyph;
+    use crate::TrackId;
+
+    #[test]
+    fn empty() {
+        assert!(to_grid(&[]).is_empty());
+    }
+
+    #[test]
+    fn rectangular() {
+        let events = [
+            StartTracks(&[TrackId(0), TrackId(1), TrackId(2)]),
+            Event::station(2, "Station 1\nWith multiple lines"),
+            JoinTrack(TrackId(1), TrackId(0)),
+            Event::label_track(0, "Label\nWith multiple lines"),
+            Legend,
+        ];
+
+        let grid = to_grid(&events);
+        let string = to_string(&events).unwrap();
+
+        assert_eq!(grid.len(), string.lines().count());
+        assert!(grid.iter().all(|row| row.len() == grid[0].len()));
+
+        for (row, line) in grid.iter().zip(string.lines()) {
+            let row = row.iter().collect::<String>();
+            assert_eq!(row.trim_end(), line.trim_end());
+        }
+    }
+
+    #[test]
+    fn styled_matches_grid() {
+        let events = [
+            StartTracks(&[TrackId(0), TrackId(1), TrackId(2)]),
+            Event::station(2, "Station 1\nWith multiple lines"),
+            JoinTrack(TrackId(1), TrackId(0)),
+            Event::label_track(0, "Label"),
+            StopTrack(TrackId(2)),
+            Event::station(0, "Station 2"),
+        ];
+
+        let grid = to_grid(&events);
+        let styled = to_styled_grid(&events);
+
+        assert_eq!(grid.len(), styled.len());
+
+        for (row, styled_row) in grid.iter().zip(styled.iter()) {
+            let styled_row = styled_row.iter().map(|cell| cell.c).collect::<Vec<_>>();
+            assert_eq!(row, &styled_row);
+        }
+    }
+
+    #[test]
+    fn styled_track_ids() {
+        let events = [
+            StartTracks(&[TrackId(0), TrackId(1), TrackId(2)]),
+            JoinTrack(TrackId(2), TrackId(0)),
+            Event::station(1, "Station"),
+        ];
+
+        let grid = to_styled_grid(&events);
+
+        let ids = |row: &[_]| {
+            row.iter()
+                .map(|cell: &super::Cell| cell.track_id)
+                .collect::<Vec<_>>()
+        };
+
+        // | | |
+        assert_eq!(
+            ids(&grid[0][..5]),
+            [
+                Some(TrackId(0)),
+                None,
+                Some(TrackId(1)),
+                None,
+                Some(TrackId(2))
+            ]
+        );
+        // | |/
+        assert_eq!(
+            ids(&grid[1][..4]),
+            [Some(TrackId(0)), None, Some(TrackId(1)), Some(TrackId(2))]
+        );
+        // |/|
+        assert_eq!(
+            ids(&grid[2][..3]),
+            [Some(TrackId(0)), Some(TrackId(2)), Some(TrackId(1))]
+        );
+        // | * Station
+        assert_eq!(grid[3][2].kind, CellKind::Glyph(Glyph::Station));
+        assert_eq!(grid[3][4].kind, CellKind::Text);
+        assert_eq!(grid[3][4].track_id, None);
+    }
+
+    #[test]
+    fn columns() {
+        let events = [
+            StartTracks(&[TrackId(0), TrackId(1), TrackId(2)]),
+            JoinTrack(TrackId(2), TrackId(0)),
+            Event::station(1, "Station 1\nWith multiple lines"),
+            StopTrack(TrackId(1)),
+            Event::label_track(0, "Label\nWith multiple lines"),
+            Legend,
+        ];
+
+        let columns = columns_per_row(&events);
+        let string = to_string(&events).unwrap();
+
+        assert_eq!(columns.len(), string.lines().count());
+
+        let (t0, t1, t2) = (Some(TrackId(0)), Some(TrackId(1)), Some(TrackId(2)));
+
+        #[rustfmt::skip]
+        assert_eq!(
+            columns,
+            [
+                vec![t0, t1, t2], // | | |
+                vec![t0, t1],     // | |/
+                vec![t0, t1],     // |/|
+                vec![t0, t1],     // | * Station 1
+                vec![t0, t1],     // | | With multiple lines
+                vec![t0, t1],     // | "
+                vec![],           // Label (legend)
+                vec![],           // With multiple lines
+            ]
+        );
+    }
+
+    #[test]
+    fn styled_split_track_n() {
+        let events = [Event::split_track_n(
+            0,
+            &[TrackId(1), TrackId(2), TrackId(3)][..],
+        )];
+
+        let grid = to_styled_grid(&events);
+
+        // |\_\_\
+        let ids = grid[0]
+            .iter()
+            .map(|cell| cell.track_id.map(|id| id.0))
+            .collect::<Vec<_>>();
+        assert_eq!(ids, [Some(0), Some(1), Some(2), Some(2), Some(3), Some(3)]);
+    }
+
+    #[test]
+    fn row_ranges() {
+        let events = [
+            StartTracks(&[TrackId(0), TrackId(1), TrackId(2)]),
+            Event::station(2, "Station 1\nWith multiple lines"),
+            NoEvent,
+            JoinTracks(&[TrackId(1), TrackId(2)], TrackId(0)),
+            Event::label_track(0, "Label\nWith multiple lines"),
+            Legend,
+            Event::station(0, "Station 2"),
+        ];
+
+        let ranges = event_row_ranges(&events);
+        let lines = to_string(&events).unwrap();
+        let lines = lines.lines().collect::<Vec<_>>();
+
+        assert_eq!(ranges, [0..1, 1..3, 3..4, 4..5, 5..5, 5..7, 7..8]);
+        assert_eq!(lines.len(), 8);
+
+        assert_eq!(lines[ranges[5].clone()], ["Label", "With multiple lines"]);
+    }
+
+    #[test]
+    fn highlighted() {
+        let events = [
+            Event::station(0, "Station 1"),
+            Event::station(0, "Station 2"),
+            Event::station(0, "Station 3"),
+        ];
+
+        let highlighted = |range| {
+            to_styled_grid_highlighted(&events, range)
+                .iter()
+                .map(|row| row.iter().all(|cell| cell.highlighted))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(highlighted(0..0), [false, false, false]);
+        assert_eq!(highlighted(1..2), [false, true, false]);
+        assert_eq!(highlighted(1..10), [false, true, true]);
+        assert_eq!(highlighted(5..10), [false, false, false]);
+    }
+
+    #[test]
+    fn styled_horizontal() {
+        let events = [
+            StartTracks(&[TrackId(0), TrackId(1), TrackId(2), TrackId(3), TrackId(4)]),
+            JoinTrack(TrackId(4), TrackId(0)),
+        ];
+
+        let grid = to_styled_grid(&events);
+
+        // | |_|_|/
+        assert_eq!(grid[1][7].c, '/');
+        assert!(grid[1][3..8]
+            .iter()
+            .step_by(2)
+            .all(|cell| cell.track_id == Some(TrackId(4))));
+        // |/| | |
+        assert_eq!(grid[2][1].c, '/');
+        assert_eq!(grid[2][1].track_id, Some(TrackId(4)));
+    }
+}
@@ -0,0 +1,137 @@
+use crate::events::{push_text, to_rows, Event, Glyph};
+
+/// Render `&[`[`Event`]`]` as a `String`, where the graph flows from
+/// left to right instead of from top to bottom, e.g. for a timeline.
+/// Defines a default track with `track_id` of `0`.
+///
+/// The graph is the transpose of the graph rendered by [`to_string`],
+/// i.e. each row produced by [`to_string`] turns into a column, and
+/// each track is a horizontal rail drawn using `-`. Splits and joins
+/// are drawn using `\` and `/`, `_` turns into `|`, and links into `:`.
+///
+/// The text of each column is placed below the graph, in a row of its
+/// own, starting at the column. The rows are ordered from the rightmost
+/// column to the leftmost, with `|` connecting the text to its column.
+///
+/// [`Event`]: enum.Event.html
+/// [`to_string`]: fn.to_string.html
+///
+/// # Example
+///
+/// ```
+/// use metro::{Event, TrackId};
+///
+/// let events = [
+///     Event::station(0, "Station 1"),
+///     Event::SplitTrack(TrackId(0), TrackId(1)),
+///     Event::station(1, "Station 2"),
+///     Event::JoinTrack(TrackId(1), TrackId(0)),
+///     Event::station(0, "Station 3"),
+/// ];
+///
+/// let string = metro::to_string_horizontal(&events);
+///
+/// assert_eq!(
+///     string,
+///     r#"*---*
+///  \ /
+///   *
+/// | | Station 3
+/// | Station 2
+/// Station 1
+/// "#
+/// );
+/// ```
+pub fn to_string_horizontal(events: &[Event]) -> String {
+    let rows = to_rows(events);
+
+    let height = rows.iter().map(|row| row.cells.len()).max().unwrap_or(0);
+
+    let mut string = String::new();
+
+    for i in 0..height {
+        let mut line = rows
+            .iter()
+            .map(|row| match row.cells.get(i) {
+                Some(&glyph) => transpose(glyph),
+                None => ' ',
+            })
+            .collect::<String>();
+
+        line.truncate(line.trim_end().len());
+
+        string.push_str(&line);
+        string.push('\n');
+    }
+
+    let texts = rows
+        .iter()
+        .enumerate()
+        .filter_map(|(column, row)| row.text.as_ref().map(|text| (column, text)))
+        .collect::<Vec<_>>();
+
+    for (i, &(column, text)) in texts.iter().enumerate().rev() {
+        let mut line = String::new();
+
+        for &(connector, _) in &texts[..i] {
+            if connector != column {
+                line.extend((line.len()..connector).map(|_| ' '));
+                line.push('|');
+            }
+        }
+
+        line.extend((line.len()..column).map(|_| ' '));
+        push_text(&mut line, text, column, None);
+
+        string.push_str(&line);
+        string.push('\n');
+    }
+
+    string
+}
+
+/// Returns the `char` of `glyph`, when the graph is transposed.
+fn transpose(glyph: Glyph) -> char {
+    match glyph {
+        Glyph::Rail => '-',
+        Glyph::Horizontal => '|',
+        Glyph::Link => ':',
+        glyph => glyph.to_char(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::to_string_horizontal;
+    use crate::Event::{self, *};
+    use crate::TrackId;
+
+    #[test]
+    fn empty() {
+        assert_eq!(to_string_horizontal(&[]), "");
+    }
+
+    #[test]
+    fn join_track_and_link() {
+        let events = [
+            StartTracks(&[TrackId(1), TrackId(2)]),
+            Link(TrackId(2), TrackId(0)),
+            JoinTrack(TrackId(1), TrackId(0)),
+            Event::annotation("Multiple\nlines"),
+            StopTrack(TrackId(2)),
+        ];
+        let string = to_string_horizontal(&events);
+
+        assert_eq!(
+            string,
+            r#"------
+ :/
+-- --"
+ :/
+--
+   |lines
+   Multiple
+"#
+        );
+    }
+}
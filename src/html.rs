@@ -0,0 +1,130 @@
+use std::fmt::Write;
+
+use crate::events::{Event, Glyph};
+use crate::grid::track_rows;
+
+/// Render `&[`[`Event`]`]` as an HTML `<table>`.
+/// Defines a default track with `track_id` of `0`.
+///
+/// Each row produced by [`to_string`] turns into a `<tr>`, where each
+/// rail, and each gap between two rails, is a `<td>`. Station text is
+/// placed in a final `<td class="text">`, with HTML special characters
+/// escaped.
+///
+/// Cells belonging to a track have the class `track-{track_id}`,
+/// such that individual tracks can be styled using CSS. Diagonals
+/// have the class of the track that is moving.
+///
+/// All rows are padded to the same number of cells, such that
+/// the station text is aligned in the same column.
+///
+/// [`Event`]: enum.Event.html
+/// [`to_string`]: fn.to_string.html
+///
+/// # Example
+///
+/// ```
+/// use metro::{Event, TrackId};
+///
+/// let events = [
+///     Event::station(0, "Station 1"),
+///     Event::SplitTrack(TrackId(0), TrackId(1)),
+///     Event::station(1, "Station 2"),
+///     Event::JoinTrack(TrackId(1), TrackId(0)),
+/// ];
+///
+/// let html = metro::to_html(&events);
+///
+/// assert!(html.starts_with("<table class=\"metro\">"));
+/// ```
+pub fn to_html(events: &[Event]) -> String {
+    let rows = track_rows(events);
+
+    let width = rows
+        .iter()
+        .map(|(row, _)| row.cells.len())
+        .max()
+        .unwrap_or(0);
+
+    let mut html = String::from("<table class=\"metro\">\n");
+
+    for (row, tracks) in rows.iter() {
+        html.push_str("<tr>");
+
+        for i in 0..width {
+            let cell = row.cells.get(i).copied().unwrap_or(Glyph::Space);
+
+            // Writing to a `String` never fails, so it is safe to `unwrap`
+            match tracks.get(i).copied().flatten() {
+                Some(track_id) if cell != Glyph::Space => {
+                    write!(html, "<td class=\"track-{}\">", track_id).unwrap()
+                }
+                _ => html.push_str("<td>"),
+            }
+
+            if cell != Glyph::Space {
+                push_escaped(&mut html, cell.to_char().encode_utf8(&mut [0; 4]));
+            }
+
+            html.push_str("</td>");
+        }
+
+        if let Some(text) = &row.text {
+            html.push_str("<td class=\"text\">");
+            push_escaped(&mut html, text);
+            html.push_str("</td>");
+        }
+
+        html.push_str("</tr>\n");
+    }
+
+    html.push_str("</table>\n");
+    html
+}
+
+fn push_escaped(html: &mut String, text: &str) {
+    for c in text.chars() {
+        match c {
+            '&' => html.push_str("&amp;"),
+            '<' => html.push_str("&lt;"),
+            '>' => html.push_str("&gt;"),
+            '"' => html.push_str("&quot;"),
+            '\'' => html.push_str("&#39;"),
+            c => html.push(c),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::to_html;
+    use crate::Event::{self, *};
+    use crate::TrackId;
+
+    #[test]
+    fn empty() {
+        assert_eq!(to_html(&[]), "<table class=\"metro\">\n</table>\n");
+    }
+
+    #[test]
+    fn split_join() {
+        let events = [
+            Event::station(0, "Station 1"),
+            SplitTrack(TrackId(0), TrackId(1)),
+            Event::station(1, "<Station 2>"),
+            JoinTrack(TrackId(1), TrackId(0)),
+        ];
+        let html = to_html(&events);
+
+        assert_eq!(
+            html,
+            r#"<table class="metro">
+<tr><td class="track-0">*</td><td></td><td></td><td class="text">Station 1</td></tr>
+<tr><td class="track-0">|</td><td class="track-1">\</td><td></td></tr>
+<tr><td class="track-0">|</td><td></td><td class="track-1">*</td><td class="text">&lt;Station 2&gt;</td></tr>
+<tr><td class="track-0">|</td><td class="track-1">/</td><td></td></tr>
+</table>
+"#
+        );
+    }
+}
@@ -0,0 +1,643 @@
+use std::slice;
+
+use crate::events::Event;
+use crate::track_id::TrackId;
+
+/// The geometry of `&[`[`Event`]`]` produced by [`layout`].
+///
+/// All coordinates are integers measured in half columns and half rows,
+/// such that the center of the rail at column index `i` is at `x = 2 * i + 1`,
+/// and the middle of row `r` is at `y = 2 * r + 1`. Multiply by half the
+/// column spacing and half the row height, to get the actual position.
+///
+/// [`Event`]: enum.Event.html
+/// [`layout`]: fn.layout.html
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Layout<'a> {
+    /// The position of every station, in the order they appear.
+    pub stations: Vec<StationPos>,
+    /// The rails, including splits and joins, as straight line segments.
+    pub segments: Vec<Segment>,
+    /// The `Link`s between tracks, as straight line segments.
+    pub links: Vec<Segment>,
+    /// The lines of station text, annotations and stop labels.
+    pub texts: Vec<TextPos<'a>>,
+    /// The width of the rails, i.e. two times the maximum number of rails.
+    /// Excludes the width of the text.
+    pub width: usize,
+    /// The height of the layout, i.e. two times the number of rows.
+    pub height: usize,
+}
+
+/// The position of a station in a [`Layout`].
+///
+/// [`Layout`]: struct.Layout.html
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct StationPos {
+    /// The id of the track the station is on.
+    pub track_id: TrackId,
+    /// The center of the station.
+    pub x: usize,
+    /// The center of the station.
+    pub y: usize,
+}
+
+/// A straight line segment in a [`Layout`].
+///
+/// [`Layout`]: struct.Layout.html
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct Segment {
+    /// The id of the track the segment belongs to.
+    ///
+    /// Diagonals belong to the track moving between the columns,
+    /// e.g. the diagonal of a join belongs to the track being joined,
+    /// and the diagonal of a split belongs to the new track.
+    /// Links belong to the track they start from.
+    pub track_id: TrackId,
+    /// The start of the segment, as `(x, y)`.
+    pub from: (usize, usize),
+    /// The end of the segment, as `(x, y)`.
+    pub to: (usize, usize),
+}
+
+/// A line of text in a [`Layout`].
+///
+/// [`Layout`]: struct.Layout.html
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct TextPos<'a> {
+    /// The left edge of the text, immediately after the rightmost rail.
+    pub x: usize,
+    /// The vertical middle of the text.
+    pub y: usize,
+    /// The line of text, excluding any newline.
+    pub text: &'a str,
+}
+
+/// Compute the [`Layout`] of `&[`[`Event`]`]`, i.e. the positions of
+/// stations and rails, independent of how they are rendered.
+/// Defines a default track with `track_id` of `0`.
+///
+/// Rails are vertical segments, while splits and joins are diagonal
+/// segments. This is the geometry used by [`to_svg`].
+///
+/// The rows of the layout do not map one-to-one with the rows
+/// produced by [`to_string`]. For instance a `JoinTrack` always
+/// occupies a single row, regardless of how many rails are
+/// between the joined tracks.
+///
+/// `LabelTrack` and `Legend` are currently ignored.
+///
+/// [`Layout`]: struct.Layout.html
+/// [`Event`]: enum.Event.html
+/// [`to_svg`]: fn.to_svg.html
+/// [`to_string`]: fn.to_string.html
+///
+/// # Example
+///
+/// ```
+/// use metro::{Event, Segment, StationPos, TrackId};
+///
+/// let events = [
+///     Event::station(0, "Station 1"),
+///     Event::SplitTrack(TrackId(0), TrackId(1)),
+///     Event::station(1, "Station 2"),
+/// ];
+///
+/// let layout = metro::layout(&events);
+///
+/// assert_eq!(
+///     layout.stations,
+///     [
+///         StationPos { track_id: TrackId(0), x: 1, y: 1 },
+///         StationPos { track_id: TrackId(1), x: 3, y: 5 },
+///     ]
+/// );
+///
+/// // The diagonal of the split
+/// assert!(layout.segments.contains(&Segment {
+///     track_id: TrackId(1),
+///     from: (1, 2),
+///     to: (3, 4),
+/// }));
+///
+/// assert_eq!((layout.width, layout.height), (4, 6));
+/// ```
+pub fn layout<'a>(events: &'a [Event]) -> Layout<'a> {
+    let mut layout = LayoutBuilder::new();
+
+    for event in events {
+        use Event::*;
+        match event {
+            &StartTrack(track_id) => {
+                if !layout.tracks.contains(&track_id) {
+                    let mut tracks = layout.tracks.clone();
+                    tracks.push(track_id);
+
+                    layout.row(tracks, &[], &[]);
+                }
+            }
+
+            &StartTracks(track_ids) => {
+                let mut tracks = layout.tracks.clone();
+
+                for &track_id in track_ids {
+                    if !tracks.contains(&track_id) {
+                        tracks.push(track_id);
+                    }
+                }
+
+                if tracks.len() != layout.tracks.len() {
+                    layout.row(tracks, &[], &[]);
+                }
+            }
+
+            &StartTrackAt(track_id, column_index) => {
+                if !layout.tracks.contains(&track_id) {
+                    let mut tracks = layout.tracks.clone();
+                    tracks.insert(column_index.min(tracks.len()), track_id);
+
+                    layout.row(tracks, &[], &[]);
+                }
+            }
+
+            &StopTrack(track_id) => layout.stop_track(track_id),
+
+            &StopTracks(track_ids) => layout.stop_tracks(|id| track_ids.contains(&id)),
+
+            StopTrackLabeled(track_id, label) => {
+                if layout.tracks.contains(track_id) {
+                    let mut lines = label.lines();
+
+                    if let Some(line) = lines.next() {
+                        layout.text(line);
+                    }
+
+                    layout.stop_track(*track_id);
+
+                    for line in lines {
+                        layout.text(line);
+                        layout.row(layout.tracks.clone(), &[], &[]);
+                    }
+                }
+            }
+
+            Station(track_id, text) | StationMarked(track_id, text, _) => {
+                layout.station(slice::from_ref(track_id), text);
+            }
+
+            StationMulti(track_ids, text) => layout.station(track_ids, text),
+
+            DetachedStation(text) => layout.station(&[], text),
+
+            Annotation(text) => {
+                for line in text.lines() {
+                    layout.text(line);
+                    layout.row(layout.tracks.clone(), &[], &[]);
+                }
+            }
+
+            &SplitTrack(from_track_id, new_track_id) => {
+                layout.split_track(from_track_id, new_track_id, from_track_id);
+            }
+
+            SplitTrackN(from_track_id, new_track_ids) => {
+                layout.split_tracks(*from_track_id, new_track_ids);
+            }
+
+            SplitStation(from_track_id, new_track_id, text) => {
+                layout.split_track(*from_track_id, *new_track_id, *from_track_id);
+                layout.station(slice::from_ref(new_track_id), text);
+            }
+
+            &SplitTrackAt(from_track_id, new_track_id, neighbor_track_id) => {
+                if layout.tracks.contains(&neighbor_track_id) {
+                    layout.split_track(from_track_id, new_track_id, neighbor_track_id);
+                } else {
+                    layout.split_track(from_track_id, new_track_id, from_track_id);
+                }
+            }
+
+            &JoinTrack(from_track_id, to_track_id)
+            | &JoinTrackToward(from_track_id, to_track_id)
+            | &JoinTrackKeepSource(to_track_id, from_track_id) => {
+                if from_track_id == to_track_id {
+                    layout.stop_track(from_track_id);
+                    continue;
+                }
+
+                let from_index = layout.tracks.iter().position(|&id| id == from_track_id);
+                let to_index = layout.tracks.iter().position(|&id| id == to_track_id);
+
+                match (from_index, to_index) {
+                    (Some(from_index), Some(_)) => {
+                        let mut tracks = layout.tracks.clone();
+                        tracks.remove(from_index);
+
+                        // Safe to use `unwrap` as only `from_track_id` was removed
+                        let to_index = tracks.iter().position(|&id| id == to_track_id).unwrap();
+
+                        layout.row(tracks, &[(from_index, to_index)], &[]);
+                    }
+                    (Some(_), None) => layout.stop_track(from_track_id),
+                    (None, _) => {}
+                }
+            }
+
+            &JoinTrackOrRename(from_track_id, to_track_id) => {
+                if from_track_id == to_track_id {
+                    layout.stop_track(from_track_id);
+                    continue;
+                }
+
+                let from_index = layout.tracks.iter().position(|&id| id == from_track_id);
+                let to_index = layout.tracks.iter().position(|&id| id == to_track_id);
+
+                match (from_index, to_index) {
+                    (Some(from_index), Some(_)) => {
+                        let mut tracks = layout.tracks.clone();
+                        tracks.remove(from_index);
+
+                        // Safe to use `unwrap` as only `from_track_id` was removed
+                        let to_index = tracks.iter().position(|&id| id == to_track_id).unwrap();
+
+                        layout.row(tracks, &[(from_index, to_index)], &[]);
+                    }
+                    (Some(_), None) => {
+                        layout.split_track(from_track_id, to_track_id, from_track_id);
+                        layout.stop_track(from_track_id);
+                    }
+                    (None, _) => {}
+                }
+            }
+
+            &JoinTracks(from_track_ids, to_track_id) => {
+                if !layout.tracks.contains(&to_track_id) || from_track_ids.contains(&to_track_id) {
+                    layout.stop_tracks(|id| (id != to_track_id) && from_track_ids.contains(&id));
+                    continue;
+                }
+
+                let mut tracks = layout.tracks.clone();
+                tracks.retain(|id| !from_track_ids.contains(id));
+
+                if tracks.len() != layout.tracks.len() {
+                    // Safe to use `unwrap` as `to_track_id` was not removed
+                    let to_index = tracks.iter().position(|&id| id == to_track_id).unwrap();
+
+                    let branches = (0..layout.tracks.len())
+                        .filter(|&i| from_track_ids.contains(&layout.tracks[i]))
+                        .map(|i| (i, to_index))
+                        .collect::<Vec<_>>();
+
+                    layout.row(tracks, &branches, &[]);
+                }
+            }
+
+            &Link(from_track_id, to_track_id) => {
+                let from_index = layout.tracks.iter().position(|&id| id == from_track_id);
+                let to_index = layout.tracks.iter().position(|&id| id == to_track_id);
+
+                if let (Some(from_index), Some(to_index)) = (from_index, to_index) {
+                    if from_index != to_index {
+                        let y = layout.mid_y();
+                        layout.links.push(Segment {
+                            track_id: from_track_id,
+                            from: (x(from_index), y),
+                            to: (x(to_index), y),
+                        });
+                        layout.row(layout.tracks.clone(), &[], &[]);
+                    }
+                }
+            }
+
+            &RenameTrack(old_track_id, new_track_id) => {
+                if !layout.tracks.contains(&new_track_id) {
+                    for track_id in layout.tracks.iter_mut() {
+                        if *track_id == old_track_id {
+                            *track_id = new_track_id;
+                        }
+                    }
+                }
+            }
+
+            LabelTrack(..) | Legend => {}
+
+            NoEvent => layout.row(layout.tracks.clone(), &[], &[]),
+        }
+    }
+
+    layout.finish()
+}
+
+/// The center of the rail at `index`.
+#[inline]
+fn x(index: usize) -> usize {
+    index * 2 + 1
+}
+
+struct LayoutBuilder<'a> {
+    tracks: Vec<TrackId>,
+    rows: usize,
+    segments: Vec<Segment>,
+    links: Vec<Segment>,
+    stations: Vec<StationPos>,
+    texts: Vec<TextPos<'a>>,
+    columns: usize,
+}
+
+impl<'a> LayoutBuilder<'a> {
+    #[inline]
+    fn new() -> Self {
+        Self {
+            tracks: vec![TrackId(0)],
+            rows: 0,
+            segments: Vec::new(),
+            links: Vec::new(),
+            stations: Vec::new(),
+            texts: Vec::new(),
+            columns: 0,
+        }
+    }
+
+    #[inline]
+    fn top_y(&self) -> usize {
+        self.rows * 2
+    }
+
+    #[inline]
+    fn mid_y(&self) -> usize {
+        self.top_y() + 1
+    }
+
+    #[inline]
+    fn bottom_y(&self) -> usize {
+        self.top_y() + 2
+    }
+
+    /// Adds `text` after the rightmost rail of the current row.
+    fn text(&mut self, text: &'a str) {
+        self.texts.push(TextPos {
+            x: self.tracks.len() * 2,
+            y: self.mid_y(),
+            text,
+        });
+    }
+
+    /// Adds a station on each of `track_ids`, followed by the lines of `text`.
+    fn station(&mut self, track_ids: &[TrackId], text: &'a str) {
+        for (i, line) in text.lines().enumerate() {
+            if i == 0 {
+                for (index, &track_id) in self.tracks.iter().enumerate() {
+                    if track_ids.contains(&track_id) {
+                        self.stations.push(StationPos {
+                            track_id,
+                            x: x(index),
+                            y: self.mid_y(),
+                        });
+                    }
+                }
+            }
+
+            self.text(line);
+            self.row(self.tracks.clone(), &[], &[]);
+        }
+    }
+
+    /// Produces a row of rails, going from the current `tracks` to `next`.
+    ///
+    /// - `branches` are `(from_index, to_index)` pairs of diagonals, where
+    ///   `from_index` is an index into the current `tracks` and `to_index`
+    ///   is an index into `next`.
+    /// - `ends` are indices into the current `tracks`, of rails which end
+    ///   in the middle of this row.
+    ///
+    /// Rails present in both `tracks` and `next` are connected, while
+    /// rails only present in `next` start in the middle of this row.
+    fn row(&mut self, next: Vec<TrackId>, branches: &[(usize, usize)], ends: &[usize]) {
+        let (top_y, mid_y, bottom_y) = (self.top_y(), self.mid_y(), self.bottom_y());
+
+        for (to_index, &track_id) in next.iter().enumerate() {
+            let from_index = self.tracks.iter().position(|&id| id == track_id);
+
+            if let Some(from_index) = from_index {
+                self.segments.push(Segment {
+                    track_id,
+                    from: (x(from_index), top_y),
+                    to: (x(to_index), bottom_y),
+                });
+            } else if branches.iter().all(|&(_, i)| i != to_index) {
+                self.segments.push(Segment {
+                    track_id,
+                    from: (x(to_index), mid_y),
+                    to: (x(to_index), bottom_y),
+                });
+            }
+        }
+
+        for &(from_index, to_index) in branches {
+            // A split moves the new track, while a join moves the joined track
+            let track_id = if self.tracks.contains(&next[to_index]) {
+                self.tracks[from_index]
+            } else {
+                next[to_index]
+            };
+
+            self.segments.push(Segment {
+                track_id,
+                from: (x(from_index), top_y),
+                to: (x(to_index), bottom_y),
+            });
+        }
+
+        for &index in ends {
+            self.segments.push(Segment {
+                track_id: self.tracks[index],
+                from: (x(index), top_y),
+                to: (x(index), mid_y),
+            });
+        }
+
+        self.columns = self.columns.max(self.tracks.len()).max(next.len());
+
+        self.tracks = next;
+        self.rows += 1;
+    }
+
+    /// Splits `new_track_id` from `from_track_id`, placing
+    /// it immediately to the right of `neighbor_track_id`.
+    fn split_track(
+        &mut self,
+        from_track_id: TrackId,
+        new_track_id: TrackId,
+        neighbor_track_id: TrackId,
+    ) {
+        if self.tracks.contains(&new_track_id) {
+            return;
+        }
+
+        let from_index = self.tracks.iter().position(|&id| id == from_track_id);
+        let neighbor_index = self.tracks.iter().position(|&id| id == neighbor_track_id);
+
+        let mut tracks = self.tracks.clone();
+
+        if let Some(neighbor_index) = neighbor_index {
+            tracks.insert(neighbor_index + 1, new_track_id);
+
+            if let Some(from_index) = from_index {
+                self.row(tracks, &[(from_index, neighbor_index + 1)], &[]);
+            } else {
+                self.row(tracks, &[], &[]);
+            }
+        } else {
+            tracks.push(new_track_id);
+
+            self.row(tracks, &[], &[]);
+        }
+    }
+
+    fn split_tracks(&mut self, from_track_id: TrackId, new_track_ids: &[TrackId]) {
+        let mut tracks = self.tracks.clone();
+
+        match self.tracks.iter().position(|&id| id == from_track_id) {
+            Some(from_index) => {
+                let mut branches = Vec::new();
+
+                for &new_track_id in new_track_ids {
+                    if !tracks.contains(&new_track_id) {
+                        let to_index = from_index + 1 + branches.len();
+                        tracks.insert(to_index, new_track_id);
+                        branches.push((from_index, to_index));
+                    }
+                }
+
+                if !branches.is_empty() {
+                    self.row(tracks, &branches, &[]);
+                }
+            }
+            None => {
+                for &new_track_id in new_track_ids {
+                    if !tracks.contains(&new_track_id) {
+                        tracks.push(new_track_id);
+                    }
+                }
+
+                if tracks.len() != self.tracks.len() {
+                    self.row(tracks, &[], &[]);
+                }
+            }
+        }
+    }
+
+    #[inline]
+    fn stop_track(&mut self, track_id: TrackId) {
+        self.stop_tracks(|id| id == track_id);
+    }
+
+    fn stop_tracks<F: Fn(TrackId) -> bool>(&mut self, stop: F) {
+        let ends = (0..self.tracks.len())
+            .filter(|&i| stop(self.tracks[i]))
+            .collect::<Vec<_>>();
+
+        if !ends.is_empty() {
+            let mut tracks = self.tracks.clone();
+            tracks.retain(|&id| !stop(id));
+
+            self.row(tracks, &[], &ends);
+        }
+    }
+
+    fn finish(self) -> Layout<'a> {
+        Layout {
+            stations: self.stations,
+            segments: self.segments,
+            links: self.links,
+            texts: self.texts,
+            width: self.columns * 2,
+            height: self.rows * 2,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{layout, Segment, StationPos, TextPos};
+    use crate::Event::{self, *};
+    use crate::TrackId;
+
+    #[test]
+    fn empty() {
+        let layout = layout(&[]);
+
+        assert!(layout.stations.is_empty());
+        assert!(layout.segments.is_empty());
+        assert_eq!((layout.width, layout.height), (0, 0));
+    }
+
+    #[test]
+    fn station() {
+        let events = [StartTrack(TrackId(1)), Event::station(1, "Station\n1")];
+        let layout = layout(&events);
+
+        assert_eq!(
+            layout.stations,
+            [StationPos {
+                track_id: TrackId(1),
+                x: 3,
+                y: 3
+            }]
+        );
+        assert_eq!(
+            layout.texts,
+            [
+                TextPos {
+                    x: 4,
+                    y: 3,
+                    text: "Station"
+                },
+                TextPos {
+                    x: 4,
+                    y: 5,
+                    text: "1"
+                },
+            ]
+        );
+        assert_eq!((layout.width, layout.height), (4, 6));
+    }
+
+    #[test]
+    fn join_track() {
+        let events = [
+            StartTracks(&[TrackId(1), TrackId(2)]),
+            JoinTrack(TrackId(1), TrackId(0)),
+            StopTrack(TrackId(2)),
+        ];
+        let layout = layout(&events);
+
+        let segment = |track_id, from, to| Segment { track_id, from, to };
+
+        assert_eq!(
+            layout.segments[3..],
+            [
+                segment(TrackId(0), (1, 2), (1, 4)),
+                segment(TrackId(2), (5, 2), (3, 4)),
+                segment(TrackId(1), (3, 2), (1, 4)),
+                segment(TrackId(0), (1, 4), (1, 6)),
+                segment(TrackId(2), (3, 4), (3, 5)),
+            ]
+        );
+    }
+
+    #[test]
+    fn link() {
+        let events = [StartTrack(TrackId(1)), Link(TrackId(1), TrackId(0))];
+        let layout = layout(&events);
+
+        assert_eq!(
+            layout.links,
+            [Segment {
+                track_id: TrackId(1),
+                from: (3, 3),
+                to: (1, 3)
+            }]
+        );
+    }
+}
@@ -94,32 +94,32 @@
 //! *The following example outputs the graph above.*
 //!
 //! ```no_run
-//! use metro::Event;
+//! use metro::{Event, TrackId};
 //!
 //! let events = [
 //!     Event::station(0, "Station 1"),
 //!     Event::station(0, "Station 2"),
 //!     Event::station(0, "Station 3"),
-//!     Event::SplitTrack(0, 1),
+//!     Event::SplitTrack(TrackId(0), TrackId(1)),
 //!     Event::station(1, "Station 4"),
-//!     Event::SplitTrack(1, 2),
+//!     Event::SplitTrack(TrackId(1), TrackId(2)),
 //!     Event::station(1, "Station 5"),
 //!     Event::station(2, "Station 6"),
 //!     Event::station(0, "Station 7"),
 //!     Event::station(1, "Station 8"),
 //!     Event::station(2, "Station 9"),
-//!     Event::SplitTrack(2, 3),
-//!     Event::SplitTrack(3, 4),
+//!     Event::SplitTrack(TrackId(2), TrackId(3)),
+//!     Event::SplitTrack(TrackId(3), TrackId(4)),
 //!     Event::station(5, "Station 10 (Detached)"),
-//!     Event::JoinTrack(4, 0),
+//!     Event::JoinTrack(TrackId(4), TrackId(0)),
 //!     Event::station(3, "Station 11"),
-//!     Event::StopTrack(1),
+//!     Event::StopTrack(TrackId(1)),
 //!     Event::station(0, "Station 12"),
 //!     Event::station(2, "Station 13"),
 //!     Event::station(3, "Station 14"),
-//!     Event::JoinTrack(3, 0),
+//!     Event::JoinTrack(TrackId(3), TrackId(0)),
 //!     Event::station(2, "Station 15"),
-//!     Event::StopTrack(2),
+//!     Event::StopTrack(TrackId(2)),
 //!     Event::station(0, "Station 16"),
 //! ];
 //!
@@ -135,7 +135,42 @@
 #![warn(clippy::all)]
 
 mod events;
+mod grid;
+mod horizontal;
+mod html;
+mod layout;
+mod mermaid;
 mod metro;
+mod options;
+mod plantuml;
+mod reverse;
+mod simplify;
+mod spans;
+mod stats;
+mod svg;
+pub mod sync;
+#[cfg(feature = "testing")]
+pub mod test_util;
+mod track_id;
+mod validate;
+mod width;
 
-pub use crate::metro::{Metro, Track};
+pub use crate::metro::{Checkpoint, Metro, Track, TrackLimitError};
 pub use events::*;
+pub use grid::{
+    columns_per_row, event_row_ranges, to_grid, to_styled_grid, to_styled_grid_highlighted, Cell,
+    CellKind,
+};
+pub use horizontal::to_string_horizontal;
+pub use html::to_html;
+pub use layout::{layout, Layout, Segment, StationPos, TextPos};
+pub use mermaid::to_mermaid;
+pub use options::{Direction, LineEnding, Options, UnknownTrackPolicy};
+pub use plantuml::to_plantuml;
+pub use reverse::reverse;
+pub use simplify::simplify;
+pub use spans::{track_spans, SpanEnd, SpanStart, TrackSpan};
+pub use stats::{stats, Stats};
+pub use svg::{to_svg, SvgOptions};
+pub use track_id::TrackId;
+pub use validate::{open_tracks, validate, Validation, ValidationKind};
@@ -0,0 +1,517 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt::Write;
+
+use crate::events::Event;
+use crate::track_id::TrackId;
+
+/// Render `&[`[`Event`]`]` as a [Mermaid `gitGraph`][gitGraph].
+/// Defines a default track with `track_id` of `0`.
+///
+/// - `StartTrack`, `StartTracks` and `StartTrackAt` turn into `branch`.
+/// - `SplitTrack` and `SplitTrackAt` turn into `branch`.
+/// - `SplitStation` turns into `branch` followed by `commit`.
+/// - `JoinTrack`, `JoinTrackToward` and `JoinTrackKeepSource` turn into `merge`.
+/// - `JoinTracks` turns into a `merge` for each joined track.
+/// - `JoinTrackOrRename` turns into `merge`, or if the track it is joined
+///   into does not exist, into a `branch` of that track, as with `SplitTrack`.
+/// - `Station` turns into `commit`, using the text as the commit id.
+/// - `StationMarked` turns into `commit` with `type: HIGHLIGHT`.
+/// - `StationMulti` turns into `commit` on the first of its tracks that exists,
+///   as a commit can only belong to a single branch.
+///
+/// The default track `0` is named `main`, while any other track is
+/// named `track-{track_id}`. If a track id is reused after its track
+/// was stopped, then the new track is named `track-{track_id}-{n}`,
+/// as Mermaid does not allow recreating a branch.
+///
+/// Commit ids must be unique in Mermaid, so if station text is repeated,
+/// then ` ({n})` is appended to the commit id, increasing `n` until the
+/// commit id is not used by another commit. Multiple lines of station
+/// text are joined with a space.
+///
+/// Stations which are not tied to any track, as well as `Annotation`s,
+/// are skipped, as every commit must belong to a branch.
+///
+/// `RenameTrack` keeps the branch name of the track.
+///
+/// `LabelTrack` and `Legend` are ignored, as branches cannot be
+/// renamed after they are created. `Link` is also ignored.
+///
+/// [`Event`]: enum.Event.html
+///
+/// [gitGraph]: https://mermaid.js.org/syntax/gitgraph.html
+///
+/// # Example
+///
+/// ```
+/// use metro::{Event, TrackId};
+///
+/// let events = [
+///     Event::station(0, "Station 1"),
+///     Event::SplitTrack(TrackId(0), TrackId(1)),
+///     Event::station(1, "Station 2"),
+///     Event::station(0, "Station 3"),
+///     Event::JoinTrack(TrackId(1), TrackId(0)),
+/// ];
+///
+/// let mermaid = metro::to_mermaid(&events);
+///
+/// assert_eq!(
+///     mermaid,
+///     r#"gitGraph
+///     commit id: "Station 1"
+///     branch track-1
+///     commit id: "Station 2"
+///     checkout main
+///     commit id: "Station 3"
+///     merge track-1
+/// "#
+/// );
+/// ```
+pub fn to_mermaid(events: &[Event]) -> String {
+    let mut mermaid = MermaidBuilder::new();
+
+    for event in events {
+        use Event::*;
+        match event {
+            &StartTrack(track_id) | &StartTrackAt(track_id, _) => mermaid.start_track(track_id),
+
+            &StartTracks(track_ids) => {
+                for &track_id in track_ids {
+                    mermaid.start_track(track_id);
+                }
+            }
+
+            &StopTrack(track_id) | &StopTrackLabeled(track_id, _) => mermaid.stop_track(track_id),
+
+            &StopTracks(track_ids) => {
+                for &track_id in track_ids {
+                    mermaid.stop_track(track_id);
+                }
+            }
+
+            Station(track_id, text) => {
+                if mermaid.branches.contains_key(track_id) {
+                    mermaid.checkout(*track_id);
+                    mermaid.commit(text, false);
+                }
+            }
+
+            StationMarked(track_id, text, _) => {
+                if mermaid.branches.contains_key(track_id) {
+                    mermaid.checkout(*track_id);
+                    mermaid.commit(text, true);
+                }
+            }
+
+            StationMulti(track_ids, text) => {
+                let track_id = track_ids
+                    .iter()
+                    .find(|track_id| mermaid.branches.contains_key(track_id));
+
+                if let Some(&track_id) = track_id {
+                    mermaid.checkout(track_id);
+                    mermaid.commit(text, false);
+                }
+            }
+
+            &SplitTrack(from_track_id, new_track_id)
+            | &SplitTrackAt(from_track_id, new_track_id, _) => {
+                if !mermaid.branches.contains_key(&new_track_id) {
+                    if mermaid.branches.contains_key(&from_track_id) {
+                        mermaid.checkout(from_track_id);
+                    }
+
+                    mermaid.branch(new_track_id);
+                }
+            }
+
+            SplitStation(from_track_id, new_track_id, text) => {
+                if !mermaid.branches.contains_key(new_track_id) {
+                    if mermaid.branches.contains_key(from_track_id) {
+                        mermaid.checkout(*from_track_id);
+                    }
+
+                    mermaid.branch(*new_track_id);
+                }
+
+                mermaid.checkout(*new_track_id);
+                mermaid.commit(text, false);
+            }
+
+            SplitTrackN(from_track_id, new_track_ids) => {
+                for &new_track_id in new_track_ids.iter() {
+                    if !mermaid.branches.contains_key(&new_track_id) {
+                        if mermaid.branches.contains_key(from_track_id) {
+                            mermaid.checkout(*from_track_id);
+                        }
+
+                        mermaid.branch(new_track_id);
+                    }
+                }
+            }
+
+            &JoinTrack(from_track_id, to_track_id)
+            | &JoinTrackToward(from_track_id, to_track_id)
+            | &JoinTrackKeepSource(to_track_id, from_track_id) => {
+                if from_track_id == to_track_id || !mermaid.branches.contains_key(&to_track_id) {
+                    mermaid.stop_track(from_track_id);
+                } else if mermaid.branches.contains_key(&from_track_id) {
+                    mermaid.checkout(to_track_id);
+                    mermaid.merge(from_track_id);
+                }
+            }
+
+            &JoinTrackOrRename(from_track_id, to_track_id) => {
+                if from_track_id == to_track_id {
+                    mermaid.stop_track(from_track_id);
+                } else if !mermaid.branches.contains_key(&from_track_id) {
+                    // Does nothing, regardless of `to_track_id`
+                } else if mermaid.branches.contains_key(&to_track_id) {
+                    mermaid.checkout(to_track_id);
+                    mermaid.merge(from_track_id);
+                } else {
+                    mermaid.checkout(from_track_id);
+                    mermaid.branch(to_track_id);
+                    mermaid.stop_track(from_track_id);
+                }
+            }
+
+            &JoinTracks(from_track_ids, to_track_id) => {
+                if from_track_ids.contains(&to_track_id)
+                    || !mermaid.branches.contains_key(&to_track_id)
+                {
+                    for &from_track_id in from_track_ids {
+                        if from_track_id != to_track_id {
+                            mermaid.stop_track(from_track_id);
+                        }
+                    }
+                    continue;
+                }
+
+                for &from_track_id in from_track_ids {
+                    if mermaid.branches.contains_key(&from_track_id) {
+                        mermaid.checkout(to_track_id);
+                        mermaid.merge(from_track_id);
+                    }
+                }
+            }
+
+            &RenameTrack(old_track_id, new_track_id) => {
+                if !mermaid.branches.contains_key(&new_track_id) {
+                    mermaid.rename_track(old_track_id, new_track_id);
+                }
+            }
+
+            DetachedStation(_) | Annotation(_) | Link(..) | LabelTrack(..) | Legend | NoEvent => {}
+        }
+    }
+
+    mermaid.output
+}
+
+struct MermaidBuilder {
+    output: String,
+    /// Branch names of the current tracks.
+    branches: HashMap<TrackId, String>,
+    /// The number of branches created per track id.
+    created: HashMap<TrackId, usize>,
+    /// The last number appended to each duplicate commit id.
+    commits: HashMap<String, usize>,
+    /// The commit ids in use, including any appended number.
+    commit_ids: HashSet<String>,
+    /// The currently checked out track id.
+    current: Option<TrackId>,
+}
+
+impl MermaidBuilder {
+    fn new() -> Self {
+        let mut mermaid = Self {
+            output: String::from("gitGraph\n"),
+            branches: HashMap::new(),
+            created: HashMap::new(),
+            commits: HashMap::new(),
+            commit_ids: HashSet::new(),
+            current: Some(TrackId(0)),
+        };
+
+        mermaid.branches.insert(TrackId(0), String::from("main"));
+        mermaid.created.insert(TrackId(0), 1);
+
+        mermaid
+    }
+
+    fn start_track(&mut self, track_id: TrackId) {
+        if !self.branches.contains_key(&track_id) {
+            self.branch(track_id);
+        }
+    }
+
+    fn stop_track(&mut self, track_id: TrackId) {
+        self.branches.remove(&track_id);
+
+        if self.current == Some(track_id) {
+            self.current = None;
+        }
+    }
+
+    /// Moves the branch of `old_track_id` to `new_track_id`,
+    /// keeping the branch name.
+    fn rename_track(&mut self, old_track_id: TrackId, new_track_id: TrackId) {
+        if let Some(name) = self.branches.remove(&old_track_id) {
+            self.branches.insert(new_track_id, name);
+
+            if self.current == Some(old_track_id) {
+                self.current = Some(new_track_id);
+            }
+        }
+    }
+
+    fn branch(&mut self, track_id: TrackId) {
+        let created = self.created.entry(track_id).or_insert(0);
+        *created += 1;
+
+        let name = if *created == 1 {
+            format!("track-{}", track_id)
+        } else {
+            format!("track-{}-{}", track_id, created)
+        };
+
+        // Writing to a `String` never fails, so it is safe to `unwrap`
+        writeln!(self.output, "    branch {}", name).unwrap();
+
+        self.branches.insert(track_id, name);
+        self.current = Some(track_id);
+    }
+
+    fn checkout(&mut self, track_id: TrackId) {
+        if self.current != Some(track_id) {
+            writeln!(self.output, "    checkout {}", self.branches[&track_id]).unwrap();
+
+            self.current = Some(track_id);
+        }
+    }
+
+    fn merge(&mut self, track_id: TrackId) {
+        writeln!(self.output, "    merge {}", self.branches[&track_id]).unwrap();
+
+        self.branches.remove(&track_id);
+    }
+
+    fn commit(&mut self, text: &str, highlight: bool) {
+        let mut id = String::new();
+
+        for (i, line) in text.lines().enumerate() {
+            if i > 0 {
+                id.push(' ');
+            }
+
+            id.push_str(&line.replace('"', "#quot;"));
+        }
+
+        // The appended number could result in the id of another
+        // commit, e.g. `"A (2)"`, so numbers are tried until unused
+        let count = self.commits.entry(id.clone()).or_insert(1);
+        let base_len = id.len();
+
+        while self.commit_ids.contains(&id) {
+            *count += 1;
+
+            id.truncate(base_len);
+            write!(id, " ({})", count).unwrap();
+        }
+
+        self.commit_ids.insert(id.clone());
+
+        if highlight {
+            writeln!(self.output, "    commit id: \"{}\" type: HIGHLIGHT", id).unwrap();
+        } else {
+            writeln!(self.output, "    commit id: \"{}\"", id).unwrap();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::to_mermaid;
+    use crate::Event::{self, *};
+    use crate::TrackId;
+
+    #[test]
+    fn empty() {
+        assert_eq!(to_mermaid(&[]), "gitGraph\n");
+    }
+
+    #[test]
+    fn lib_example() {
+        let events = [
+            Event::station(0, "Station 1"),
+            Event::station(0, "Station 2"),
+            Event::station(0, "Station 3"),
+            SplitTrack(TrackId(0), TrackId(1)),
+            Event::station(1, "Station 4"),
+            SplitTrack(TrackId(1), TrackId(2)),
+            Event::station(1, "Station 5"),
+            Event::station(2, "Station 6"),
+            Event::station(0, "Station 7"),
+            Event::station(1, "Station 8"),
+            Event::station(2, "Station 9"),
+            SplitTrack(TrackId(2), TrackId(3)),
+            SplitTrack(TrackId(3), TrackId(4)),
+            Event::station(5, "Station 10 (Detached)"),
+            JoinTrack(TrackId(4), TrackId(0)),
+            Event::station(3, "Station 11"),
+            StopTrack(TrackId(1)),
+            Event::station(0, "Station 12"),
+            Event::station(2, "Station 13"),
+            Event::station(3, "Station 14"),
+            JoinTrack(TrackId(3), TrackId(0)),
+            Event::station(2, "Station 15"),
+            StopTrack(TrackId(2)),
+            Event::station(0, "Station 16"),
+        ];
+        let mermaid = to_mermaid(&events);
+
+        assert_eq!(
+            mermaid,
+            r#"gitGraph
+    commit id: "Station 1"
+    commit id: "Station 2"
+    commit id: "Station 3"
+    branch track-1
+    commit id: "Station 4"
+    branch track-2
+    checkout track-1
+    commit id: "Station 5"
+    checkout track-2
+    commit id: "Station 6"
+    checkout main
+    commit id: "Station 7"
+    checkout track-1
+    commit id: "Station 8"
+    checkout track-2
+    commit id: "Station 9"
+    branch track-3
+    branch track-4
+    checkout main
+    merge track-4
+    checkout track-3
+    commit id: "Station 11"
+    checkout main
+    commit id: "Station 12"
+    checkout track-2
+    commit id: "Station 13"
+    checkout track-3
+    commit id: "Station 14"
+    checkout main
+    merge track-3
+    checkout track-2
+    commit id: "Station 15"
+    checkout main
+    commit id: "Station 16"
+"#
+        );
+    }
+
+    #[test]
+    fn reused_track_id() {
+        let events = [
+            SplitTrack(TrackId(0), TrackId(1)),
+            StopTrack(TrackId(1)),
+            SplitTrack(TrackId(0), TrackId(1)),
+            Event::station(1, "Station 1"),
+        ];
+        let mermaid = to_mermaid(&events);
+
+        assert_eq!(
+            mermaid,
+            r#"gitGraph
+    branch track-1
+    checkout main
+    branch track-1-2
+    commit id: "Station 1"
+"#
+        );
+    }
+
+    #[test]
+    fn join_tracks() {
+        let events = [
+            SplitTrack(TrackId(0), TrackId(1)),
+            SplitTrack(TrackId(0), TrackId(2)),
+            Event::station(0, "Station 1"),
+            JoinTracks(&[TrackId(1), TrackId(2), TrackId(3)], TrackId(0)),
+        ];
+        let mermaid = to_mermaid(&events);
+
+        assert_eq!(
+            mermaid,
+            r#"gitGraph
+    branch track-1
+    checkout main
+    branch track-2
+    checkout main
+    commit id: "Station 1"
+    merge track-1
+    merge track-2
+"#
+        );
+    }
+
+    #[test]
+    fn station_marked() {
+        let events = [
+            Event::station(0, "Station 1"),
+            Event::station_marked(0, "Station 2", '@'),
+        ];
+        let mermaid = to_mermaid(&events);
+
+        assert_eq!(
+            mermaid,
+            r#"gitGraph
+    commit id: "Station 1"
+    commit id: "Station 2" type: HIGHLIGHT
+"#
+        );
+    }
+
+    #[test]
+    fn duplicate_commit_ids() {
+        let events = [
+            Event::station(0, "Station"),
+            Event::station(0, "Station"),
+            Event::station(0, "\"Multiple\"\nlines"),
+        ];
+        let mermaid = to_mermaid(&events);
+
+        assert_eq!(
+            mermaid,
+            r##"gitGraph
+    commit id: "Station"
+    commit id: "Station (2)"
+    commit id: "#quot;Multiple#quot; lines"
+"##
+        );
+    }
+
+    #[test]
+    fn duplicate_commit_ids_numbered_text() {
+        let events = [
+            Event::station(0, "Station (2)"),
+            Event::station(0, "Station"),
+            Event::station(0, "Station"),
+            Event::station(0, "Station (2)"),
+        ];
+        let mermaid = to_mermaid(&events);
+
+        assert_eq!(
+            mermaid,
+            r#"gitGraph
+    commit id: "Station (2)"
+    commit id: "Station"
+    commit id: "Station (3)"
+    commit id: "Station (2) (2)"
+"#
+        );
+    }
+}
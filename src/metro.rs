@@ -1,11 +1,21 @@
 use std::borrow::Cow;
 use std::cell::RefCell;
+use std::collections::HashMap;
+use std::error;
 use std::fmt;
-use std::io::{self, Write};
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
 use std::mem;
+use std::path::Path;
 use std::rc::Rc;
+use std::vec;
 
-use crate::events::{to_string, to_vec, to_writer, Event};
+use crate::events::{
+    to_fmt_writer, to_string, to_string_with, to_vec, to_vec_with, to_writer, to_writer_with, Event,
+};
+use crate::options::Options;
+use crate::spans::{track_spans, TrackSpan};
+use crate::track_id::TrackId;
 
 type RcMetro<'a> = Rc<RefCell<MetroState<'a>>>;
 
@@ -118,11 +128,13 @@ type RcMetro<'a> = Rc<RefCell<MetroState<'a>>>;
 /// [`BorrowError`]: https://doc.rust-lang.org/stable/std/cell/struct.BorrowError.html
 /// [`BorrowMutError`]: https://doc.rust-lang.org/stable/std/cell/struct.BorrowMutError.html
 ///
-/// Additionally, if you have a need for [`Arc`] then feel free to submit
-/// an issue on the [issue tracker] with your use case.
-///
 /// [issue tracker]: https://github.com/vallentin/metro/issues
+///
+/// Additionally, if you have a need for [`Arc`], e.g. to construct
+/// the graph from multiple threads, then use [`SyncMetro`] instead.
+///
 /// [`Arc`]: https://doc.rust-lang.org/std/sync/struct.Arc.html
+/// [`SyncMetro`]: sync/struct.SyncMetro.html
 #[allow(missing_debug_implementations)]
 pub struct Metro<'a> {
     state: RcMetro<'a>,
@@ -138,6 +150,86 @@ impl<'a> Metro<'a> {
         }
     }
 
+    /// Create a `Metro` from existing `events`, e.g. from a previous
+    /// [`into_events`] call, such that it can be continued using [`Track`]s.
+    ///
+    /// The current tracks are reconstructed by replaying the `events`,
+    /// so they can be retrieved using [`get_track`]. New track ids
+    /// continue after the highest track id started in `events`.
+    ///
+    /// Note that when rendering, track `0` exists implicitly. Use
+    /// [`get_or_create_track`] to continue events relying on that.
+    ///
+    /// [`Track`]: struct.Track.html
+    /// [`into_events`]: struct.Metro.html#method.into_events
+    /// [`get_track`]: struct.Metro.html#method.get_track
+    /// [`get_or_create_track`]: struct.Metro.html#method.get_or_create_track
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use metro::Metro;
+    ///
+    /// let mut metro = Metro::new();
+    ///
+    /// let mut track1 = metro.new_track();
+    /// track1.add_station("Station 1");
+    ///
+    /// let events = metro.into_events();
+    ///
+    /// let mut metro = Metro::from_events(events);
+    ///
+    /// let mut track1 = metro.get_track(0).unwrap();
+    /// let mut track2 = track1.split();
+    /// track2.add_station("Station 2");
+    /// track1.add_station("Station 3");
+    ///
+    /// assert_eq!(track2.id(), 1);
+    ///
+    /// assert_eq!(
+    ///     metro.to_string().unwrap(),
+    ///     "* Station 1\n|\\\n| * Station 2\n* | Station 3\n"
+    /// );
+    /// ```
+    #[inline]
+    pub fn from_events(events: Vec<Event<'a>>) -> Self {
+        Self {
+            state: Rc::new(RefCell::new(MetroState::from_events(events))),
+        }
+    }
+
+    /// Appends the `events`, updating the tracks as each event is added,
+    /// such that existing [`Track`]s remain consistent with the events.
+    ///
+    /// *See also the [`Extend`] implementation.*
+    ///
+    /// [`Track`]: struct.Track.html
+    /// [`Extend`]: struct.Metro.html#impl-Extend%3CEvent%3C%27a%3E%3E
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use metro::{Event, Metro, TrackId};
+    ///
+    /// let mut metro = Metro::new();
+    ///
+    /// let mut track1 = metro.new_track();
+    /// track1.add_station("Station 1");
+    ///
+    /// metro.extend_events((1..=2).map(|i| Event::SplitTrack(TrackId(0), TrackId(i))));
+    ///
+    /// let mut track2 = metro.get_track(2).unwrap();
+    /// track2.add_station("Station 2");
+    ///
+    /// assert_eq!(metro.track_ids(), [0, 2, 1]);
+    /// ```
+    pub fn extend_events<I>(&mut self, events: I)
+    where
+        I: IntoIterator<Item = Event<'a>>,
+    {
+        self.state.borrow_mut().extend_events(events);
+    }
+
     // [`Metro`]: struct.Metro.html
     // [`new_track`]: struct.Metro.html#method.new_track
 
@@ -153,8 +245,10 @@ impl<'a> Metro<'a> {
     /// # Panics
     ///
     /// Panics if more than [`usize`] tracks have been created.
+    /// *[See `try_new_track` for returning an error instead.][`try_new_track`]*
     ///
     /// [`usize`]: https://doc.rust-lang.org/stable/std/primitive.usize.html
+    /// [`try_new_track`]: struct.Metro.html#method.try_new_track
     ///
     /// ## Output Example
     ///
@@ -166,8 +260,30 @@ impl<'a> Metro<'a> {
     /// ```
     #[inline]
     pub fn new_track(&mut self) -> Track<'a> {
-        let id = self.state.borrow_mut().next_id();
-        self.new_track_with_id(id)
+        self.try_new_track().unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    /// Create a new [`Track`] like [`new_track`], except an error is
+    /// returned instead of panicking, if more than [`usize`] tracks
+    /// have been created.
+    ///
+    /// [`Track`]: struct.Track.html
+    /// [`new_track`]: struct.Metro.html#method.new_track
+    /// [`usize`]: https://doc.rust-lang.org/stable/std/primitive.usize.html
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use metro::Metro;
+    /// let mut metro = Metro::new();
+    ///
+    /// let track = metro.try_new_track().unwrap();
+    /// assert_eq!(track.id(), 0);
+    /// ```
+    #[inline]
+    pub fn try_new_track(&mut self) -> Result<Track<'a>, TrackLimitError> {
+        let id = self.state.borrow_mut().try_next_id()?;
+        Ok(self.new_track_with_id(id))
     }
 
     /// Create a new [`Track`] with a specific track [`id`].
@@ -198,624 +314,2583 @@ impl<'a> Metro<'a> {
     /// | | | |
     /// ```
     #[inline]
-    pub fn new_track_with_id(&mut self, track_id: usize) -> Track<'a> {
-        MetroState::new_track(&self.state, track_id)
-    }
-
-    /// If the `track_id` exists then `Some` is returned, otherwise `None`.
-    #[inline]
-    pub fn get_track(&mut self, track_id: usize) -> Option<Track<'a>> {
-        MetroState::get_track(&self.state, track_id)
+    pub fn new_track_with_id<T: Into<TrackId>>(&mut self, track_id: T) -> Track<'a> {
+        let track_id = track_id.into();
+        self.state.borrow_mut().start_track(track_id);
+        Track::new(Rc::clone(&self.state), track_id)
     }
 
-    /// Creates a station that is not tied to any [`Track`].
-    ///
-    /// See [`Track::add_station`] to create a station that is
-    /// tied to a [`Track`].
+    /// Returns the [`Track`] with `track_id`, creating it if it does not exist.
     ///
-    /// Note that all stations require a `track_id`, so it uses
-    /// [`std::usize::MAX`] as `track_id`.
+    /// The returned `bool` is `true` if a new [`Track`] was created, i.e. if
+    /// an [`Event::StartTrack`] was added. If the track [`id`] is already in
+    /// use, then `false` is returned along with the existing [`Track`].
     ///
-    /// [`Track::add_station`]: struct.Track.html#method.add_station
+    /// *[See also `new_track_with_id`.][`new_track_with_id`]*
     ///
-    /// [`std::usize::MAX`]: https://doc.rust-lang.org/stable/std/usize/constant.MAX.html
+    /// [`new_track_with_id`]: struct.Metro.html#method.new_track_with_id
     ///
-    /// ## Output Example
+    /// [`Track`]: struct.Track.html
+    /// [`id`]: struct.Track.html#method.id
+    /// [`Event::StartTrack`]: enum.Event.html#variant.StartTrack
     ///
-    /// Given 3 existing tracks then `add_station("Hello World")` would render as:
+    /// # Example
     ///
-    /// ```text
-    /// | | |
-    /// | | | Hello World
-    /// | | |
     /// ```
+    /// # use metro::Metro;
+    /// let mut metro = Metro::new();
     ///
-    /// Text with multiple lines is also allowed.
-    /// Given 3 existing tracks then `add_station("Hello\nWorld")` would render as:
+    /// let (track1, created) = metro.get_or_create_track(1);
+    /// assert!(created);
     ///
-    /// ```text
-    /// | | |
-    /// | | | Hello
-    /// | | | World
-    /// | | |
+    /// let (track2, created) = metro.get_or_create_track(1);
+    /// assert!(!created);
+    ///
+    /// assert_eq!(track1.id(), track2.id());
     /// ```
     #[inline]
-    pub fn add_station<S: Into<Cow<'a, str>>>(&mut self, text: S) {
-        MetroState::add_event(&self.state, Event::station(std::usize::MAX, text));
+    pub fn get_or_create_track<T: Into<TrackId>>(&mut self, track_id: T) -> (Track<'a>, bool) {
+        let track_id = track_id.into();
+        let created = self.state.borrow_mut().start_track(track_id);
+        (Track::new(Rc::clone(&self.state), track_id), created)
     }
 
-    /// *[See `to_writer`.][`to_writer`]*
+    /// Returns the ids of the current tracks, in column order,
+    /// i.e. from left to right.
     ///
-    /// [`to_writer`]: fn.to_writer.html
-    #[inline]
-    pub fn to_writer<W: Write>(&self, writer: W) -> io::Result<()> {
-        let state = self.state.borrow();
-        to_writer(writer, &state.events)
-    }
-
-    /// *[See `to_vec`.][`to_vec`]*
+    /// The tracks are the same as when rendering with the default
+    /// [`Options::initial_tracks`], i.e. track `0` exists implicitly,
+    /// until it is stopped.
     ///
-    /// [`to_vec`]: fn.to_vec.html
-    #[inline]
-    pub fn to_vec(&self) -> io::Result<Vec<u8>> {
-        let state = self.state.borrow();
-        to_vec(&state.events)
-    }
-
-    /// *[See `to_string`.][`to_string`]*
+    /// [`Options::initial_tracks`]: struct.Options.html#structfield.initial_tracks
     ///
-    /// [`to_string`]: fn.to_string.html
-    #[inline]
-    pub fn to_string(&self) -> io::Result<String> {
-        let state = self.state.borrow();
-        to_string(&state.events)
-    }
-
-    /// Returns [`Vec`]`<`[`Event`]`>` of the events currently
-    /// in this `Metro`.
+    /// # Example
     ///
-    /// *[See also `into_events`.][`into_events`]*
+    /// ```
+    /// # use metro::Metro;
+    /// let mut metro = Metro::new();
     ///
-    /// *[See also `Metro::to_string`.][`to_string`]*
+    /// let track1 = metro.new_track();
+    /// let track2 = metro.new_track();
+    /// let track3 = track1.split();
     ///
-    /// [`into_events`]: struct.Metro.html#method.into_events
-    /// [`to_string`]: struct.Metro.html#method.to_string
-    /// [`Event`]: enum.Event.html
+    /// assert_eq!(metro.track_ids(), [0, 2, 1]);
     ///
-    /// [`Vec`]: https://doc.rust-lang.org/stable/std/vec/struct.Vec.html
+    /// track2.stop();
+    ///
+    /// assert_eq!(metro.track_ids(), [0, 2]);
+    /// ```
     #[inline]
-    pub fn to_events(&self) -> Vec<Event<'a>> {
-        let state = self.state.borrow();
-        state.events.clone()
+    pub fn track_ids(&self) -> Vec<TrackId> {
+        self.state.borrow().track_ids()
     }
 
-    /// Consumes `Metro` and returns its [`Vec`]`<`[`Event`]`>`
-    /// of the events.
+    /// Returns the number of events added so far, without
+    /// cloning them like [`to_events`].
     ///
-    /// *[See also `to_events`.][`to_events`]*
+    /// [`to_events`]: struct.Metro.html#method.to_events
     ///
-    /// *[See also `Metro::to_string`.][`to_string`]*
+    /// # Example
     ///
-    /// [`to_events`]: struct.Metro.html#method.to_events
-    /// [`to_string`]: struct.Metro.html#method.to_string
+    /// ```
+    /// # use metro::Metro;
+    /// let mut metro = Metro::new();
+    ///
+    /// let mut track1 = metro.new_track();
+    /// track1.add_station("Station 1");
+    ///
+    /// assert_eq!(metro.event_count(), 2);
+    /// ```
     #[inline]
-    pub fn into_events(self) -> Vec<Event<'a>> {
-        let mut state = self.state.borrow_mut();
-        mem::replace(&mut state.events, Vec::new())
+    pub fn event_count(&self) -> usize {
+        self.state.borrow().event_count()
     }
-}
-
-/// The `Track` struct represents a track in the [`Metro`].
-/// The `Track` struct is created with the [`new_track`] or
-/// [`new_track_with_id`] on [`Metro`].
-///
-/// *[See `Metro` for a complete example.][`Metro`]*
-///
-/// [`Metro`]: struct.Metro.html
-/// [`new_track`]: struct.Metro.html#method.new_track
-/// [`new_track_with_id`]: struct.Metro.html#method.new_track_with_id
-pub struct Track<'a> {
-    state: RcMetro<'a>,
-    id: usize,
-}
 
-impl<'a> Track<'a> {
+    /// Returns the number of current tracks, i.e. the length
+    /// of [`track_ids`] without cloning it. Thereby it includes
+    /// track `0`, which exists implicitly until it is stopped.
+    ///
+    /// [`track_ids`]: struct.Metro.html#method.track_ids
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use metro::Metro;
+    /// let mut metro = Metro::new();
+    ///
+    /// let track1 = metro.new_track();
+    /// let track2 = track1.split();
+    ///
+    /// assert_eq!(metro.active_track_count(), 2);
+    ///
+    /// track2.stop();
+    ///
+    /// assert_eq!(metro.active_track_count(), 1);
+    /// ```
     #[inline]
-    fn new(state: RcMetro<'a>, id: usize) -> Self {
-        Self { state, id }
+    pub fn active_track_count(&self) -> usize {
+        self.state.borrow().active_track_count()
     }
 
-    /// Returns the track id.
+    /// Returns a [`Checkpoint`] of the current events and tracks,
+    /// which can later be passed to [`rewind`].
+    ///
+    /// [`Checkpoint`]: struct.Checkpoint.html
+    /// [`rewind`]: struct.Metro.html#method.rewind
     #[inline]
-    pub fn id(&self) -> usize {
-        self.id
+    pub fn checkpoint(&self) -> Checkpoint {
+        self.state.borrow().checkpoint()
     }
 
-    /// Stop this `Track`, and removes it from [`Metro`].
-    ///
-    /// The track [`id`] can be reused after with [`new_track_with_id`].
-    ///
-    /// ### Dangling Tracks
-    ///
-    /// If other tracks exist, which has the same track [`id`], e.g. by
-    /// calling `get_track` multiple times with the same track [`id`], then
-    /// the other tracks become "dangling". However, if a new track is created
-    /// with [`new_track_with_id`], then the other references are
-    /// not dangling anymore.
+    /// Rewinds to `checkpoint`, discarding all events added after it,
+    /// and restoring the tracks and track ids as they were.
     ///
-    /// *[See `is_dangling` for more information.][`is_dangling`]*
+    /// [`Track`]s created after `checkpoint` are [dangling] after rewinding.
+    /// Tracks stopped or joined after `checkpoint` exist again, and can be
+    /// retrieved using [`get_track`].
     ///
-    /// [`id`]: struct.Track.html#method.id
-    /// [`is_dangling`]: struct.Track.html#method.is_dangling
+    /// The `checkpoint` must have been created by this `Metro`, and not
+    /// discarded by rewinding to an earlier checkpoint. Otherwise the
+    /// events are left as is, while the tracks are still restored.
     ///
-    /// [`Metro`]: struct.Metro.html
-    /// [`new_track_with_id`]: struct.Metro.html#method.new_track_with_id
+    /// [`Track`]: struct.Track.html
+    /// [dangling]: struct.Track.html#method.is_dangling
     /// [`get_track`]: struct.Metro.html#method.get_track
     ///
-    /// ## Output Example
-    ///
-    /// Given 3 existing tracks then calling `stop()` on the middle track would render as:
+    /// # Example
     ///
-    /// ```text
-    /// | | |
-    /// | " |
-    /// |  /
-    /// | |
     /// ```
-    #[inline]
-    pub fn stop(self) {
-        // Method is empty as the logic is implemented in Drop for Track
-    }
-
-    /// Creates a station that is tied to this `Track`.
+    /// use metro::Metro;
     ///
-    /// See [`Metro::add_station`] to create a station that is
-    /// tied to a `Track`.
+    /// let mut metro = Metro::new();
     ///
-    /// [`Metro::add_station`]: struct.Metro.html#method.add_station
+    /// let mut track1 = metro.new_track();
+    /// track1.add_station("Station 1");
     ///
-    /// ## Output Example
+    /// let checkpoint = metro.checkpoint();
     ///
-    /// Given 3 existing tracks then `add_station("Hello World")` on the middle track would render as:
+    /// let mut track2 = track1.split();
+    /// track2.add_station("Discarded");
     ///
-    /// ```text
-    /// | | |
-    /// | * | Hello World
-    /// | | |
-    /// ```
+    /// metro.rewind(checkpoint);
     ///
-    /// Text with multiple lines is also allowed.
-    /// Given 3 existing tracks then `add_station("Hello\nWorld")` would render as:
+    /// assert!(track2.is_dangling());
     ///
-    /// ```text
-    /// | | |
-    /// | * | Hello
-    /// | | | World
-    /// | | |
+    /// track1.add_station("Station 2");
+    ///
+    /// assert_eq!(metro.to_string().unwrap(), "* Station 1\n* Station 2\n");
     /// ```
     #[inline]
-    pub fn add_station<S: Into<Cow<'a, str>>>(&mut self, text: S) {
-        MetroState::add_event(&self.state, Event::station(self.id, text));
+    pub fn rewind(&mut self, checkpoint: Checkpoint) {
+        self.state.borrow_mut().rewind(checkpoint);
     }
 
-    /// Create a new `Track` that branches of from this track.
+    /// Removes all events and tracks, and resets the track ids, such
+    /// that the `Metro` is the same as [`new`], while keeping the
+    /// allocated capacity, e.g. to reuse it for rendering another graph.
     ///
-    /// To create a new `Track` with a specific track [`id`], then use [`new_track_with_id`].
+    /// All existing [`Track`]s are [dangling] after clearing. As track
+    /// ids start from `0` again, a dangling `Track` is no longer dangling
+    /// if a new track is created with the same id. So existing `Track`s
+    /// should be dropped before creating new tracks.
     ///
-    /// [`id`]: struct.Track.html#method.id
-    /// [`new_track_with_id`]: struct.Track.html#method.split_with_id
+    /// [`new`]: struct.Metro.html#method.new
+    /// [`Track`]: struct.Track.html
+    /// [dangling]: struct.Track.html#method.is_dangling
     ///
-    /// # Panics
+    /// # Example
     ///
-    /// Panics if more than [`usize`] tracks have been created.
+    /// ```
+    /// use metro::Metro;
     ///
-    /// [`usize`]: https://doc.rust-lang.org/stable/std/primitive.usize.html
+    /// let mut metro = Metro::new();
     ///
-    /// ## Output Example
+    /// let mut track1 = metro.new_track();
+    /// track1.add_station("Station 1");
     ///
-    /// Given 3 existing tracks then calling `split` on the middle track would render as:
+    /// metro.clear();
     ///
-    /// ```text
-    /// | | |
-    /// | |\ \
-    /// | | | |
+    /// assert!(track1.is_dangling());
+    /// assert_eq!(metro.event_count(), 0);
+    /// drop(track1);
+    ///
+    /// let mut track1 = metro.new_track();
+    /// assert_eq!(track1.id(), 0);
+    /// track1.add_station("Station 2");
+    ///
+    /// assert_eq!(metro.to_string().unwrap(), "* Station 2\n");
     /// ```
     #[inline]
-    pub fn split(&self) -> Track<'a> {
-        let id = self.state.borrow_mut().next_id();
-        self.split_with_id(id)
+    pub fn clear(&mut self) {
+        self.state.borrow_mut().clear();
     }
 
-    /// Create a new `Track` that branches of from this track.
+    /// If the `track_id` exists then `Some` is returned, otherwise `None`.
+    #[inline]
+    pub fn get_track<T: Into<TrackId>>(&mut self, track_id: T) -> Option<Track<'a>> {
+        let track_id = track_id.into();
+        if self.state.borrow().has_track(track_id) {
+            Some(Track::new(Rc::clone(&self.state), track_id))
+        } else {
+            None
+        }
+    }
+
+    /// Joins the track `from_track_id` into the track `to_track_id`,
+    /// the same as [`Track::join`], but without requiring the [`Track`]
+    /// of either, e.g. when only their ids are at hand.
     ///
-    /// To create a new `Track` without a specific track [`id`], then use [`split`].
+    /// *[See `Event::JoinTrack` for more information.][`Event::JoinTrack`]*
     ///
-    /// [`id`]: struct.Track.html#method.id
-    /// [`split`]: struct.Track.html#method.split
+    /// Any [`Track`] of `from_track_id` becomes [dangling], until a
+    /// new track reuses the id. Thus when such a [`Track`] is dropped
+    /// after the id was reused, then the new track is stopped.
     ///
-    /// # Panics
+    /// [`Track`]: struct.Track.html
+    /// [`Track::join`]: struct.Track.html#method.join
+    /// [`Event::JoinTrack`]: enum.Event.html#variant.JoinTrack
+    /// [dangling]: struct.Track.html#method.is_dangling
     ///
-    /// Panics if more than [`usize`] tracks have been created.
+    /// # Example
     ///
-    /// [`usize`]: https://doc.rust-lang.org/stable/std/primitive.usize.html
+    /// ```
+    /// # use metro::Metro;
+    /// let mut metro = Metro::new();
     ///
-    /// ## Output Example
+    /// let mut track1 = metro.new_track();
+    /// track1.add_station("Station 1");
     ///
-    /// Given 3 existing tracks then calling `split` on the middle track would render as:
+    /// let mut track2 = track1.split();
+    /// track2.add_station("Station 2");
     ///
-    /// ```text
-    /// | | |
-    /// | |\ \
-    /// | | | |
+    /// metro.join_tracks(track2.id(), track1.id());
+    /// assert!(track2.is_dangling());
+    ///
+    /// track1.add_station("Station 3");
+    ///
+    /// let string = metro.to_string().unwrap();
+    ///
+    /// assert_eq!(
+    ///     string,
+    ///     "* Station 1\n|\\\n| * Station 2\n|/\n* Station 3\n"
+    /// );
     /// ```
     #[inline]
-    pub fn split_with_id(&self, new_track_id: usize) -> Track<'a> {
-        MetroState::split_track(&self.state, self, new_track_id)
+    pub fn join_tracks<F, T>(&mut self, from_track_id: F, to_track_id: T)
+    where
+        F: Into<TrackId>,
+        T: Into<TrackId>,
+    {
+        self.state
+            .borrow_mut()
+            .join_track(from_track_id.into(), to_track_id.into());
     }
 
-    /// Merges `self` with `to_track`, removing `self` from
-    /// the [`Metro`].
+    /// Creates a station that is not tied to any [`Track`].
     ///
-    /// [`Metro`]: struct.Metro.html
+    /// See [`Track::add_station`] to create a station that is
+    /// tied to a [`Track`].
+    ///
+    /// The station is added as an [`Event::DetachedStation`], which has
+    /// no `track_id`. Thereby it never collides with a [`Track`] of any
+    /// `track_id`, including [`std::usize::MAX`].
+    ///
+    /// [`Track`]: struct.Track.html
+    /// [`Track::add_station`]: struct.Track.html#method.add_station
+    /// [`Event::DetachedStation`]: enum.Event.html#variant.DetachedStation
+    ///
+    /// [`std::usize::MAX`]: https://doc.rust-lang.org/stable/std/usize/constant.MAX.html
     ///
     /// ## Output Example
     ///
-    /// Given 3 existing tracks, then calling join on the middle
-    /// track with the leftmost track would render as:
+    /// Given 3 existing tracks then `add_station("Hello World")` would render as:
     ///
     /// ```text
     /// | | |
-    /// |/ /
-    /// | |
+    /// | | | Hello World
+    /// | | |
     /// ```
     ///
-    /// Given 6 existing tracks, then calling join on the second
-    /// rightmost track with the leftmost track would render as:
+    /// Text with multiple lines is also allowed.
+    /// Given 3 existing tracks then `add_station("Hello\nWorld")` would render as:
     ///
     /// ```text
-    /// | | | | | |
-    /// | |_|_|/ /
-    /// |/| | | |
-    /// | | | | |
+    /// | | |
+    /// | | | Hello
+    /// | | | World
+    /// | | |
     /// ```
     #[inline]
-    pub fn join(self, to_track: &Track) {
-        MetroState::join_track(&self.state, &self, to_track);
+    pub fn add_station<S: Into<Cow<'a, str>>>(&mut self, text: S) {
+        self.state
+            .borrow_mut()
+            .add_event(Event::detached_station(text));
     }
 
-    /// Returns `true` if the `Track` has been removed from
-    /// its [`Metro`].
+    /// Adds an annotation, i.e. text that is not tied to any [`Track`].
     ///
-    /// Note if a new track is created in the same [`Metro`], with
-    /// the same track [`id`], then `self` is no longer dangling
-    /// and the two `Track`s represent the same `Track`.
+    /// *[See `Event::Annotation` for more information.][`Event::Annotation`]*
     ///
-    /// [`Metro`]: struct.Metro.html
-    /// [`id`]: struct.Track.html#method.id
+    /// [`Track`]: struct.Track.html
+    /// [`Event::Annotation`]: enum.Event.html#variant.Annotation
     ///
-    /// # Example
+    /// ## Output Example
     ///
+    /// Given 3 existing tracks then `add_annotation("Hello World")` would render as:
+    ///
+    /// ```text
+    /// | | |
+    /// | | | Hello World
+    /// | | |
     /// ```
-    /// # use metro::Metro;
-    /// let mut metro = Metro::new();
+    #[inline]
+    pub fn add_annotation<S: Into<Cow<'a, str>>>(&mut self, text: S) {
+        self.state.borrow_mut().add_event(Event::annotation(text));
+    }
+
+    /// Adds a row of rails, that has no station.
     ///
-    /// // Create a new track
-    /// let mut track1 = metro.new_track();
+    /// *[See `Event::NoEvent` for more information.][`Event::NoEvent`]*
     ///
-    /// // Get a second reference to the same track
-    /// let mut track2 = metro.get_track(track1.id()).unwrap();
+    /// [`Event::NoEvent`]: enum.Event.html#variant.NoEvent
     ///
-    /// // They represent the same track, so `is_dangling` returns `false` for both
-    /// assert!(!track1.is_dangling());
-    /// assert!(!track2.is_dangling());
+    /// ## Output Example
     ///
-    /// // Stop the track
-    /// track1.stop();
-    /// // or
-    /// // drop(track1);
+    /// Given 3 existing tracks then `add_no_event()` would render as:
     ///
-    /// // Now `track2` is dangling as `track1` was stopped and they share track id
-    /// assert!(track2.is_dangling());
+    /// ```text
+    /// | | |
+    /// | | |
+    /// ```
+    #[inline]
+    pub fn add_no_event(&mut self) {
+        self.state.borrow_mut().add_event(Event::NoEvent);
+    }
+
+    /// Adds a legend of the labels of the current tracks.
     ///
-    /// // Create a new track that uses the same track id `0`
+    /// *[See `Event::Legend` for more information.][`Event::Legend`]*
     ///
-    /// // Create a new track with the same track id as `track2`
-    /// let mut track3 = metro.new_track_with_id(track2.id());
+    /// *[See also `Track::set_label`.][`Track::set_label`]*
     ///
-    /// // Now `track2` and `track3` represent the same track,
-    /// // so `is_dangling` again returns `false` for both
-    /// assert!(!track2.is_dangling());
-    /// assert!(!track3.is_dangling());
+    /// [`Event::Legend`]: enum.Event.html#variant.Legend
+    /// [`Track::set_label`]: struct.Track.html#method.set_label
+    ///
+    /// ## Output Example
+    ///
+    /// Given 3 existing tracks labeled `main`, `release` and `feature/x`
+    /// then `add_legend()` would render as:
+    ///
+    /// ```text
+    /// main
+    /// | release
+    /// | | feature/x
     /// ```
     #[inline]
-    pub fn is_dangling(&self) -> bool {
-        self.state
-            .borrow()
-            .tracks
-            .iter()
-            .all(|track| track.id != self.id)
+    pub fn add_legend(&mut self) {
+        self.state.borrow_mut().add_event(Event::Legend);
     }
 
+    /// *[See `to_writer`.][`to_writer`]*
+    ///
+    /// [`to_writer`]: fn.to_writer.html
     #[inline]
-    fn clone_ref(&self) -> Self {
-        Self {
-            state: Rc::clone(&self.state),
-            id: self.id,
-        }
+    pub fn to_writer<W: Write>(&self, writer: W) -> io::Result<()> {
+        let state = self.state.borrow();
+        to_writer(writer, &state.events)
     }
-}
 
-impl<'a> Drop for Track<'a> {
-    /// Drop implicitly calls [`Track::stop`].
+    /// *[See `to_writer_with`.][`to_writer_with`]*
     ///
-    /// [`Track::stop`]: struct.Track.html#method.stop
+    /// [`to_writer_with`]: fn.to_writer_with.html
     #[inline]
-    fn drop(&mut self) {
-        // Is `Track` still present in its `Metro`?
-        let is_dangling = self
-            .state
-            // When `metro.tracks.remove(index)` is called, then
-            // `Metro` is going to be mutably borrowed already,
-            // while triggering this `Drop`.
-            .try_borrow()
-            .map(|metro| metro.tracks.iter().all(|track| track.id != self.id))
-            // If "already mutably borrowed" when dropping,
-            // then assume it is in the context of something
-            // performing `tracks.remove(index)`, thus we
-            // assume the `Track` is dangling and already
-            // removed or being removed.
-            .unwrap_or(true);
-
-        if !is_dangling {
-            MetroState::add_event(&self.state, Event::StopTrack(self.id));
-
-            let mut state = self.state.borrow_mut();
-
-            // Remove the `Track` from its `Metro`
-            let index = state
-                .tracks
-                .iter()
-                .position(|track| track.id == self.id)
-                // Safe to use `unwrap` as `is_dangling` just verified the `Track`'s presence
-                .unwrap();
-            state.tracks.remove(index);
-        }
+    pub fn to_writer_with<W: Write>(&self, writer: W, opts: &Options) -> io::Result<()> {
+        let state = self.state.borrow();
+        to_writer_with(writer, &state.events, opts)
     }
-}
 
-impl fmt::Debug for Track<'_> {
-    #[inline]
-    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
-        fmt.debug_struct("Track").field("id", &self.id).finish()
-    }
-}
+    /// Creates the file at `path`, truncating it if it exists,
+    /// and writes the rendered events to it, using a [`BufWriter`].
+    ///
+    /// *[See `to_writer`.][`to_writer`]*
+    ///
+    /// [`to_writer`]: fn.to_writer.html
+    ///
+    /// [`BufWriter`]: https://doc.rust-lang.org/stable/std/io/struct.BufWriter.html
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use metro::Metro;
+    ///
+    /// let mut metro = Metro::new();
+    ///
+    /// let mut track1 = metro.new_track();
+    /// track1.add_station("Station 1");
+    ///
+    /// metro.render_to_file("metro.txt").unwrap();
+    /// ```
+    pub fn render_to_file<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        self.to_writer(&mut writer)?;
 
-struct MetroState<'a> {
-    tracks: Vec<Track<'a>>,
-    events: Vec<Event<'a>>,
-    next_id: usize,
-}
+        // Flush explicitly, as errors are ignored when dropping a `BufWriter`
+        writer.flush()
+    }
 
-impl<'a> MetroState<'a> {
+    /// *[See `to_vec`.][`to_vec`]*
+    ///
+    /// [`to_vec`]: fn.to_vec.html
     #[inline]
-    fn new() -> Self {
-        Self {
-            tracks: vec![],
-            events: vec![],
-            next_id: 0,
-        }
+    pub fn to_vec(&self) -> io::Result<Vec<u8>> {
+        let state = self.state.borrow();
+        to_vec(&state.events)
     }
 
-    /// Get a new track id.
-    ///
-    /// # Panics
+    /// *[See `to_vec_with`.][`to_vec_with`]*
     ///
-    /// Panics if the `next_id` overflows [`usize`].
-    ///
-    /// [`usize`]: https://doc.rust-lang.org/stable/std/primitive.usize.html
+    /// [`to_vec_with`]: fn.to_vec_with.html
     #[inline]
-    fn next_id(&mut self) -> usize {
-        let id = self.next_id;
-        self.next_id += 1;
-        id
+    pub fn to_vec_with(&self, opts: &Options) -> io::Result<Vec<u8>> {
+        let state = self.state.borrow();
+        to_vec_with(&state.events, opts)
     }
 
+    /// *[See `to_string`.][`to_string`]*
+    ///
+    /// [`to_string`]: fn.to_string.html
     #[inline]
-    fn new_track(metro: &RcMetro<'a>, track_id: usize) -> Track<'a> {
-        let state = metro.borrow();
-        let track = state.tracks.iter().find(|track| track.id == track_id);
-
-        if let Some(track) = track {
-            track.clone_ref()
-        } else {
-            drop(state);
-
-            let track = Track::new(Rc::clone(metro), track_id);
-            metro.borrow_mut().tracks.push(track.clone_ref());
-
-            MetroState::add_event(metro, Event::StartTrack(track_id));
-
-            track
-        }
+    pub fn to_string(&self) -> io::Result<String> {
+        let state = self.state.borrow();
+        to_string(&state.events)
     }
 
+    /// *[See `to_string_with`.][`to_string_with`]*
+    ///
+    /// [`to_string_with`]: fn.to_string_with.html
     #[inline]
-    fn get_track(metro: &RcMetro<'a>, track_id: usize) -> Option<Track<'a>> {
-        let state = metro.borrow();
-        let track = state.tracks.iter().find(|track| track.id == track_id);
-
-        track.map(Track::clone_ref)
+    pub fn to_string_with(&self, opts: &Options) -> io::Result<String> {
+        let state = self.state.borrow();
+        to_string_with(&state.events, opts)
     }
 
+    /// Renders the events with the text of all rows aligned to the
+    /// same column, i.e. using [`Options::align_text`]. All other
+    /// options are the defaults.
+    ///
+    /// Note that the whole graph is rendered before any text is
+    /// written, to measure the width of the widest rails.
+    ///
+    /// [`Options::align_text`]: struct.Options.html#structfield.align_text
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use metro::Metro;
+    ///
+    /// let mut metro = Metro::new();
+    ///
+    /// let mut track1 = metro.new_track();
+    /// track1.add_station("Station 1");
+    ///
+    /// let mut track2 = track1.split();
+    /// track2.add_station("Station 2");
+    ///
+    /// assert_eq!(
+    ///     metro.to_string_pretty().unwrap(),
+    ///     "*   Station 1\n|\\\n| * Station 2\n"
+    /// );
+    /// ```
     #[inline]
-    fn split_track(metro: &RcMetro<'a>, from_track: &Track, new_track_id: usize) -> Track<'a> {
-        let state = metro.borrow();
-        let new_track = state.tracks.iter().find(|track| track.id == new_track_id);
-
-        if let Some(new_track) = new_track {
-            new_track.clone_ref()
-        } else {
-            drop(state);
-
-            let new_track = Track::new(Rc::clone(metro), new_track_id);
-            metro.borrow_mut().tracks.push(new_track.clone_ref());
-
-            MetroState::add_event(metro, Event::SplitTrack(from_track.id(), new_track_id));
-
-            new_track
-        }
+    pub fn to_string_pretty(&self) -> io::Result<String> {
+        let opts = Options {
+            align_text: true,
+            ..Options::default()
+        };
+        self.to_string_with(&opts)
     }
 
-    /// The caller must consume `from_track`.
-    /// The caller must not produce `Event::StopTrack`.
+    /// Returns [`Vec`]`<`[`Event`]`>` of the events currently
+    /// in this `Metro`.
+    ///
+    /// *[See also `into_events`.][`into_events`]*
+    ///
+    /// *[See also `Metro::to_string`.][`to_string`]*
+    ///
+    /// [`into_events`]: struct.Metro.html#method.into_events
+    /// [`to_string`]: struct.Metro.html#method.to_string
+    /// [`Event`]: enum.Event.html
+    ///
+    /// [`Vec`]: https://doc.rust-lang.org/stable/std/vec/struct.Vec.html
     #[inline]
-    fn join_track(metro: &RcMetro<'a>, from_track: &Track, to_track: &Track) {
-        let from_track_id = from_track.id();
-
-        // Whether either track already stopped existing does not matter
-        // as `to_string` handles rendering and resolving "edge cases".
-        MetroState::add_event(metro, Event::JoinTrack(from_track_id, to_track.id()));
+    pub fn to_events(&self) -> Vec<Event<'a>> {
+        let state = self.state.borrow();
+        state.events.clone()
+    }
 
-        let mut state = metro.borrow_mut();
+    /// Returns the lifespans of the tracks, grouping the events
+    /// of each track from its start to its stop.
+    ///
+    /// *[See `track_spans` for more information.][`track_spans`]*
+    ///
+    /// [`track_spans`]: fn.track_spans.html
+    #[inline]
+    pub fn to_spans(&self) -> Vec<TrackSpan<'a>> {
+        track_spans(&self.state.borrow().events)
+    }
 
-        // If `from_track` `is_dangling` then it has already been removed from `tracks`
-        if let Some(index) = state
-            .tracks
-            .iter()
-            .position(|track| track.id == from_track_id)
-        {
-            state.tracks.remove(index);
-        }
+    /// Consumes `Metro` and returns its [`Vec`]`<`[`Event`]`>`
+    /// of the events.
+    ///
+    /// *[See also `to_events`.][`to_events`]*
+    ///
+    /// *[See also `Metro::to_string`.][`to_string`]*
+    ///
+    /// [`to_events`]: struct.Metro.html#method.to_events
+    /// [`to_string`]: struct.Metro.html#method.to_string
+    #[inline]
+    pub fn into_events(self) -> Vec<Event<'a>> {
+        let mut state = self.state.borrow_mut();
+        mem::take(&mut state.events)
     }
+}
 
+impl<'a> Extend<Event<'a>> for Metro<'a> {
+    /// *[See `Metro::extend_events`.][`Metro::extend_events`]*
+    ///
+    /// [`Metro::extend_events`]: struct.Metro.html#method.extend_events
     #[inline]
-    fn add_event(metro: &RcMetro<'a>, event: Event<'a>) {
-        metro.borrow_mut().events.push(event);
+    fn extend<I: IntoIterator<Item = Event<'a>>>(&mut self, events: I) {
+        self.extend_events(events);
     }
 }
 
-#[cfg(test)]
-mod tests {
-    use super::{to_string, Event, Event::*, Metro};
+impl<'a> IntoIterator for Metro<'a> {
+    type Item = Event<'a>;
+    type IntoIter = vec::IntoIter<Event<'a>>;
 
-    #[test]
-    fn lib_example() {
+    /// Consumes `Metro` and returns an iterator over its events.
+    ///
+    /// *[See `Metro::into_events`.][`Metro::into_events`]*
+    ///
+    /// [`Metro::into_events`]: struct.Metro.html#method.into_events
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use metro::{Event, Metro};
+    ///
+    /// let mut metro = Metro::new();
+    ///
+    /// let mut track1 = metro.new_track();
+    /// track1.add_station("Station 1");
+    ///
+    /// let stations = metro
+    ///     .into_iter()
+    ///     .filter(|event| matches!(event, Event::Station(..)))
+    ///     .count();
+    ///
+    /// assert_eq!(stations, 1);
+    /// ```
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.into_events().into_iter()
+    }
+}
+
+impl<'a> IntoIterator for &Metro<'a> {
+    type Item = Event<'a>;
+    type IntoIter = vec::IntoIter<Event<'a>>;
+
+    /// Returns an iterator over clones of the events, as the
+    /// events cannot be borrowed beyond the `Metro`'s `RefCell`.
+    ///
+    /// *[See `Metro::to_events`.][`Metro::to_events`]*
+    ///
+    /// [`Metro::to_events`]: struct.Metro.html#method.to_events
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.to_events().into_iter()
+    }
+}
+
+impl fmt::Display for Metro<'_> {
+    /// Renders the events, the same as [`Metro::to_string`].
+    ///
+    /// [`Metro::to_string`]: struct.Metro.html#method.to_string
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use metro::Metro;
+    ///
+    /// let mut metro = Metro::new();
+    ///
+    /// let mut track1 = metro.new_track();
+    /// track1.add_station("Station 1");
+    ///
+    /// assert_eq!(format!("{}", metro), "* Station 1\n");
+    /// ```
+    #[inline]
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        let state = self.state.borrow();
+        to_fmt_writer(fmt, &state.events)
+    }
+}
+
+/// A snapshot of a [`Metro`], created by [`Metro::checkpoint`], which
+/// can be passed to [`Metro::rewind`] to discard everything added after.
+///
+/// [`Metro`]: struct.Metro.html
+/// [`Metro::checkpoint`]: struct.Metro.html#method.checkpoint
+/// [`Metro::rewind`]: struct.Metro.html#method.rewind
+#[derive(Clone, Debug)]
+pub struct Checkpoint {
+    events: usize,
+    tracks: Vec<TrackId>,
+    implicit: bool,
+    parents: HashMap<TrackId, TrackId>,
+    next_id: usize,
+}
+
+/// The error returned by [`Metro::try_new_track`] and [`Track::try_split`],
+/// when more than [`usize`] tracks have been created, i.e. when no
+/// unused track id remains.
+///
+/// [`Metro::try_new_track`]: struct.Metro.html#method.try_new_track
+/// [`Track::try_split`]: struct.Track.html#method.try_split
+/// [`usize`]: https://doc.rust-lang.org/stable/std/primitive.usize.html
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct TrackLimitError;
+
+impl fmt::Display for TrackLimitError {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        write!(fmt, "more than {} tracks have been created", usize::MAX)
+    }
+}
+
+impl error::Error for TrackLimitError {}
+
+/// The `Track` struct represents a track in the [`Metro`].
+/// The `Track` struct is created with the [`new_track`] or
+/// [`new_track_with_id`] on [`Metro`].
+///
+/// *[See `Metro` for a complete example.][`Metro`]*
+///
+/// [`Metro`]: struct.Metro.html
+/// [`new_track`]: struct.Metro.html#method.new_track
+/// [`new_track_with_id`]: struct.Metro.html#method.new_track_with_id
+pub struct Track<'a> {
+    state: RcMetro<'a>,
+    id: TrackId,
+}
+
+impl<'a> Track<'a> {
+    #[inline]
+    fn new(state: RcMetro<'a>, id: TrackId) -> Self {
+        Self { state, id }
+    }
+
+    /// Returns the track id.
+    #[inline]
+    pub fn id(&self) -> TrackId {
+        self.id
+    }
+
+    /// Stop this `Track`, and removes it from [`Metro`].
+    ///
+    /// The track [`id`] can be reused after with [`new_track_with_id`].
+    ///
+    /// ### Dangling Tracks
+    ///
+    /// If other tracks exist, which has the same track [`id`], e.g. by
+    /// calling `get_track` multiple times with the same track [`id`], then
+    /// the other tracks become "dangling". However, if a new track is created
+    /// with [`new_track_with_id`], then the other references are
+    /// not dangling anymore.
+    ///
+    /// *[See `is_dangling` for more information.][`is_dangling`]*
+    ///
+    /// [`id`]: struct.Track.html#method.id
+    /// [`is_dangling`]: struct.Track.html#method.is_dangling
+    ///
+    /// [`Metro`]: struct.Metro.html
+    /// [`new_track_with_id`]: struct.Metro.html#method.new_track_with_id
+    /// [`get_track`]: struct.Metro.html#method.get_track
+    ///
+    /// ## Output Example
+    ///
+    /// Given 3 existing tracks then calling `stop()` on the middle track would render as:
+    ///
+    /// ```text
+    /// | | |
+    /// | " |
+    /// |  /
+    /// | |
+    /// ```
+    #[inline]
+    pub fn stop(self) {
+        // Method is empty as the logic is implemented in Drop for Track
+    }
+
+    /// Creates a station that is tied to this `Track`.
+    ///
+    /// See [`Metro::add_station`] to create a station that is
+    /// tied to a `Track`.
+    ///
+    /// The `text` can either be borrowed, i.e. `&str`, or owned, i.e.
+    /// `String`, such as text computed at runtime. Owned text is moved
+    /// into the [`Event::Station`], so it does not need to outlive the
+    /// [`Metro`]. Borrowed text avoids allocating, but must outlive it.
+    ///
+    /// [`Metro`]: struct.Metro.html
+    /// [`Metro::add_station`]: struct.Metro.html#method.add_station
+    /// [`Event::Station`]: enum.Event.html#variant.Station
+    ///
+    /// ## Output Example
+    ///
+    /// Given 3 existing tracks then `add_station("Hello World")` on the middle track would render as:
+    ///
+    /// ```text
+    /// | | |
+    /// | * | Hello World
+    /// | | |
+    /// ```
+    ///
+    /// Text with multiple lines is also allowed.
+    /// Given 3 existing tracks then `add_station("Hello\nWorld")` would render as:
+    ///
+    /// ```text
+    /// | | |
+    /// | * | Hello
+    /// | | | World
+    /// | | |
+    /// ```
+    #[inline]
+    pub fn add_station<S: Into<Cow<'a, str>>>(&mut self, text: S) {
+        self.state
+            .borrow_mut()
+            .add_event(Event::station(self.id, text));
+    }
+
+    /// Creates a station tied to this `Track` for each of the `texts`, in order.
+    ///
+    /// *[See `add_station`.][`add_station`]*
+    ///
+    /// [`add_station`]: struct.Track.html#method.add_station
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use metro::Metro;
+    ///
+    /// let mut metro = Metro::new();
+    ///
+    /// let mut track1 = metro.new_track();
+    /// track1.add_stations(&["Station 1", "Station 2", "Station 3"]);
+    ///
+    /// let string = metro.to_string().unwrap();
+    ///
+    /// assert_eq!(string, "* Station 1\n* Station 2\n* Station 3\n");
+    /// ```
+    pub fn add_stations(&mut self, texts: &[&'a str]) {
+        let mut state = self.state.borrow_mut();
+
+        for &text in texts {
+            state.add_event(Event::station(self.id, text));
+        }
+    }
+
+    /// Creates a station that is tied to this `Track`, which is
+    /// rendered using `marker` instead of `*`.
+    ///
+    /// *[See `Event::StationMarked` for more information.][`Event::StationMarked`]*
+    ///
+    /// [`Event::StationMarked`]: enum.Event.html#variant.StationMarked
+    ///
+    /// ## Output Example
+    ///
+    /// Given 3 existing tracks then `add_station_marked("Hello World", '@')`
+    /// on the middle track would render as:
+    ///
+    /// ```text
+    /// | | |
+    /// | @ | Hello World
+    /// | | |
+    /// ```
+    #[inline]
+    pub fn add_station_marked<S: Into<Cow<'a, str>>>(&mut self, text: S, marker: char) {
+        self.state
+            .borrow_mut()
+            .add_event(Event::station_marked(self.id, text, marker));
+    }
+
+    /// Sets the label of this `Track`, which is rendered
+    /// by [`Metro::add_legend`].
+    ///
+    /// *[See `Event::LabelTrack` for more information.][`Event::LabelTrack`]*
+    ///
+    /// [`Metro::add_legend`]: struct.Metro.html#method.add_legend
+    /// [`Event::LabelTrack`]: enum.Event.html#variant.LabelTrack
+    #[inline]
+    pub fn set_label<S: Into<Cow<'a, str>>>(&mut self, label: S) {
+        self.state
+            .borrow_mut()
+            .add_event(Event::label_track(self.id, label));
+    }
+
+    /// Create a new `Track` that branches of from this track.
+    ///
+    /// To create a new `Track` with a specific track [`id`], then use [`new_track_with_id`].
+    ///
+    /// [`id`]: struct.Track.html#method.id
+    /// [`new_track_with_id`]: struct.Track.html#method.split_with_id
+    ///
+    /// # Panics
+    ///
+    /// Panics if more than [`usize`] tracks have been created.
+    /// *[See `try_split` for returning an error instead.][`try_split`]*
+    ///
+    /// [`usize`]: https://doc.rust-lang.org/stable/std/primitive.usize.html
+    /// [`try_split`]: struct.Track.html#method.try_split
+    ///
+    /// ## Output Example
+    ///
+    /// Given 3 existing tracks then calling `split` on the middle track would render as:
+    ///
+    /// ```text
+    /// | | |
+    /// | |\ \
+    /// | | | |
+    /// ```
+    #[inline]
+    pub fn split(&self) -> Track<'a> {
+        self.try_split().unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    /// Create a new `Track` like [`split`], except an error is
+    /// returned instead of panicking, if more than [`usize`] tracks
+    /// have been created.
+    ///
+    /// [`split`]: struct.Track.html#method.split
+    /// [`usize`]: https://doc.rust-lang.org/stable/std/primitive.usize.html
+    #[inline]
+    pub fn try_split(&self) -> Result<Track<'a>, TrackLimitError> {
+        let id = self.state.borrow_mut().try_next_id()?;
+        Ok(self.split_with_id(id))
+    }
+
+    /// Create a new `Track` that branches of from this track.
+    ///
+    /// To create a new `Track` without a specific track [`id`], then use [`split`].
+    ///
+    /// [`id`]: struct.Track.html#method.id
+    /// [`split`]: struct.Track.html#method.split
+    ///
+    /// # Panics
+    ///
+    /// Panics if more than [`usize`] tracks have been created.
+    ///
+    /// [`usize`]: https://doc.rust-lang.org/stable/std/primitive.usize.html
+    ///
+    /// ## Output Example
+    ///
+    /// Given 3 existing tracks then calling `split` on the middle track would render as:
+    ///
+    /// ```text
+    /// | | |
+    /// | |\ \
+    /// | | | |
+    /// ```
+    #[inline]
+    pub fn split_with_id<T: Into<TrackId>>(&self, new_track_id: T) -> Track<'a> {
+        let new_track_id = new_track_id.into();
+        self.state.borrow_mut().split_track(self.id, new_track_id);
+        Track::new(Rc::clone(&self.state), new_track_id)
+    }
+
+    /// Create `n` new `Track`s that branch of from this track within
+    /// a single row, ordered from left to right.
+    ///
+    /// *[See `Event::SplitTrackN` for more information.][`Event::SplitTrackN`]*
+    ///
+    /// [`Event::SplitTrackN`]: enum.Event.html#variant.SplitTrackN
+    ///
+    /// # Panics
+    ///
+    /// Panics if more than [`usize`] tracks have been created.
+    ///
+    /// [`usize`]: https://doc.rust-lang.org/stable/std/primitive.usize.html
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use metro::Metro;
+    /// let mut metro = Metro::new();
+    ///
+    /// let mut track1 = metro.new_track();
+    /// track1.add_station("Station 1");
+    ///
+    /// let mut tracks = track1.split_n(3);
+    /// tracks[2].add_station("Station 2");
+    ///
+    /// assert_eq!(metro.track_ids(), [0, 1, 2, 3]);
+    ///
+    /// let string = metro.to_string().unwrap();
+    ///
+    /// assert_eq!(
+    ///     string,
+    ///     r#"* Station 1
+    /// |\_\_\
+    /// | | | * Station 2
+    /// "#
+    /// );
+    /// ```
+    ///
+    /// ## Output Example
+    ///
+    /// Given 3 existing tracks then calling `split_n(2)` on the middle track would render as:
+    ///
+    /// ```text
+    /// | | |
+    /// | |  \
+    /// | |\_\ \
+    /// | | | | |
+    /// ```
+    pub fn split_n(&self, n: usize) -> Vec<Track<'a>> {
+        let new_track_ids = self.state.borrow_mut().split_tracks(self.id, n);
+
+        new_track_ids
+            .into_iter()
+            .map(|new_track_id| Track::new(Rc::clone(&self.state), new_track_id))
+            .collect()
+    }
+
+    /// Create a new `Track` that branches of from this track, with
+    /// a station on the new `Track` immediately after the split.
+    ///
+    /// *[See `Event::SplitStation` for more information.][`Event::SplitStation`]*
+    ///
+    /// [`Event::SplitStation`]: enum.Event.html#variant.SplitStation
+    ///
+    /// # Panics
+    ///
+    /// Panics if more than [`usize`] tracks have been created.
+    ///
+    /// [`usize`]: https://doc.rust-lang.org/stable/std/primitive.usize.html
+    ///
+    /// ## Output Example
+    ///
+    /// Given 3 existing tracks then calling `split_station("Hello World")`
+    /// on the middle track would render as:
+    ///
+    /// ```text
+    /// | | |
+    /// | |\ \
+    /// | | * | Hello World
+    /// | | | |
+    /// ```
+    pub fn split_station<S: Into<Cow<'a, str>>>(&self, text: S) -> Track<'a> {
+        let new_track_id = self.state.borrow_mut().split_station(self.id, text.into());
+        Track::new(Rc::clone(&self.state), new_track_id)
+    }
+
+    /// Create a new `Track` that branches of from this track, and is placed
+    /// immediately to the right of `neighbor`, instead of this track.
+    ///
+    /// If `neighbor` is [dangling], then the new `Track` is placed immediately
+    /// to the right of this track, i.e. the same as [`split`].
+    ///
+    /// *[See `Event::SplitTrackAt` for more information.][`Event::SplitTrackAt`]*
+    ///
+    /// [dangling]: struct.Track.html#method.is_dangling
+    /// [`split`]: struct.Track.html#method.split
+    /// [`Event::SplitTrackAt`]: enum.Event.html#variant.SplitTrackAt
+    ///
+    /// # Panics
+    ///
+    /// Panics if more than [`usize`] tracks have been created.
+    ///
+    /// [`usize`]: https://doc.rust-lang.org/stable/std/primitive.usize.html
+    ///
+    /// ## Output Example
+    ///
+    /// Given 4 existing tracks then calling `split_at` on the leftmost track,
+    /// with the third track as `neighbor` would render as:
+    ///
+    /// ```text
+    /// | | | |
+    /// |\|_|_ \
+    /// | | | | |
+    /// ```
+    #[inline]
+    pub fn split_at(&self, neighbor: &Track) -> Track<'a> {
+        let mut state = self.state.borrow_mut();
+
+        let new_track_id = state.next_id();
+        state.split_track_at(self.id, new_track_id, neighbor.id);
+
+        drop(state);
+
+        Track::new(Rc::clone(&self.state), new_track_id)
+    }
+
+    /// Merges `self` with `to_track`, removing `self` from
+    /// the [`Metro`].
+    ///
+    /// [`Metro`]: struct.Metro.html
+    ///
+    /// ## Output Example
+    ///
+    /// Given 3 existing tracks, then calling join on the middle
+    /// track with the leftmost track would render as:
+    ///
+    /// ```text
+    /// | | |
+    /// |/ /
+    /// | |
+    /// ```
+    ///
+    /// Given 6 existing tracks, then calling join on the second
+    /// rightmost track with the leftmost track would render as:
+    ///
+    /// ```text
+    /// | | | | | |
+    /// | |_|_|/ /
+    /// |/| | | |
+    /// | | | | |
+    /// ```
+    #[inline]
+    pub fn join(self, to_track: &Track) {
+        self.state.borrow_mut().join_track(self.id, to_track.id);
+    }
+
+    /// Links `self` with `to_track`, without joining them.
+    ///
+    /// *[See `Event::Link` for more information.][`Event::Link`]*
+    ///
+    /// [`Event::Link`]: enum.Event.html#variant.Link
+    ///
+    /// ## Output Example
+    ///
+    /// Given 3 existing tracks, then calling link on the rightmost
+    /// track with the leftmost track would render as:
+    ///
+    /// ```text
+    /// | | |
+    /// |.|.|
+    /// | | |
+    /// ```
+    #[inline]
+    pub fn link(&self, to_track: &Track) {
+        self.state
+            .borrow_mut()
+            .add_event(Event::Link(self.id, to_track.id));
+    }
+
+    /// Changes the id of this `Track` to `new_id`, e.g. when the
+    /// canonical id of a track is only known after it was created.
+    /// Subsequent events of this `Track` use `new_id`.
+    ///
+    /// Returns `false` and leaves the `Track` unchanged, if a track with
+    /// `new_id` already exists, or if this `Track` [is dangling]. Tracks
+    /// are never merged by renaming, use [`join`] for that instead.
+    ///
+    /// Other `Track`s with the old [`id`] become dangling, while the old
+    /// [`id`] can be reused for new tracks.
+    ///
+    /// *[See `Event::RenameTrack` for more information.][`Event::RenameTrack`]*
+    ///
+    /// [`id`]: struct.Track.html#method.id
+    /// [is dangling]: struct.Track.html#method.is_dangling
+    /// [`join`]: struct.Track.html#method.join
+    /// [`Event::RenameTrack`]: enum.Event.html#variant.RenameTrack
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use metro::Metro;
+    /// let mut metro = Metro::new();
+    ///
+    /// let mut track1 = metro.new_track();
+    /// let track2 = metro.new_track_with_id(5);
+    ///
+    /// assert!(track1.rename_id(3));
+    /// assert_eq!(track1.id(), 3);
+    /// assert_eq!(metro.track_ids(), [3, 5]);
+    ///
+    /// // Track 5 already exists
+    /// assert!(!track1.rename_id(track2.id()));
+    /// assert_eq!(track1.id(), 3);
+    /// ```
+    #[inline]
+    pub fn rename_id<T: Into<TrackId>>(&mut self, new_id: T) -> bool {
+        let new_id = new_id.into();
+        if new_id == self.id {
+            return !self.is_dangling();
+        }
+
+        let mut state = self.state.borrow_mut();
+
+        if state.rename_track(self.id, new_id) {
+            state.add_event(Event::RenameTrack(self.id, new_id));
+
+            drop(state);
+
+            self.id = new_id;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Returns `true` if the `Track` has been removed from
+    /// its [`Metro`].
+    ///
+    /// Note if a new track is created in the same [`Metro`], with
+    /// the same track [`id`], then `self` is no longer dangling
+    /// and the two `Track`s represent the same `Track`.
+    ///
+    /// [`Metro`]: struct.Metro.html
+    /// [`id`]: struct.Track.html#method.id
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use metro::Metro;
+    /// let mut metro = Metro::new();
+    ///
+    /// // Create a new track
+    /// let mut track1 = metro.new_track();
+    ///
+    /// // Get a second reference to the same track
+    /// let mut track2 = metro.get_track(track1.id()).unwrap();
+    ///
+    /// // They represent the same track, so `is_dangling` returns `false` for both
+    /// assert!(!track1.is_dangling());
+    /// assert!(!track2.is_dangling());
+    ///
+    /// // Stop the track
+    /// track1.stop();
+    /// // or
+    /// // drop(track1);
+    ///
+    /// // Now `track2` is dangling as `track1` was stopped and they share track id
+    /// assert!(track2.is_dangling());
+    ///
+    /// // Create a new track that uses the same track id `0`
+    ///
+    /// // Create a new track with the same track id as `track2`
+    /// let mut track3 = metro.new_track_with_id(track2.id());
+    ///
+    /// // Now `track2` and `track3` represent the same track,
+    /// // so `is_dangling` again returns `false` for both
+    /// assert!(!track2.is_dangling());
+    /// assert!(!track3.is_dangling());
+    /// ```
+    #[inline]
+    pub fn is_dangling(&self) -> bool {
+        !self.state.borrow().has_track(self.id)
+    }
+
+    /// Returns the column of this `Track`, i.e. the index of its rail
+    /// from the left, or `None` if the `Track` [is dangling].
+    ///
+    /// The column is the same as when rendering with the default
+    /// [`Options::initial_tracks`], i.e. track `0` exists implicitly,
+    /// until it is stopped.
+    ///
+    /// [`Options::initial_tracks`]: struct.Options.html#structfield.initial_tracks
+    /// *[See also `Metro::track_ids`.][`Metro::track_ids`]*
+    ///
+    /// [is dangling]: struct.Track.html#method.is_dangling
+    /// [`Metro::track_ids`]: struct.Metro.html#method.track_ids
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use metro::Metro;
+    /// let mut metro = Metro::new();
+    ///
+    /// let track1 = metro.new_track();
+    /// let track2 = metro.new_track();
+    /// let track3 = track1.split();
+    ///
+    /// assert_eq!(track1.column(), Some(0));
+    /// assert_eq!(track2.column(), Some(2));
+    /// assert_eq!(track3.column(), Some(1));
+    ///
+    /// let track4 = metro.get_track(track3.id()).unwrap();
+    /// track3.stop();
+    ///
+    /// assert_eq!(track2.column(), Some(1));
+    /// assert_eq!(track4.column(), None);
+    /// ```
+    #[inline]
+    pub fn column(&self) -> Option<usize> {
+        self.state.borrow().column(self.id)
+    }
+
+    /// Returns the track id of the track this `Track` was split from,
+    /// or `None` if it was not split from a track, e.g. if it was created
+    /// by [`Metro::new_track`], or if the `Track` [is dangling].
+    ///
+    /// The parent is recorded when the `Track` is split, and is kept until
+    /// the `Track` is stopped, even if the parent track is stopped before.
+    ///
+    /// [is dangling]: struct.Track.html#method.is_dangling
+    /// [`Metro::new_track`]: struct.Metro.html#method.new_track
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use metro::{Metro, TrackId};
+    /// let mut metro = Metro::new();
+    ///
+    /// let track1 = metro.new_track();
+    /// let track2 = track1.split();
+    /// let track3 = track2.split();
+    ///
+    /// assert_eq!(track1.parent_id(), None);
+    /// assert_eq!(track2.parent_id(), Some(TrackId(0)));
+    /// assert_eq!(track3.parent_id(), Some(TrackId(1)));
+    ///
+    /// track2.stop();
+    ///
+    /// assert_eq!(track3.parent_id(), Some(TrackId(1)));
+    /// ```
+    #[inline]
+    pub fn parent_id(&self) -> Option<TrackId> {
+        self.state.borrow().parent(self.id)
+    }
+}
+
+impl<'a> Drop for Track<'a> {
+    /// Drop implicitly calls [`Track::stop`].
+    ///
+    /// [`Track::stop`]: struct.Track.html#method.stop
+    #[inline]
+    fn drop(&mut self) {
+        self.state.borrow_mut().stop_track(self.id);
+    }
+}
+
+impl fmt::Debug for Track<'_> {
+    #[inline]
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt.debug_struct("Track").field("id", &self.id).finish()
+    }
+}
+
+/// The state shared by [`Metro`] and [`Track`], as well as by
+/// [`SyncMetro`] and [`SyncTrack`].
+///
+/// The state only keeps track of the ids of the current tracks,
+/// so the handles are free to reference it however they like.
+///
+/// [`Metro`]: struct.Metro.html
+/// [`Track`]: struct.Track.html
+/// [`SyncMetro`]: sync/struct.SyncMetro.html
+/// [`SyncTrack`]: sync/struct.SyncTrack.html
+pub(crate) struct MetroState<'a> {
+    /// The current tracks, in column order, including track `0`,
+    /// which exists implicitly when rendering.
+    tracks: Vec<TrackId>,
+    /// Whether track `0` only exists implicitly, i.e. it has not
+    /// been started by any event.
+    implicit: bool,
+    /// The track each current track was split from, if any.
+    parents: HashMap<TrackId, TrackId>,
+    pub(crate) events: Vec<Event<'a>>,
+    next_id: usize,
+}
+
+impl<'a> MetroState<'a> {
+    #[inline]
+    pub(crate) fn new() -> Self {
+        Self {
+            tracks: vec![TrackId(0)],
+            implicit: true,
+            parents: HashMap::new(),
+            events: vec![],
+            next_id: 0,
+        }
+    }
+
+    /// Creates the state from existing `events`, by replaying them
+    /// to reconstruct the current `tracks` and `next_id`.
+    pub(crate) fn from_events(events: Vec<Event<'a>>) -> Self {
+        let mut state = Self::new();
+
+        for event in &events {
+            state.replay(event);
+        }
+
+        state.events = events;
+        state
+    }
+
+    /// Updates `tracks`, `parents` and `next_id` as if `event` was added.
+    fn replay(&mut self, event: &Event) {
+        use Event::*;
+        match event {
+            &StartTrack(track_id) => self.replay_start_track(track_id, None),
+
+            &StartTracks(track_ids) => {
+                for &track_id in track_ids {
+                    self.replay_start_track(track_id, None);
+                }
+            }
+
+            &StartTrackAt(track_id, column_index) => {
+                let column_index = column_index.min(self.tracks.len());
+                self.replay_start_track(track_id, Some(column_index));
+            }
+
+            &SplitTrack(from_track_id, new_track_id)
+            | &SplitStation(from_track_id, new_track_id, _) => {
+                let index = self.insert_index(from_track_id, from_track_id);
+                self.replay_split_track(from_track_id, new_track_id, index);
+            }
+
+            &SplitTrackAt(from_track_id, new_track_id, neighbor_track_id) => {
+                let index = self.insert_index(from_track_id, neighbor_track_id);
+                self.replay_split_track(from_track_id, new_track_id, index);
+            }
+
+            SplitTrackN(from_track_id, new_track_ids) => {
+                let mut index = self.insert_index(*from_track_id, *from_track_id);
+
+                for &new_track_id in new_track_ids.iter() {
+                    if !self.has_track(new_track_id) {
+                        self.insert_split_track(*from_track_id, new_track_id, index);
+                        index = index.map(|index| index + 1);
+                    }
+
+                    self.next_id = self.next_id.max(new_track_id.0.saturating_add(1));
+                }
+            }
+
+            &StopTrack(track_id)
+            | &StopTrackLabeled(track_id, _)
+            | &JoinTrack(track_id, _)
+            | &JoinTrackToward(track_id, _) => {
+                self.tracks.retain(|&id| id != track_id);
+            }
+
+            &JoinTrackKeepSource(from_track_id, to_track_id) => {
+                let from_track_index = self.tracks.iter().position(|&id| id == from_track_id);
+                let to_track_index = self.tracks.iter().position(|&id| id == to_track_id);
+
+                // The remaining rail is the leftmost of the two
+                if let (Some(from_track_index), Some(to_track_index)) =
+                    (from_track_index, to_track_index)
+                {
+                    self.tracks[from_track_index.min(to_track_index)] = from_track_id;
+                    self.tracks.remove(from_track_index.max(to_track_index));
+                } else {
+                    self.tracks.retain(|&id| id != to_track_id);
+                }
+            }
+
+            &JoinTrackOrRename(from_track_id, to_track_id) => {
+                self.start_implicit(from_track_id);
+
+                // The track continues as `to_track_id` in the same column
+                if !self.rename_track(from_track_id, to_track_id) {
+                    self.tracks.retain(|&id| id != from_track_id);
+                }
+            }
+
+            &StopTracks(track_ids) => self.tracks.retain(|id| !track_ids.contains(id)),
+
+            &RenameTrack(old_track_id, new_track_id) => {
+                self.start_implicit(old_track_id);
+                self.rename_track(old_track_id, new_track_id);
+            }
+
+            &JoinTracks(from_track_ids, to_track_id) => {
+                self.tracks
+                    .retain(|id| (*id == to_track_id) || !from_track_ids.contains(id));
+            }
+
+            Station(..) | StationMarked(..) | StationMulti(..) | DetachedStation(_)
+            | Annotation(_) | Link(..) | LabelTrack(..) | Legend | NoEvent => {}
+        }
+    }
+
+    /// Inserts `track_id` at `index`, or rightmost if `None`,
+    /// unless it already exists.
+    #[inline]
+    fn replay_start_track(&mut self, track_id: TrackId, index: Option<usize>) {
+        if !self.has_track(track_id) {
+            self.insert_track(track_id, index);
+        }
+
+        self.next_id = self.next_id.max(track_id.0.saturating_add(1));
+    }
+
+    /// Inserts `new_track_id` at `index`, or rightmost if `None`,
+    /// unless it already exists.
+    #[inline]
+    fn replay_split_track(
+        &mut self,
+        from_track_id: TrackId,
+        new_track_id: TrackId,
+        index: Option<usize>,
+    ) {
+        if !self.has_track(new_track_id) {
+            self.insert_split_track(from_track_id, new_track_id, index);
+        }
+
+        self.next_id = self.next_id.max(new_track_id.0.saturating_add(1));
+    }
+
+    /// Returns the index a track split from `from_track_id` is inserted
+    /// at, i.e. to the right of `neighbor_track_id` if it exists, otherwise
+    /// to the right of `from_track_id`, otherwise `None` for rightmost.
+    #[inline]
+    fn insert_index(&self, from_track_id: TrackId, neighbor_track_id: TrackId) -> Option<usize> {
+        let position = |track_id| self.tracks.iter().position(|&id| id == track_id);
+
+        position(neighbor_track_id)
+            .or_else(|| position(from_track_id))
+            .map(|index| index + 1)
+    }
+
+    #[inline]
+    fn insert_track(&mut self, track_id: TrackId, index: Option<usize>) {
+        // Starting the implicit track keeps it in its column
+        if self.is_implicit(track_id) {
+            self.implicit = false;
+            return;
+        } else if track_id == TrackId(0) {
+            self.implicit = false;
+        }
+
+        match index {
+            Some(index) => self.tracks.insert(index, track_id),
+            None => self.tracks.push(track_id),
+        }
+
+        // The track id may be reused from a stopped track
+        self.parents.remove(&track_id);
+    }
+
+    /// Inserts `new_track_id` like `insert_track`, recording
+    /// `from_track_id` as its parent, if it exists.
+    #[inline]
+    fn insert_split_track(
+        &mut self,
+        from_track_id: TrackId,
+        new_track_id: TrackId,
+        index: Option<usize>,
+    ) {
+        let has_parent = self.tracks.contains(&from_track_id);
+
+        self.insert_track(new_track_id, index);
+
+        if has_parent {
+            self.parents.insert(new_track_id, from_track_id);
+        }
+    }
+
+    /// Returns the id of the track `track_id` was split from,
+    /// if `track_id` exists and was split from another track.
+    #[inline]
+    pub(crate) fn parent(&self, track_id: TrackId) -> Option<TrackId> {
+        if self.has_track(track_id) {
+            self.parents.get(&track_id).copied()
+        } else {
+            None
+        }
+    }
+
+    /// Returns the current tracks, in column order.
+    #[inline]
+    pub(crate) fn track_ids(&self) -> Vec<TrackId> {
+        self.tracks.clone()
+    }
+
+    #[inline]
+    pub(crate) fn event_count(&self) -> usize {
+        self.events.len()
+    }
+
+    #[inline]
+    pub(crate) fn active_track_count(&self) -> usize {
+        self.tracks.len()
+    }
+
+    #[inline]
+    pub(crate) fn checkpoint(&self) -> Checkpoint {
+        Checkpoint {
+            events: self.events.len(),
+            tracks: self.tracks.clone(),
+            implicit: self.implicit,
+            parents: self.parents.clone(),
+            next_id: self.next_id,
+        }
+    }
+
+    #[inline]
+    pub(crate) fn rewind(&mut self, checkpoint: Checkpoint) {
+        self.events.truncate(checkpoint.events);
+        self.tracks = checkpoint.tracks;
+        self.implicit = checkpoint.implicit;
+        self.parents = checkpoint.parents;
+        self.next_id = checkpoint.next_id;
+    }
+
+    /// Removes all events and tracks, keeping their capacity.
+    #[inline]
+    pub(crate) fn clear(&mut self) {
+        self.events.clear();
+        self.tracks.clear();
+        self.tracks.push(TrackId(0));
+        self.implicit = true;
+        self.parents.clear();
+        self.next_id = 0;
+    }
+
+    /// Get a new track id.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the `next_id` overflows [`usize`].
+    ///
+    /// [`usize`]: https://doc.rust-lang.org/stable/std/primitive.usize.html
+    #[inline]
+    pub(crate) fn next_id(&mut self) -> TrackId {
+        self.try_next_id().unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    /// Returns the next unused track id, or an error if none remains.
+    #[inline]
+    pub(crate) fn try_next_id(&mut self) -> Result<TrackId, TrackLimitError> {
+        let id = self.next_id;
+        self.next_id = id.checked_add(1).ok_or(TrackLimitError)?;
+        Ok(TrackId(id))
+    }
+
+    /// Returns the column of `track_id`, if it is a current track.
+    #[inline]
+    pub(crate) fn column(&self, track_id: TrackId) -> Option<usize> {
+        if self.is_implicit(track_id) {
+            None
+        } else {
+            self.tracks.iter().position(|&id| id == track_id)
+        }
+    }
+
+    /// Returns `true` if `track_id` is a current track.
+    #[inline]
+    pub(crate) fn has_track(&self, track_id: TrackId) -> bool {
+        self.tracks.contains(&track_id) && !self.is_implicit(track_id)
+    }
+
+    /// Returns `true` if `track_id` is track `0`, and it only exists
+    /// implicitly, i.e. no [`Track`] refers to it yet.
+    ///
+    /// [`Track`]: struct.Track.html
+    #[inline]
+    fn is_implicit(&self, track_id: TrackId) -> bool {
+        self.implicit && (track_id == TrackId(0)) && self.tracks.contains(&track_id)
+    }
+
+    /// Marks track `0` as started, if `track_id` is the implicit track `0`.
+    #[inline]
+    fn start_implicit(&mut self, track_id: TrackId) {
+        if self.is_implicit(track_id) {
+            self.implicit = false;
+        }
+    }
+
+    /// Starts `track_id`, unless it already exists.
+    ///
+    /// Returns `true` if the track was started.
+    #[inline]
+    pub(crate) fn start_track(&mut self, track_id: TrackId) -> bool {
+        if !self.has_track(track_id) {
+            self.insert_track(track_id, None);
+            self.add_event(Event::StartTrack(track_id));
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Stops `track_id`, unless it has already been removed.
+    #[inline]
+    pub(crate) fn stop_track(&mut self, track_id: TrackId) {
+        if let Some(index) = self.column(track_id) {
+            self.tracks.remove(index);
+            self.add_event(Event::StopTrack(track_id));
+        }
+    }
+
+    /// Splits `new_track_id` from `from_track_id`, unless `new_track_id` already exists.
+    #[inline]
+    pub(crate) fn split_track(&mut self, from_track_id: TrackId, new_track_id: TrackId) {
+        if !self.has_track(new_track_id) {
+            let index = self.insert_index(from_track_id, from_track_id);
+            self.insert_split_track(from_track_id, new_track_id, index);
+            self.add_event(Event::SplitTrack(from_track_id, new_track_id));
+        }
+    }
+
+    /// Splits `new_track_id` from `from_track_id`, placing it to the right
+    /// of `neighbor_track_id`, unless `new_track_id` already exists.
+    #[inline]
+    pub(crate) fn split_track_at(
+        &mut self,
+        from_track_id: TrackId,
+        new_track_id: TrackId,
+        neighbor_track_id: TrackId,
+    ) {
+        if !self.has_track(new_track_id) {
+            let index = self.insert_index(from_track_id, neighbor_track_id);
+            self.insert_split_track(from_track_id, new_track_id, index);
+            self.add_event(Event::SplitTrackAt(
+                from_track_id,
+                new_track_id,
+                neighbor_track_id,
+            ));
+        }
+    }
+
+    /// Renames `old_track_id` to `new_track_id`, keeping its column.
+    ///
+    /// Returns `false` if `old_track_id` does not exist,
+    /// or if `new_track_id` already exists.
+    #[inline]
+    pub(crate) fn rename_track(&mut self, old_track_id: TrackId, new_track_id: TrackId) -> bool {
+        match self.column(old_track_id) {
+            Some(index) if !self.tracks.contains(&new_track_id) => {
+                self.tracks[index] = new_track_id;
+
+                if new_track_id == TrackId(0) {
+                    self.implicit = false;
+                }
+
+                // The renamed track is still split from the same track
+                match self.parents.remove(&old_track_id) {
+                    Some(parent) => self.parents.insert(new_track_id, parent),
+                    None => self.parents.remove(&new_track_id),
+                };
+
+                self.next_id = self.next_id.max(new_track_id.0.saturating_add(1));
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Splits `n` new tracks from `from_track_id`, and returns their ids.
+    pub(crate) fn split_tracks(&mut self, from_track_id: TrackId, n: usize) -> Vec<TrackId> {
+        let new_track_ids = (0..n).map(|_| self.next_id()).collect::<Vec<_>>();
+
+        if !new_track_ids.is_empty() {
+            let event = Event::SplitTrackN(from_track_id, Cow::Owned(new_track_ids.clone()));
+            self.replay(&event);
+            self.add_event(event);
+        }
+
+        new_track_ids
+    }
+
+    /// Splits a new track from `from_track_id`, with a station
+    /// on the new track, and returns the id of the new track.
+    pub(crate) fn split_station(&mut self, from_track_id: TrackId, text: Cow<'a, str>) -> TrackId {
+        let new_track_id = self.next_id();
+
+        let event = Event::SplitStation(from_track_id, new_track_id, text);
+        self.replay(&event);
+        self.add_event(event);
+
+        new_track_id
+    }
+
+    /// The caller must not produce `Event::StopTrack` for `from_track_id`.
+    #[inline]
+    pub(crate) fn join_track(&mut self, from_track_id: TrackId, to_track_id: TrackId) {
+        // Whether either track already stopped existing does not matter
+        // as `to_string` handles rendering and resolving "edge cases".
+        self.add_event(Event::JoinTrack(from_track_id, to_track_id));
+
+        // If `from_track` `is_dangling` then it has already been removed from `tracks`
+        if let Some(index) = self.tracks.iter().position(|&id| id == from_track_id) {
+            self.tracks.remove(index);
+        }
+    }
+
+    #[inline]
+    pub(crate) fn add_event(&mut self, event: Event<'a>) {
+        self.events.push(event);
+    }
+
+    /// Adds `events`, updating `tracks` and `next_id` for each event.
+    pub(crate) fn extend_events<I>(&mut self, events: I)
+    where
+        I: IntoIterator<Item = Event<'a>>,
+    {
+        for event in events {
+            self.replay(&event);
+            self.add_event(event);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{to_string, Event, Event::*, Metro, TrackLimitError};
+    use crate::{Options, TrackId};
+
+    #[test]
+    fn lib_example() {
         // If this example is changed, then update both `events`
         // and the output in lib.rs, events.rs, and metro.rs.
 
-        #[inline]
-        fn station(track_id: usize, text: &str) -> Event {
-            Event::station(track_id, text)
+        #[inline]
+        fn station(track_id: usize, text: &str) -> Event<'_> {
+            Event::station(track_id, text)
+        }
+
+        let events = [
+            station(0, "Station 1"),
+            station(0, "Station 2"),
+            station(0, "Station 3"),
+            SplitTrack(TrackId(0), TrackId(1)),
+            station(1, "Station 4"),
+            SplitTrack(TrackId(1), TrackId(2)),
+            station(1, "Station 5"),
+            station(2, "Station 6"),
+            station(0, "Station 7"),
+            station(1, "Station 8"),
+            station(2, "Station 9"),
+            SplitTrack(TrackId(2), TrackId(3)),
+            SplitTrack(TrackId(3), TrackId(4)),
+            station(5, "Station 10 (Detached)"),
+            JoinTrack(TrackId(4), TrackId(0)),
+            station(3, "Station 11"),
+            StopTrack(TrackId(1)),
+            station(0, "Station 12"),
+            station(2, "Station 13"),
+            station(3, "Station 14"),
+            JoinTrack(TrackId(3), TrackId(0)),
+            station(2, "Station 15"),
+            StopTrack(TrackId(2)),
+            station(0, "Station 16"),
+        ];
+        let string1 = to_string(&events).unwrap();
+
+        assert_eq!(
+            string1,
+            r#"* Station 1
+* Station 2
+* Station 3
+|\
+| * Station 4
+| |\
+| * | Station 5
+| | * Station 6
+* | | Station 7
+| * | Station 8
+| | * Station 9
+| | |\
+| | | |\
+| | | | | Station 10 (Detached)
+| |_|_|/
+|/| | |
+| | | * Station 11
+| " | |
+|  / /
+* | | Station 12
+| * | Station 13
+| | * Station 14
+| |/
+|/|
+| * Station 15
+| "
+* Station 16
+"#
+        );
+
+        let mut metro = Metro::new();
+
+        let mut track1 = metro.new_track();
+        track1.add_station("Station 1");
+        track1.add_station("Station 2");
+        track1.add_station("Station 3");
+
+        let mut track2 = track1.split();
+        track2.add_station("Station 4");
+
+        let mut track3 = track2.split();
+        track2.add_station("Station 5");
+        track3.add_station("Station 6");
+
+        track1.add_station("Station 7");
+        track2.add_station("Station 8");
+        track3.add_station("Station 9");
+
+        let mut track4 = track3.split();
+        let track5 = track4.split();
+
+        metro.add_station("Station 10 (Detached)");
+
+        track5.join(&track1);
+
+        track4.add_station("Station 11");
+
+        track2.stop();
+
+        track1.add_station("Station 12");
+        track3.add_station("Station 13");
+        track4.add_station("Station 14");
+
+        track4.join(&track1);
+
+        track3.add_station("Station 15");
+
+        track3.stop();
+
+        track1.add_station("Station 16");
+
+        let string2 = metro.to_string().unwrap();
+
+        assert_eq!(string1, string2);
+    }
+
+    #[test]
+    fn no_event() {
+        let events = [
+            Event::station(0, "Station 1"),
+            NoEvent,
+            SplitTrack(TrackId(0), TrackId(1)),
+            NoEvent,
+            Event::station(1, "Station 2"),
+            NoEvent,
+        ];
+        let string1 = to_string(&events).unwrap();
+
+        let mut metro = Metro::new();
+
+        let mut track1 = metro.new_track();
+        track1.add_station("Station 1");
+        metro.add_no_event();
+
+        let mut track2 = track1.split();
+        metro.add_no_event();
+        track2.add_station("Station 2");
+        metro.add_no_event();
+
+        let string2 = metro.to_string().unwrap();
+
+        assert_eq!(string1, string2);
+        assert_eq!(
+            string2,
+            r#"* Station 1
+|
+|\
+| |
+| * Station 2
+| |
+"#
+        );
+    }
+
+    #[test]
+    fn display() {
+        let mut metro = Metro::new();
+
+        let mut track1 = metro.new_track();
+        track1.add_station("Station 1");
+
+        let mut track2 = track1.split();
+        track2.add_station("Station 2\nWith multiple lines");
+
+        track2.join(&track1);
+        track1.add_station("Station 3");
+
+        assert_eq!(format!("{}", metro), metro.to_string().unwrap());
+    }
+
+    #[test]
+    fn legend() {
+        let mut metro = Metro::new();
+
+        let mut track1 = metro.new_track();
+        track1.set_label("main");
+
+        let mut track2 = track1.split();
+        track2.set_label("release");
+
+        let mut track3 = track1.split();
+        track3.set_label("feature/x");
+
+        metro.add_legend();
+
+        track3.add_station("Station 1");
+
+        assert_eq!(
+            metro.to_string().unwrap(),
+            r#"|\
+|\ \
+main
+| feature/x
+| | release
+| * | Station 1
+"#
+        );
+    }
+
+    #[test]
+    fn get_or_create_track() {
+        let mut metro = Metro::new();
+
+        let (track1, created) = metro.get_or_create_track(0);
+        assert!(created);
+
+        let (track2, created) = metro.get_or_create_track(0);
+        assert!(!created);
+
+        track1.stop();
+        assert!(track2.is_dangling());
+
+        let (_track3, created) = metro.get_or_create_track(0);
+        assert!(created);
+        assert!(!track2.is_dangling());
+
+        assert_eq!(
+            metro.to_events(),
+            [
+                StartTrack(TrackId(0)),
+                StopTrack(TrackId(0)),
+                StartTrack(TrackId(0))
+            ]
+        );
+    }
+
+    #[test]
+    fn split_at() {
+        let mut metro = Metro::new();
+
+        let track1 = metro.new_track();
+        let track2 = metro.new_track();
+        let mut track3 = metro.new_track();
+        let _track4 = metro.new_track();
+
+        let mut track5 = track1.split_at(&track3);
+        track5.add_station("Station 1");
+        track3.add_station("Station 2");
+
+        let track6 = track2.split();
+        drop(track2);
+
+        let track7 = track1.split_at(&track6);
+        drop(track7);
+
+        let string = metro.to_string().unwrap();
+
+        assert_eq!(
+            string,
+            r#"| |
+| | |
+| | | |
+|\|_|_ \
+| | | * | Station 1
+| | * | | Station 2
+| |\ \ \ \
+| " | | | |
+|  / / / /
+|\|_ \ \ \
+| | " | | |
+| |  / / /
+"#
+        );
+    }
+
+    #[test]
+    fn join_tracks() {
+        let mut metro = Metro::new();
+
+        let track1 = metro.new_track();
+        let track2 = metro.new_track();
+        let track3 = metro.new_track();
+
+        metro.join_tracks(track3.id(), track1.id());
+        assert!(track3.is_dangling());
+        assert_eq!(metro.track_ids(), [0, 1]);
+
+        // Dropping the dangling `Track` does not stop any track
+        drop(track3);
+        assert_eq!(metro.track_ids(), [0, 1]);
+
+        // Joining a track that does not exist only adds the event
+        metro.join_tracks(5, track2.id());
+        assert_eq!(metro.track_ids(), [0, 1]);
+
+        assert_eq!(
+            metro.to_events()[3..],
+            [
+                JoinTrack(TrackId(2), TrackId(0)),
+                JoinTrack(TrackId(5), TrackId(1)),
+            ]
+        );
+    }
+
+    #[test]
+    fn track_ids() {
+        let mut metro = Metro::new();
+
+        let track1 = metro.new_track();
+        let track2 = metro.new_track();
+        let track3 = metro.new_track();
+
+        let track4 = track1.split_at(&track2);
+        let track5 = track3.split();
+        let _track6 = track1.split();
+
+        assert_eq!(metro.track_ids(), [0, 5, 1, 3, 2, 4]);
+
+        track4.join(&track1);
+        drop(track5);
+
+        assert_eq!(metro.track_ids(), [0, 5, 1, 2]);
+
+        let events = metro.to_events();
+        let metro = Metro::from_events(events);
+
+        assert_eq!(metro.track_ids(), [0, 5, 1, 2]);
+
+        drop((track2, track3));
+    }
+
+    #[test]
+    fn track_ids_implicit_track() {
+        let mut metro = Metro::new();
+
+        assert_eq!(metro.track_ids(), [0]);
+
+        let mut track1 = metro.new_track_with_id(5);
+        track1.add_station("Station 1");
+
+        assert_eq!(metro.to_string().unwrap(), "| |\n| * Station 1\n");
+        assert_eq!(metro.track_ids(), [0, 5]);
+
+        // Starting the implicit track keeps it in its column
+        let track2 = metro.new_track();
+
+        assert_eq!(track2.id(), 0);
+        assert_eq!(metro.track_ids(), [0, 5]);
+
+        drop(track2);
+
+        assert_eq!(metro.track_ids(), [5]);
+    }
+
+    #[test]
+    fn active_track_count() {
+        let mut metro = Metro::new();
+
+        assert_eq!(metro.active_track_count(), 1);
+
+        let track1 = metro.new_track_with_id(5);
+        let track2 = metro.new_track_with_id(6);
+
+        assert_eq!(metro.active_track_count(), 3);
+
+        drop((track1, track2));
+
+        assert_eq!(metro.active_track_count(), 1);
+    }
+
+    #[test]
+    fn owned_station_text() {
+        let mut metro = Metro::new();
+
+        let mut track1 = metro.new_track();
+
+        for i in 1..=3 {
+            // The `String` is dropped before the `Metro`
+            let text = format!("Station {}", i);
+            track1.add_station(text);
         }
 
-        // TODO: Currently not including `NoEvent` as `Metro`
-        // TODO: does not have an equivalent
-        let events = [
-            station(0, "Station 1"),
-            station(0, "Station 2"),
-            // NoEvent,
-            station(0, "Station 3"),
-            SplitTrack(0, 1),
-            station(1, "Station 4"),
-            SplitTrack(1, 2),
-            station(1, "Station 5"),
-            station(2, "Station 6"),
-            // NoEvent,
-            station(0, "Station 7"),
-            station(1, "Station 8"),
-            station(2, "Station 9"),
-            SplitTrack(2, 3),
-            SplitTrack(3, 4),
-            station(5, "Station 10 (Detached)"),
-            JoinTrack(4, 0),
-            station(3, "Station 11"),
-            StopTrack(1),
-            // NoEvent,
-            station(0, "Station 12"),
-            station(2, "Station 13"),
-            station(3, "Station 14"),
-            JoinTrack(3, 0),
-            station(2, "Station 15"),
-            StopTrack(2),
-            station(0, "Station 16"),
-        ];
-        let string1 = to_string(&events).unwrap();
+        let string = metro.to_string().unwrap();
 
         assert_eq!(
-            string1,
+            string,
             r#"* Station 1
 * Station 2
 * Station 3
-|\
-| * Station 4
-| |\
-| * | Station 5
-| | * Station 6
-* | | Station 7
-| * | Station 8
-| | * Station 9
-| | |\
-| | | |\
-| | | | | Station 10 (Detached)
-| |_|_|/
-|/| | |
-| | | * Station 11
-| " | |
-|  / /
-* | | Station 12
-| * | Station 13
-| | * Station 14
-| |/
-|/|
-| * Station 15
-| "
-* Station 16
 "#
         );
+    }
+
+    #[test]
+    fn detached_station_with_max_track_id() {
+        let mut metro = Metro::new();
+
+        let mut track1 = metro.new_track_with_id(usize::MAX);
+        track1.add_station("Station 1");
+
+        metro.add_station("Station 2");
+
+        assert_eq!(
+            metro.to_string().unwrap(),
+            "| |\n| * Station 1\n| | Station 2\n"
+        );
+    }
+
+    #[test]
+    fn detached_station_event() {
+        let mut metro = Metro::new();
+
+        metro.add_station("Station 1");
+
+        assert_eq!(metro.to_events(), [Event::detached_station("Station 1")]);
+    }
 
+    #[test]
+    fn detached_gutter() {
         let mut metro = Metro::new();
 
         let mut track1 = metro.new_track();
         track1.add_station("Station 1");
-        track1.add_station("Station 2");
-        track1.add_station("Station 3");
+
+        metro.add_station("Note");
 
         let mut track2 = track1.split();
-        track2.add_station("Station 4");
+        track2.add_station("Station 2");
+
+        let opts = Options {
+            detached_gutter: true,
+            ..Options::default()
+        };
+
+        assert_eq!(
+            metro.to_string_with(&opts).unwrap(),
+            "     * Station 1\nNote |\n     |\\\n     | * Station 2\n"
+        );
+    }
+
+    #[test]
+    fn align_detached_text() {
+        let mut metro = Metro::new();
+
+        let mut track1 = metro.new_track();
+        track1.add_station("Station 1");
 
+        metro.add_station("Note 1");
+
+        let track2 = track1.split();
         let mut track3 = track2.split();
-        track2.add_station("Station 5");
-        track3.add_station("Station 6");
 
-        track1.add_station("Station 7");
-        track2.add_station("Station 8");
-        track3.add_station("Station 9");
+        metro.add_station("Note 2");
+        track3.add_station("Station 2");
 
-        let mut track4 = track3.split();
-        let track5 = track4.split();
+        let opts = Options {
+            align_detached_text: true,
+            ..Options::default()
+        };
 
-        metro.add_station("Station 10 (Detached)");
+        assert_eq!(
+            metro.to_string_with(&opts).unwrap(),
+            "* Station 1\n|     Note 1\n|\\\n| |\\\n| | | Note 2\n| | * Station 2\n"
+        );
+    }
 
-        track5.join(&track1);
+    #[test]
+    fn from_events_join_track_keep_source() {
+        let metro = Metro::from_events(vec![
+            StartTracks(&[TrackId(0), TrackId(1), TrackId(2)]),
+            JoinTrackKeepSource(TrackId(2), TrackId(0)),
+        ]);
 
-        track4.add_station("Station 11");
+        assert_eq!(metro.track_ids(), [2, 1]);
+    }
+
+    #[test]
+    fn from_events_split_track_n() {
+        let mut metro = Metro::from_events(vec![
+            StartTracks(&[TrackId(0), TrackId(1)]),
+            Event::split_track_n(0, vec![TrackId(3), TrackId(1), TrackId(2)]),
+        ]);
+
+        assert_eq!(metro.track_ids(), [0, 3, 2, 1]);
+        assert_eq!(metro.new_track().id(), 4);
+    }
+
+    #[test]
+    fn try_new_track() {
+        let mut metro = Metro::from_events(vec![StartTrack(TrackId(usize::MAX - 1))]);
+
+        let track1 = metro.get_track(usize::MAX - 1).unwrap();
+
+        assert_eq!(metro.try_new_track().unwrap_err(), TrackLimitError);
+        assert_eq!(track1.try_split().unwrap_err(), TrackLimitError);
+
+        // Failing does not add any events
+        assert_eq!(metro.event_count(), 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "tracks have been created")]
+    fn new_track_limit() {
+        let mut metro = Metro::from_events(vec![StartTrack(TrackId(usize::MAX - 1))]);
+
+        let _track = metro.new_track();
+    }
+
+    #[test]
+    fn split_n() {
+        let mut metro = Metro::new();
+
+        let track1 = metro.new_track();
+        let _track2 = metro.new_track();
+
+        let event_count = metro.event_count();
+        assert!(track1.split_n(0).is_empty());
+        assert_eq!(metro.event_count(), event_count);
+
+        let tracks = track1.split_n(2);
+        let ids = tracks.iter().map(|track| track.id()).collect::<Vec<_>>();
+
+        assert_eq!(ids, [2, 3]);
+        assert_eq!(metro.track_ids(), [0, 2, 3, 1]);
+    }
+
+    #[test]
+    fn render_to_file() {
+        let mut metro = Metro::new();
+
+        let mut track1 = metro.new_track();
+        track1.add_station("Station 1");
+        let mut track2 = track1.split();
+        track2.add_station("Station 2");
+
+        let path = std::env::temp_dir().join(format!("metro-{}.txt", std::process::id()));
+        metro.render_to_file(&path).unwrap();
+
+        let contents = std::fs::read_to_string(&path);
+        let _ = std::fs::remove_file(&path);
+
+        assert_eq!(contents.unwrap(), metro.to_string().unwrap());
+    }
+
+    #[test]
+    fn parent_id() {
+        let mut metro = Metro::from_events(vec![
+            StartTrack(TrackId(0)),
+            SplitTrack(TrackId(0), TrackId(1)),
+            SplitTrackAt(TrackId(5), TrackId(2), TrackId(0)),
+            Event::split_track_n(1, vec![TrackId(3), TrackId(4)]),
+            JoinTrackOrRename(TrackId(3), TrackId(6)),
+        ]);
+
+        let parent_id = |metro: &Metro, track_id| metro.state.borrow().parent(TrackId(track_id));
+
+        assert_eq!(parent_id(&metro, 0), None);
+        assert_eq!(parent_id(&metro, 1), Some(TrackId(0)));
+        assert_eq!(parent_id(&metro, 2), None);
+        assert_eq!(parent_id(&metro, 4), Some(TrackId(1)));
+        assert_eq!(parent_id(&metro, 6), Some(TrackId(1)));
+
+        let track6 = metro.get_track(6).unwrap();
+        let track7 = track6.split_station("Station 1");
+        assert_eq!(track7.parent_id(), Some(TrackId(6)));
+
+        let checkpoint = metro.checkpoint();
+        track7.stop();
+        assert_eq!(metro.new_track_with_id(7).parent_id(), None);
+
+        metro.rewind(checkpoint);
+        assert_eq!(parent_id(&metro, 7), Some(TrackId(6)));
+    }
+
+    #[test]
+    fn into_iter() {
+        let mut metro = Metro::new();
+
+        let mut track1 = metro.new_track();
+        track1.add_station("Station 1");
+        let mut track2 = track1.split();
+        track2.add_station("Station 2");
+
+        drop(track2);
+        drop(track1);
+
+        let events = metro.to_events();
+
+        assert_eq!((&metro).into_iter().collect::<Vec<_>>(), events);
+        assert_eq!(metro.event_count(), events.len());
+
+        let mut count = 0;
+        for event in metro {
+            assert_eq!(event, events[count]);
+            count += 1;
+        }
+        assert_eq!(count, events.len());
+    }
+
+    #[test]
+    fn split_station() {
+        let mut metro = Metro::new();
+
+        let track1 = metro.new_track();
+        let _track2 = metro.new_track();
+
+        let track3 = track1.split_station("Station 1");
+        assert_eq!(track3.id(), 2);
+        assert_eq!(metro.track_ids(), [0, 2, 1]);
+
+        let string = metro.to_string().unwrap();
+        assert_eq!(string, "| |\n|\\ \\\n| * | Station 1\n");
+    }
+
+    #[test]
+    fn from_events_join_track_or_rename() {
+        let mut metro = Metro::from_events(vec![
+            StartTracks(&[TrackId(0), TrackId(1), TrackId(2)]),
+            JoinTrackOrRename(TrackId(1), TrackId(5)),
+            JoinTrackOrRename(TrackId(2), TrackId(0)),
+        ]);
+
+        assert_eq!(metro.track_ids(), [0, 5]);
+        assert_eq!(metro.new_track().id(), 6);
+    }
+
+    #[test]
+    fn clear() {
+        let mut metro = Metro::new();
+
+        let mut track1 = metro.new_track();
+        let track2 = track1.split();
+        track1.add_station("Station 1");
+
+        let capacity = metro.state.borrow().events.capacity();
+
+        metro.clear();
+
+        assert!(track1.is_dangling());
+        assert!(track2.is_dangling());
+        assert_eq!(metro.event_count(), 0);
+        assert_eq!(metro.track_ids(), [0]);
+        assert_eq!(metro.state.borrow().events.capacity(), capacity);
+
+        // Dropping dangling tracks does not add any events
+        drop((track1, track2));
+        assert_eq!(metro.event_count(), 0);
+
+        let track1 = metro.new_track();
+        assert_eq!(track1.id(), 0);
+    }
+
+    #[test]
+    fn column() {
+        let mut metro = Metro::new();
+
+        let mut track1 = metro.new_track_with_id(5);
+        track1.add_station("Station 1");
+
+        // Track 0 exists implicitly, so track 5 is rendered in column 1
+        assert_eq!(metro.to_string().unwrap(), "| |\n| * Station 1\n");
+        assert_eq!(track1.column(), Some(1));
+
+        let mut track2 = metro.new_track_with_id(0);
+        track2.add_station("Station 2");
+
+        assert_eq!(
+            metro.to_string().unwrap(),
+            "| |\n| * Station 1\n* | Station 2\n"
+        );
+        assert_eq!(track2.column(), Some(0));
+        assert_eq!(track1.column(), Some(1));
 
         track2.stop();
+        track1.add_station("Station 3");
 
-        track1.add_station("Station 12");
-        track3.add_station("Station 13");
-        track4.add_station("Station 14");
+        assert_eq!(
+            metro.to_string().unwrap(),
+            "| |\n| * Station 1\n* | Station 2\n\" |\n /\n* Station 3\n"
+        );
+        assert_eq!(track1.column(), Some(0));
+    }
 
-        track4.join(&track1);
+    #[test]
+    fn rename_id() {
+        let mut metro = Metro::new();
 
-        track3.add_station("Station 15");
+        let mut track1 = metro.new_track();
+        let mut track2 = track1.split();
+        let track3 = metro.get_track(track2.id()).unwrap();
 
-        track3.stop();
+        assert!(track2.rename_id(7));
+        assert!(track2.rename_id(7));
+        assert!(track3.is_dangling());
+        assert!(!track1.rename_id(7));
+        drop(track3);
 
-        track1.add_station("Station 16");
+        track2.add_station("Station 1");
+        track1.add_station("Station 2");
+
+        // New track ids continue after the renamed track id
+        let track4 = track2.split();
+        assert_eq!(track4.id(), 8);
+
+        // Track ids are reusable after being renamed
+        let track5 = metro.new_track_with_id(1);
+        assert_eq!(metro.track_ids(), [0, 7, 8, 1]);
+
+        drop((track4, track5));
+        track2.join(&track1);
+
+        assert_eq!(
+            Metro::from_events(metro.to_events()).track_ids(),
+            metro.track_ids()
+        );
+        assert_eq!(
+            metro.to_string().unwrap(),
+            r#"|\
+| * Station 1
+* | Station 2
+| |\
+| | | |
+| | " |
+| |  /
+| | "
+|/
+"#
+        );
+    }
+
+    #[test]
+    fn extend_events() {
+        let mut metro = Metro::new();
+
+        let mut track1 = metro.new_track();
+        track1.add_station("Station 1");
+
+        metro.extend(vec![
+            SplitTrack(TrackId(0), TrackId(5)),
+            Event::station(5, "Station 2"),
+            StopTrack(TrackId(0)),
+        ]);
+
+        assert!(track1.is_dangling());
+        assert_eq!(metro.track_ids(), [5]);
+        assert_eq!(metro.event_count(), 5);
+
+        // New track ids continue after the highest track id
+        let track5 = metro.get_track(5).unwrap();
+        let mut track2 = track5.split();
+        assert_eq!(track2.id(), 6);
+        track2.add_station("Station 3");
+
+        assert_eq!(
+            metro.to_string().unwrap(),
+            "* Station 1\n|\\\n| * Station 2\n\" |\n /\n|\\\n| * Station 3\n"
+        );
+    }
+
+    #[test]
+    fn from_events() {
+        let mut metro = Metro::new();
+
+        let mut track1 = metro.new_track();
+        track1.add_station("Station 1");
+
+        let mut track2 = track1.split();
+        track2.add_station("Station 2");
+
+        let track3 = track2.split();
+        track3.join(&track1);
+
+        let mut track4 = metro.new_track_with_id(10);
+        track4.add_station("Station 3");
+
+        let mut track5 = track2.split();
+        track5.add_station("Station 4");
+
+        drop(track2);
+        track1.add_station("Station 5");
+
+        let string1 = metro.to_string().unwrap();
+
+        let events = metro.into_events();
+
+        // Only affects the previous state, as its events were moved out
+        drop((track1, track4, track5));
+
+        let mut metro = Metro::from_events(events);
+
+        assert!(metro.get_track(1).is_none());
+        assert!(metro.get_track(2).is_none());
+
+        let mut track1 = metro.get_track(0).unwrap();
+        let mut track4 = metro.get_track(10).unwrap();
+        let mut track5 = metro.get_track(3).unwrap();
+
+        track4.add_station("Station 3");
+        track5.add_station("Station 4");
+        track1.add_station("Station 5");
+
+        // New track ids continue after the highest track id
+        let track6 = track1.split();
+        assert_eq!(track6.id(), 11);
 
         let string2 = metro.to_string().unwrap();
 
-        assert_eq!(string1, string2);
+        assert!(string2.starts_with(&string1));
+    }
+
+    #[test]
+    fn rewind() {
+        let mut metro = Metro::new();
+
+        let mut track1 = metro.new_track();
+        let track2 = track1.split();
+        track1.add_station("Station 1");
+
+        let string1 = metro.to_string().unwrap();
+        let checkpoint = metro.checkpoint();
+
+        let mut track3 = track1.split();
+        track3.add_station("Discarded");
+        track2.join(&track1);
+        metro.add_station("Discarded");
+
+        metro.rewind(checkpoint.clone());
+
+        assert!(track3.is_dangling());
+        assert_eq!(metro.track_ids(), [0, 1]);
+        assert_eq!(metro.to_string().unwrap(), string1);
+
+        // Dropping a dangling track adds no events
+        drop(track3);
+        assert_eq!(metro.to_string().unwrap(), string1);
+
+        // Track ids are reused after rewinding
+        let mut track2 = metro.get_track(1).unwrap();
+        let mut track3 = track1.split();
+        assert_eq!(track3.id(), 2);
+
+        track2.add_station("Station 2");
+        track3.add_station("Station 3");
+
+        assert_eq!(
+            metro.to_string().unwrap(),
+            r#"|\
+* | Station 1
+|\ \
+| | * Station 2
+| * | Station 3
+"#
+        );
+
+        metro.rewind(checkpoint);
+        assert_eq!(metro.to_string().unwrap(), string1);
     }
 }
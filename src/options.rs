@@ -0,0 +1,600 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::track_id::TrackId;
+
+/// Options used by [`to_writer_with`], [`to_vec_with`] and [`to_string_with`].
+///
+/// [`to_writer_with`]: fn.to_writer_with.html
+/// [`to_vec_with`]: fn.to_vec_with.html
+/// [`to_string_with`]: fn.to_string_with.html
+///
+/// # Example
+///
+/// ```
+/// use metro::{Direction, Options};
+///
+/// let opts = Options {
+///     direction: Direction::RightToLeft,
+///     ..Options::default()
+/// };
+/// ```
+#[derive(Clone, Debug)]
+pub struct Options {
+    /// The horizontal direction the graph is rendered in.
+    ///
+    /// Default: [`Direction::LeftToRight`]
+    ///
+    /// [`Direction::LeftToRight`]: enum.Direction.html#variant.LeftToRight
+    pub direction: Direction,
+
+    /// The number of characters between two rails.
+    ///
+    /// Diagonals between rails are extended with `_`, such that
+    /// they span the whole gap. The station text is separated
+    /// from the rails by the same amount of spaces.
+    ///
+    /// A `rail_spacing` of `0` is treated as `1`.
+    ///
+    /// Default: `1`
+    ///
+    /// ## Output Example
+    ///
+    /// Given 3 tracks `0, 1, 2` and a `rail_spacing` of `3`,
+    /// then `JoinTrack(TrackId(1), TrackId(0))` would render as:
+    ///
+    /// ```text
+    /// |   |   |
+    /// |__/ __/
+    /// |   |
+    /// ```
+    pub rail_spacing: usize,
+
+    /// Whether the last row ends with a newline.
+    ///
+    /// If `false`, then only the newline of the very last row is
+    /// omitted, e.g. for embedding the graph inline. All other
+    /// rows still end with a newline.
+    ///
+    /// Default: `true`
+    pub trailing_newline: bool,
+
+    /// The number of spaces between the rails and the text,
+    /// e.g. the text of a station.
+    ///
+    /// If `None`, then the text is separated from the rails
+    /// by [`rail_spacing`].
+    ///
+    /// Default: `None`
+    ///
+    /// [`rail_spacing`]: struct.Options.html#structfield.rail_spacing
+    ///
+    /// ## Output Example
+    ///
+    /// Given 3 tracks `0, 1, 2` and a `text_gap` of `3`,
+    /// then `Station(TrackId(1), "Hello World")` would render as:
+    ///
+    /// ```text
+    /// | | |
+    /// | * |   Hello World
+    /// | | |
+    /// ```
+    pub text_gap: Option<usize>,
+
+    /// Whether the text of all rows is aligned to the same column,
+    /// regardless of how many rails precede it.
+    ///
+    /// The column is the display width of the widest rails followed
+    /// by text, plus the [`text_gap`]. Labels in a legend are not
+    /// aligned, as their position marks their track.
+    ///
+    /// Note that the whole graph must be rendered before the first
+    /// row can be written, to know the width of the widest rails.
+    ///
+    /// Default: `false`
+    ///
+    /// [`text_gap`]: struct.Options.html#structfield.text_gap
+    ///
+    /// ## Output Example
+    ///
+    /// ```text
+    /// *     Station 1
+    /// |\
+    /// | *   Station 2
+    /// | |\
+    /// | | * Station 3
+    /// * | | Station 4
+    /// ```
+    pub align_text: bool,
+
+    /// Whether a split of the rightmost track, immediately followed
+    /// by a station on the new track, is rendered as a single row.
+    ///
+    /// Only splits of the rightmost track are compacted, as then no
+    /// other rails are pushed to the right, and the station is placed
+    /// right where the diagonal ends. Note that this changes the
+    /// number of rendered rows.
+    ///
+    /// Default: `false`
+    ///
+    /// ## Output Example
+    ///
+    /// ```text
+    /// * Station 1
+    /// |\* Station 2
+    /// | |\* Station 3
+    /// * | | Station 4
+    /// ```
+    pub compact_splits: bool,
+
+    /// Whether a header with the id of each track is rendered above
+    /// the rails, at the start and whenever the tracks change.
+    ///
+    /// Each id is placed above its rail. An id wider than the
+    /// [`rail_spacing`] does not fit before the next rail, so the
+    /// following ids are pushed to the right, and no longer align
+    /// with their rails. Thus to keep ids of `n` digits aligned,
+    /// use a `rail_spacing` of at least `n`.
+    ///
+    /// Default: `false`
+    ///
+    /// [`rail_spacing`]: struct.Options.html#structfield.rail_spacing
+    ///
+    /// ## Output Example
+    ///
+    /// ```text
+    /// 0
+    /// * Station 1
+    /// |\
+    /// 0 1
+    /// | * Station 2
+    /// ```
+    pub show_track_ids: bool,
+
+    /// The maximum display width of rows with text, including the rails.
+    ///
+    /// Any line of text exceeding the width is truncated, ending with `…`.
+    /// The rails are never truncated, so rows where the rails alone
+    /// exceed the width, still exceed it.
+    ///
+    /// Default: `None`
+    ///
+    /// ## Output Example
+    ///
+    /// Given a `max_text_width` of `12`:
+    ///
+    /// ```text
+    /// * Station 1
+    /// |\
+    /// | * Station…
+    /// ```
+    pub max_text_width: Option<usize>,
+
+    /// Whether the text of detached stations, i.e. stations whose
+    /// track does not exist, such as those added with [`Metro::add_station`],
+    /// is placed in a gutter before the rails, instead of after the rails
+    /// alongside the text of other stations.
+    ///
+    /// The gutter is as wide as the widest text of a detached station,
+    /// plus the [`text_gap`]. If there are no detached stations, then
+    /// no gutter is rendered.
+    ///
+    /// Note that the whole graph must be rendered before the first
+    /// row can be written, to know the width of the gutter.
+    ///
+    /// Default: `false`
+    ///
+    /// [`Metro::add_station`]: struct.Metro.html#method.add_station
+    /// [`text_gap`]: struct.Options.html#structfield.text_gap
+    ///
+    /// ## Output Example
+    ///
+    /// ```text
+    ///        * Station 1
+    /// Note   | |
+    ///        |\
+    ///        | * Station 2
+    /// ```
+    pub detached_gutter: bool,
+
+    /// Whether the text of detached stations, i.e. stations whose
+    /// track does not exist, such as those added with [`Metro::add_station`],
+    /// is aligned to the same column, such that it reads as a margin of
+    /// notes beside the graph.
+    ///
+    /// The column is the display width of the widest rails of all rows,
+    /// plus the [`text_gap`], regardless of how many rails precede the
+    /// text of each detached station. The text of other rows is unaffected.
+    /// Ignored when using [`detached_gutter`].
+    ///
+    /// Note that the whole graph must be rendered before the first
+    /// row can be written, to know the width of the widest rails.
+    ///
+    /// Default: `false`
+    ///
+    /// [`Metro::add_station`]: struct.Metro.html#method.add_station
+    /// [`text_gap`]: struct.Options.html#structfield.text_gap
+    /// [`detached_gutter`]: struct.Options.html#structfield.detached_gutter
+    ///
+    /// ## Output Example
+    ///
+    /// ```text
+    /// * Station 1
+    /// |     Note 1
+    /// |\
+    /// | |\
+    /// | | | Note 2
+    /// | | * Station 2
+    /// ```
+    pub align_detached_text: bool,
+
+    /// The tracks that exist before the first event, from left to right.
+    ///
+    /// By default the track `0` exists before any events, such that
+    /// events can target it without starting it first. Thereby
+    /// `StartTrack(TrackId(0))` does nothing, as the track already exists.
+    /// Use an empty `Vec` to start without any tracks, e.g. when the
+    /// first track has a nonzero id.
+    ///
+    /// Default: `vec![TrackId(0)]`
+    ///
+    /// ## Output Example
+    ///
+    /// Given no `initial_tracks`, then `[StartTrack(TrackId(1)), Station(TrackId(1), "Station 1")]`
+    /// would render as:
+    ///
+    /// ```text
+    /// |
+    /// * Station 1
+    /// ```
+    pub initial_tracks: Vec<TrackId>,
+
+    /// The marker used instead of `*` for the stations of a track,
+    /// e.g. to distinguish kinds of tracks.
+    ///
+    /// The marker of [`StationMarked`] takes precedence. A marker
+    /// must be a single column wide, such that the rails remain
+    /// aligned, otherwise `*` is used. With the `unicode-width`
+    /// feature, the display width of the marker is used, such that
+    /// e.g. `'◆'` is allowed, while `'🚉'` is not.
+    ///
+    /// Default: empty
+    ///
+    /// [`StationMarked`]: enum.Event.html#variant.StationMarked
+    ///
+    /// ## Output Example
+    ///
+    /// Given `station_markers` mapping track `1` to `'◆'`:
+    ///
+    /// ```text
+    /// * Station 1
+    /// |\
+    /// | ◆ Station 2
+    /// * | Station 3
+    /// ```
+    pub station_markers: HashMap<TrackId, char>,
+
+    /// The line ending written after each line.
+    ///
+    /// This applies to every line, including lines of text spanning
+    /// multiple lines, e.g. the lines of a station with multiple lines.
+    ///
+    /// Default: [`LineEnding::Lf`]
+    ///
+    /// [`LineEnding::Lf`]: enum.LineEnding.html#variant.Lf
+    pub line_ending: LineEnding,
+
+    /// If `Some`, then a row of the marker is written under each rail
+    /// of the tracks remaining after the last event, e.g. to indicate
+    /// that only the first events of a larger graph were rendered.
+    ///
+    /// A marker wider than a single column is replaced by `:`,
+    /// as the rails would otherwise no longer align.
+    ///
+    /// Default: `None`
+    ///
+    /// ## Output Example
+    ///
+    /// Given a `continuation_marker` of `'⋮'`:
+    ///
+    /// ```text
+    /// * Station 1
+    /// |\
+    /// | * Station 2
+    /// ⋮ ⋮
+    /// ```
+    pub continuation_marker: Option<char>,
+
+    /// If `true`, then the rails are kept sorted by track id, instead of
+    /// placing new tracks rightmost or to the right of the track they are
+    /// split from, e.g. such that the columns remain the same regardless of
+    /// the order the tracks are started in.
+    ///
+    /// This changes the geometry of starting and splitting tracks, as new
+    /// rails move into their sorted position using `\` and `/`. The
+    /// `column_index` of `StartTrackAt` and the `neighbor_track_id` of
+    /// `SplitTrackAt` are ignored, while `SplitTrackN` only fans out within
+    /// a single row, if all the new tracks are sorted right after the track
+    /// they are split from. The [`initial_tracks`] are sorted as well.
+    ///
+    /// Renaming a track keeps its rail, so after `RenameTrack` or
+    /// `JoinTrackOrRename` the rails are not necessarily sorted.
+    ///
+    /// Default: `false`
+    ///
+    /// [`initial_tracks`]: struct.Options.html#structfield.initial_tracks
+    ///
+    /// ## Output Example
+    ///
+    /// Given 3 tracks `0, 1, 3` then `SplitTrack(TrackId(0), TrackId(2))` would render as:
+    ///
+    /// ```text
+    /// | | |
+    /// |\|_ \
+    /// | | | |
+    /// ```
+    pub sort_tracks: bool,
+
+    /// If `true`, then the text of each station is followed by the age of
+    /// its track, i.e. the number of rows since the track was started,
+    /// e.g. by `StartTrack` or `SplitTrack`. The tracks of [`initial_tracks`]
+    /// are started before the first row.
+    ///
+    /// For a station on multiple tracks, the age of the oldest track is used.
+    /// Stations which are not tied to any track have no age. The rows are
+    /// counted before [`compact_splits`] is applied, and track id headers
+    /// are not counted.
+    ///
+    /// Default: `false`
+    ///
+    /// [`initial_tracks`]: struct.Options.html#structfield.initial_tracks
+    /// [`compact_splits`]: struct.Options.html#structfield.compact_splits
+    ///
+    /// ## Output Example
+    ///
+    /// ```text
+    /// * Station 1 (age 0)
+    /// |\
+    /// | * Station 2 (age 1)
+    /// * | Station 3 (age 3)
+    /// ```
+    pub show_track_age: bool,
+
+    /// If `true`, then a row of rails of the [`initial_tracks`] is rendered
+    /// before the first event, e.g. such that rendering no events results
+    /// in the initial tracks, instead of an empty string.
+    ///
+    /// If there are no initial tracks, then no row is rendered.
+    ///
+    /// Default: `false`
+    ///
+    /// [`initial_tracks`]: struct.Options.html#structfield.initial_tracks
+    ///
+    /// ## Output Example
+    ///
+    /// Given 2 initial tracks `0, 1` then `Station(TrackId(1), "Station 1")`
+    /// would render as:
+    ///
+    /// ```text
+    /// | |
+    /// | * Station 1
+    /// ```
+    pub render_initial_tracks: bool,
+
+    /// If `Some`, then the rail of the remaining track of a join is rendered
+    /// as the marker, where the joined rail meets it, e.g. to mark the merge
+    /// of a branch. This applies to `JoinTrack`, `JoinTrackToward`,
+    /// `JoinTrackKeepSource` and `JoinTrackOrRename`, when both tracks exist.
+    ///
+    /// A marker wider than a single column is ignored,
+    /// as the rails would otherwise no longer align.
+    ///
+    /// Default: `None`
+    ///
+    /// ## Output Example
+    ///
+    /// Given 4 tracks `0, 1, 2, 3` and a `merge_marker` of `'M'`,
+    /// then `JoinTrack(TrackId(3), TrackId(0))` would render as:
+    ///
+    /// ```text
+    /// | | | |
+    /// | |_|/
+    /// M/| |
+    /// | | |
+    /// ```
+    pub merge_marker: Option<char>,
+
+    /// The tracks rendered as ghost tracks, i.e. with rails of `:` instead
+    /// of `|`, e.g. to indicate a reserved column, that is not yet in use.
+    ///
+    /// A track remains a ghost track until its first station, after which
+    /// its rails are rendered as usual. A ghost track that is stopped is no
+    /// longer a ghost track, even if its track id is reused. Rows where the
+    /// rails move, e.g. when splitting or joining, are rendered as usual.
+    ///
+    /// Default: empty
+    ///
+    /// ## Output Example
+    ///
+    /// Given `ghost_tracks` containing track `1`:
+    ///
+    /// ```text
+    /// * Station 1
+    /// |\
+    /// | :
+    /// * : Station 2
+    /// | * Station 3
+    /// * | Station 4
+    /// ```
+    pub ghost_tracks: HashSet<TrackId>,
+
+    /// How a station on a track that does not exist is handled, i.e.
+    /// a `Station`, `StationMarked` or `StationMulti` with a `track_id`
+    /// that does not exist. An [`Event::DetachedStation`] is always
+    /// rendered as not tied to any track.
+    ///
+    /// Default: [`UnknownTrackPolicy::Ignore`]
+    ///
+    /// [`Event::DetachedStation`]: enum.Event.html#variant.DetachedStation
+    /// [`UnknownTrackPolicy::Ignore`]: enum.UnknownTrackPolicy.html#variant.Ignore
+    pub unknown_track_policy: UnknownTrackPolicy,
+}
+
+impl Default for Options {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            direction: Direction::LeftToRight,
+            rail_spacing: 1,
+            trailing_newline: true,
+            text_gap: None,
+            align_text: false,
+            compact_splits: false,
+            show_track_ids: false,
+            max_text_width: None,
+            detached_gutter: false,
+            align_detached_text: false,
+            initial_tracks: vec![TrackId(0)],
+            station_markers: HashMap::new(),
+            line_ending: LineEnding::Lf,
+            continuation_marker: None,
+            sort_tracks: false,
+            show_track_age: false,
+            render_initial_tracks: false,
+            merge_marker: None,
+            ghost_tracks: HashSet::new(),
+            unknown_track_policy: UnknownTrackPolicy::Ignore,
+        }
+    }
+}
+
+impl Options {
+    /// Returns the [`text_gap`], or the [`rail_spacing`] if `None`.
+    ///
+    /// [`text_gap`]: struct.Options.html#structfield.text_gap
+    /// [`rail_spacing`]: struct.Options.html#structfield.rail_spacing
+    #[inline]
+    pub(crate) fn text_gap(&self) -> usize {
+        self.text_gap.unwrap_or_else(|| self.rail_spacing.max(1))
+    }
+}
+
+/// The horizontal direction the graph is rendered in.
+///
+/// *[See `Options`.][`Options`]*
+///
+/// [`Options`]: struct.Options.html
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Direction {
+    /// The first track is the leftmost rail, and new
+    /// tracks are added to the right.
+    ///
+    /// ## Output Example
+    ///
+    /// ```text
+    /// * Station 1
+    /// |\
+    /// | * Station 2
+    /// |/
+    /// * Station 3
+    /// ```
+    LeftToRight,
+
+    /// The first track is the rightmost rail, and new
+    /// tracks are added to the left. Station text is
+    /// still placed to the right of the rails.
+    ///
+    /// This mirrors the rails of [`LeftToRight`], i.e. splits
+    /// push rails to the left, and joins pull rails to the right.
+    ///
+    /// Note that all rows are padded to the width of the widest
+    /// row, so the whole graph must be rendered before the first
+    /// row can be written.
+    ///
+    /// [`LeftToRight`]: enum.Direction.html#variant.LeftToRight
+    ///
+    /// ## Output Example
+    ///
+    /// ```text
+    ///   * Station 1
+    ///  /|
+    /// * | Station 2
+    ///  \|
+    ///   * Station 3
+    /// ```
+    RightToLeft,
+}
+
+/// How a station on a track that does not exist is handled.
+///
+/// *[See `Options`.][`Options`]*
+///
+/// [`Options`]: struct.Options.html
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum UnknownTrackPolicy {
+    /// The text of the station is rendered, just not tied to any track.
+    ///
+    /// ## Output Example
+    ///
+    /// Given 2 tracks `0, 1` then `Station(TrackId(5), "Station 1")` would render as:
+    ///
+    /// ```text
+    /// | | Station 1
+    /// ```
+    Ignore,
+
+    /// The track is started before the station, as if by `StartTrack`.
+    ///
+    /// ## Output Example
+    ///
+    /// Given 2 tracks `0, 1` then `Station(TrackId(5), "Station 1")` would render as:
+    ///
+    /// ```text
+    /// | | |
+    /// | | * Station 1
+    /// ```
+    AutoStart,
+
+    /// Rendering fails with an [`io::Error`] of kind [`InvalidInput`],
+    /// without rendering the station. When rendering to a [`fmt::Write`]
+    /// a [`fmt::Error`] is returned instead.
+    ///
+    /// [`io::Error`]: https://doc.rust-lang.org/stable/std/io/struct.Error.html
+    /// [`InvalidInput`]: https://doc.rust-lang.org/stable/std/io/enum.ErrorKind.html#variant.InvalidInput
+    /// [`fmt::Write`]: https://doc.rust-lang.org/stable/std/fmt/trait.Write.html
+    /// [`fmt::Error`]: https://doc.rust-lang.org/stable/std/fmt/struct.Error.html
+    Error,
+}
+
+/// The line ending written after each line.
+///
+/// *[See `Options`.][`Options`]*
+///
+/// [`Options`]: struct.Options.html
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum LineEnding {
+    /// `\n`
+    Lf,
+
+    /// `\r\n`, e.g. for files consumed on Windows.
+    Crlf,
+}
+
+impl LineEnding {
+    /// Returns the line ending as a `&str`, i.e. `"\n"` or `"\r\n"`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use metro::LineEnding;
+    ///
+    /// assert_eq!(LineEnding::Lf.as_str(), "\n");
+    /// assert_eq!(LineEnding::Crlf.as_str(), "\r\n");
+    /// ```
+    #[inline]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            LineEnding::Lf => "\n",
+            LineEnding::Crlf => "\r\n",
+        }
+    }
+}
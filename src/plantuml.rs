@@ -0,0 +1,392 @@
+use std::collections::HashMap;
+use std::fmt::Write;
+
+use crate::events::Event;
+use crate::track_id::TrackId;
+
+/// Render `&[`[`Event`]`]` as a [PlantUML sequence diagram][sequence].
+/// Defines a default track with `track_id` of `0`.
+///
+/// Each track is a participant, whose lifeline spans from where the
+/// track starts to where it stops.
+///
+/// - `StartTrack`, `StartTracks` and `StartTrackAt` turn into `participant`.
+/// - `SplitTrack` and `SplitTrackAt` turn into `create participant`,
+///   followed by an arrow from the track it was split from.
+/// - `SplitStation` turns into the same, followed by `hnote` over the
+///   participant of the new track.
+/// - `JoinTrack`, `JoinTrackToward` and `JoinTrackKeepSource` turn into an
+///   arrow to the track it was joined into, followed by `destroy`.
+/// - `JoinTracks` turns into the same for each joined track.
+/// - `JoinTrackOrRename` turns into the same, or if the track it is joined
+///   into does not exist, into `create participant` of that track, followed
+///   by `destroy`.
+/// - `StopTrack` and `StopTracks` turn into `destroy`.
+/// - `StopTrackLabeled` turns into `hnote` with the label, followed by `destroy`.
+/// - `Station` turns into `hnote` over the participant of the track.
+/// - `StationMarked` turns into a highlighted `hnote`.
+/// - `StationMulti` turns into `hnote` over the participants of the tracks.
+/// - `Annotation` turns into a `== text ==` separator.
+/// - `Link` turns into a dashed arrow.
+///
+/// The participant of a track is aliased `T{track_id}`, such that the
+/// aliases remain the same when events are added. The default track
+/// `0` is named `main`, while any other track is named `track-{track_id}`.
+/// If a track id is reused after its track was stopped, then the new
+/// participant is aliased `T{track_id}_{n}` and named `track-{track_id}-{n}`,
+/// as a destroyed participant cannot be recreated.
+///
+/// Multiple lines of text are joined with `\n`. Stations which are not
+/// tied to any track turn into `note across`.
+///
+/// `RenameTrack` keeps the participant of the track.
+///
+/// `LabelTrack` and `Legend` are ignored, as participants cannot be
+/// renamed after they are created.
+///
+/// [`Event`]: enum.Event.html
+///
+/// [sequence]: https://plantuml.com/sequence-diagram
+///
+/// # Example
+///
+/// ```
+/// use metro::{Event, TrackId};
+///
+/// let events = [
+///     Event::station(0, "Station 1"),
+///     Event::SplitTrack(TrackId(0), TrackId(1)),
+///     Event::station(1, "Station 2"),
+///     Event::station(0, "Station 3"),
+///     Event::JoinTrack(TrackId(1), TrackId(0)),
+/// ];
+///
+/// let plantuml = metro::to_plantuml(&events);
+///
+/// assert_eq!(
+///     plantuml,
+///     r#"@startuml
+/// participant "main" as T0
+/// hnote over T0 : Station 1
+/// create participant "track-1" as T1
+/// T0 -> T1
+/// hnote over T1 : Station 2
+/// hnote over T0 : Station 3
+/// T1 -> T0
+/// destroy T1
+/// @enduml
+/// "#
+/// );
+/// ```
+pub fn to_plantuml(events: &[Event]) -> String {
+    let mut plantuml = PlantUmlBuilder::new();
+
+    for event in events {
+        use Event::*;
+        match event {
+            &StartTrack(track_id) | &StartTrackAt(track_id, _) => {
+                plantuml.start_track(track_id, None)
+            }
+
+            &StartTracks(track_ids) => {
+                for &track_id in track_ids {
+                    plantuml.start_track(track_id, None);
+                }
+            }
+
+            &StopTrack(track_id) => plantuml.stop_track(track_id),
+
+            &StopTracks(track_ids) => {
+                for &track_id in track_ids {
+                    plantuml.stop_track(track_id);
+                }
+            }
+
+            StopTrackLabeled(track_id, label) => {
+                if plantuml.aliases.contains_key(track_id) {
+                    plantuml.note(*track_id, label, false);
+                    plantuml.stop_track(*track_id);
+                }
+            }
+
+            Station(track_id, text) => plantuml.note(*track_id, text, false),
+
+            StationMarked(track_id, text, _) => plantuml.note(*track_id, text, true),
+
+            StationMulti(track_ids, text) => plantuml.note_multi(track_ids, text),
+
+            DetachedStation(text) => plantuml.note_multi(&[], text),
+
+            Annotation(text) => {
+                // Writing to a `String` never fails, so it is safe to `unwrap`
+                writeln!(plantuml.output, "== {} ==", join_lines(text)).unwrap();
+            }
+
+            &SplitTrack(from_track_id, new_track_id)
+            | &SplitTrackAt(from_track_id, new_track_id, _) => {
+                plantuml.start_track(new_track_id, Some(from_track_id))
+            }
+
+            SplitTrackN(from_track_id, new_track_ids) => {
+                for &new_track_id in new_track_ids.iter() {
+                    plantuml.start_track(new_track_id, Some(*from_track_id));
+                }
+            }
+
+            SplitStation(from_track_id, new_track_id, text) => {
+                plantuml.start_track(*new_track_id, Some(*from_track_id));
+                plantuml.note(*new_track_id, text, false);
+            }
+
+            &JoinTrack(from_track_id, to_track_id)
+            | &JoinTrackToward(from_track_id, to_track_id)
+            | &JoinTrackKeepSource(to_track_id, from_track_id) => {
+                if from_track_id != to_track_id {
+                    plantuml.arrow(from_track_id, to_track_id, "->");
+                }
+                plantuml.stop_track(from_track_id);
+            }
+
+            &JoinTrackOrRename(from_track_id, to_track_id) => {
+                if plantuml.aliases.contains_key(&from_track_id)
+                    && !plantuml.aliases.contains_key(&to_track_id)
+                {
+                    plantuml.start_track(to_track_id, Some(from_track_id));
+                } else if from_track_id != to_track_id {
+                    plantuml.arrow(from_track_id, to_track_id, "->");
+                }
+                plantuml.stop_track(from_track_id);
+            }
+
+            &JoinTracks(from_track_ids, to_track_id) => {
+                for &from_track_id in from_track_ids {
+                    if from_track_id != to_track_id {
+                        plantuml.arrow(from_track_id, to_track_id, "->");
+                        plantuml.stop_track(from_track_id);
+                    }
+                }
+            }
+
+            &Link(from_track_id, to_track_id) => {
+                if from_track_id != to_track_id {
+                    plantuml.arrow(from_track_id, to_track_id, "-->");
+                }
+            }
+
+            &RenameTrack(old_track_id, new_track_id) => {
+                if !plantuml.aliases.contains_key(&new_track_id) {
+                    if let Some(alias) = plantuml.aliases.remove(&old_track_id) {
+                        plantuml.aliases.insert(new_track_id, alias);
+                    }
+                }
+            }
+
+            LabelTrack(..) | Legend | NoEvent => {}
+        }
+    }
+
+    plantuml.output.push_str("@enduml\n");
+    plantuml.output
+}
+
+/// Joins the lines of `text` with `\n`, which PlantUML renders as a line break.
+fn join_lines(text: &str) -> String {
+    text.lines().collect::<Vec<_>>().join("\\n")
+}
+
+struct PlantUmlBuilder {
+    output: String,
+    /// Participant aliases of the current tracks.
+    aliases: HashMap<TrackId, String>,
+    /// The number of participants created per track id.
+    created: HashMap<TrackId, usize>,
+}
+
+impl PlantUmlBuilder {
+    fn new() -> Self {
+        let mut plantuml = Self {
+            output: String::from("@startuml\n"),
+            aliases: HashMap::new(),
+            created: HashMap::new(),
+        };
+
+        plantuml.output.push_str("participant \"main\" as T0\n");
+        plantuml.aliases.insert(TrackId(0), String::from("T0"));
+        plantuml.created.insert(TrackId(0), 1);
+
+        plantuml
+    }
+
+    /// Starts `track_id` unless it already exists. If `from_track_id`
+    /// exists, then the participant is created by an arrow from it.
+    fn start_track(&mut self, track_id: TrackId, from_track_id: Option<TrackId>) {
+        if self.aliases.contains_key(&track_id) {
+            return;
+        }
+
+        let created = self.created.entry(track_id).or_insert(0);
+        *created += 1;
+
+        let (alias, name) = if *created == 1 {
+            (format!("T{}", track_id), format!("track-{}", track_id))
+        } else {
+            (
+                format!("T{}_{}", track_id, created),
+                format!("track-{}-{}", track_id, created),
+            )
+        };
+
+        let aliases = &self.aliases;
+        let from = from_track_id.and_then(|id| aliases.get(&id));
+
+        // Writing to a `String` never fails, so it is safe to `unwrap`
+        match from {
+            Some(from) => {
+                writeln!(self.output, "create participant \"{}\" as {}", name, alias).unwrap();
+                writeln!(self.output, "{} -> {}", from, alias).unwrap();
+            }
+            None => writeln!(self.output, "participant \"{}\" as {}", name, alias).unwrap(),
+        }
+
+        self.aliases.insert(track_id, alias);
+    }
+
+    fn stop_track(&mut self, track_id: TrackId) {
+        if let Some(alias) = self.aliases.remove(&track_id) {
+            writeln!(self.output, "destroy {}", alias).unwrap();
+        }
+    }
+
+    fn arrow(&mut self, from_track_id: TrackId, to_track_id: TrackId, arrow: &str) {
+        if let (Some(from), Some(to)) = (
+            self.aliases.get(&from_track_id),
+            self.aliases.get(&to_track_id),
+        ) {
+            writeln!(self.output, "{} {} {}", from, arrow, to).unwrap();
+        }
+    }
+
+    fn note(&mut self, track_id: TrackId, text: &str, highlight: bool) {
+        let text = join_lines(text);
+
+        match (self.aliases.get(&track_id), highlight) {
+            (Some(alias), false) => writeln!(self.output, "hnote over {} : {}", alias, text),
+            (Some(alias), true) => writeln!(self.output, "hnote over {} #yellow : {}", alias, text),
+            (None, _) => writeln!(self.output, "note across : {}", text),
+        }
+        .unwrap();
+    }
+
+    fn note_multi(&mut self, track_ids: &[TrackId], text: &str) {
+        let aliases = track_ids
+            .iter()
+            .filter_map(|track_id| self.aliases.get(track_id).map(String::as_str))
+            .collect::<Vec<_>>();
+
+        let text = join_lines(text);
+
+        if aliases.is_empty() {
+            writeln!(self.output, "note across : {}", text).unwrap();
+        } else {
+            writeln!(self.output, "hnote over {} : {}", aliases.join(", "), text).unwrap();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::to_plantuml;
+    use crate::Event::{self, *};
+    use crate::TrackId;
+
+    #[test]
+    fn empty() {
+        assert_eq!(
+            to_plantuml(&[]),
+            "@startuml\nparticipant \"main\" as T0\n@enduml\n"
+        );
+    }
+
+    #[test]
+    fn reused_track_id() {
+        let events = [
+            SplitTrack(TrackId(0), TrackId(1)),
+            StopTrack(TrackId(1)),
+            SplitTrack(TrackId(0), TrackId(1)),
+            Event::station(1, "Station 1"),
+            StartTrack(TrackId(2)),
+        ];
+        let plantuml = to_plantuml(&events);
+
+        assert_eq!(
+            plantuml,
+            r#"@startuml
+participant "main" as T0
+create participant "track-1" as T1
+T0 -> T1
+destroy T1
+create participant "track-1-2" as T1_2
+T0 -> T1_2
+hnote over T1_2 : Station 1
+participant "track-2" as T2
+@enduml
+"#
+        );
+    }
+
+    #[test]
+    fn joins() {
+        let events = [
+            StartTracks(&[TrackId(1), TrackId(2), TrackId(3)]),
+            JoinTrackKeepSource(TrackId(1), TrackId(0)),
+            JoinTracks(&[TrackId(2), TrackId(3)], TrackId(1)),
+            Link(TrackId(0), TrackId(1)),
+        ];
+        let plantuml = to_plantuml(&events);
+
+        assert_eq!(
+            plantuml,
+            r#"@startuml
+participant "main" as T0
+participant "track-1" as T1
+participant "track-2" as T2
+participant "track-3" as T3
+T0 -> T1
+destroy T0
+T2 -> T1
+destroy T2
+T3 -> T1
+destroy T3
+@enduml
+"#
+        );
+    }
+
+    #[test]
+    fn text() {
+        let events = [
+            Event::station_marked(0, "Station 1", '@'),
+            Event::station(5, "Detached\nStation"),
+            StartTrack(TrackId(1)),
+            Event::station_multi(&[TrackId(1), TrackId(0)], "Shared"),
+            Event::annotation("Annotation"),
+            Event::stop_track_labeled(0, "Deleted"),
+        ];
+        let plantuml = to_plantuml(&events);
+
+        assert_eq!(
+            plantuml,
+            r#"@startuml
+participant "main" as T0
+hnote over T0 #yellow : Station 1
+note across : Detached\nStation
+participant "track-1" as T1
+hnote over T1, T0 : Shared
+== Annotation ==
+hnote over T0 : Deleted
+destroy T0
+@enduml
+"#
+        );
+    }
+}
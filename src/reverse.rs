@@ -0,0 +1,280 @@
+use crate::events::{render_event, Event, RenderState};
+use crate::track_id::TrackId;
+
+/// Reverse `&[`[`Event`]`]`, such that the rendered graph is upside-down,
+/// e.g. to turn a newest-first log into an oldest-first log.
+/// Defines a default track with `track_id` of `0`.
+///
+/// The events are both reversed and inverted:
+///
+/// - `SplitTrack` and `SplitTrackAt` turn into `JoinTrack`.
+/// - `SplitStation` turns into `Station` followed by `JoinTrack`.
+/// - `JoinTrack`, `JoinTrackToward` and `JoinTrackKeepSource` turn into `SplitTrack`,
+///   or `SplitTrackAt` if the removed track was not right next to the track it joined.
+/// - `JoinTracks` turns into a split for each joined track.
+/// - `StopTrack` and `StopTrackLabeled` turn into a start, placing the track
+///   where it was, and dropping the label.
+/// - `StartTrack` and `StartTrackAt` turn into `StopTrack`.
+/// - `StartTracks` turns into `StopTracks`, and vice versa.
+/// - `RenameTrack` turns into `RenameTrack` back to the old track id.
+/// - `JoinTrackOrRename` turns into `JoinTrackOrRename` back to the old track id,
+///   if it renamed the track, otherwise it turns into a split like `JoinTrack`.
+///
+/// The tracks remaining at the end of `events` are started first,
+/// while leading `StartTrack` and `StartTracks` events are skipped,
+/// as their tracks simply remain at the end of the reversed events.
+///
+/// Reversing twice renders the same as the original `events`, with the
+/// following exceptions. `JoinTracks` turns into multiple `JoinTrack`s,
+/// `JoinTrackKeepSource` turns into `JoinTrack`, which only keeps the same
+/// track ids if `from_track_id` was to the left of `to_track_id`,
+/// `StartTrack` and `StartTracks` always place the tracks rightmost, and
+/// trailing stops turn into leading starts, which are then skipped.
+///
+/// `LabelTrack` and `Legend` are kept in their reversed position,
+/// so a `Legend` only includes the labels set below it.
+///
+/// [`Event`]: enum.Event.html
+///
+/// # Example
+///
+/// ```
+/// use metro::{Event, TrackId};
+///
+/// let events = [
+///     Event::station(0, "Station 1"),
+///     Event::SplitTrack(TrackId(0), TrackId(1)),
+///     Event::station(1, "Station 2"),
+///     Event::station(0, "Station 3"),
+///     Event::JoinTrack(TrackId(1), TrackId(0)),
+///     Event::station(0, "Station 4"),
+/// ];
+///
+/// let reversed = metro::reverse(&events);
+///
+/// assert_eq!(
+///     metro::to_string(&reversed).unwrap(),
+///     r#"* Station 4
+/// |\
+/// * | Station 3
+/// | * Station 2
+/// |/
+/// * Station 1
+/// "#
+/// );
+/// ```
+pub fn reverse<'a>(events: &[Event<'a>]) -> Vec<Event<'a>> {
+    let mut state = RenderState::new();
+    let mut rows = Vec::new();
+
+    // Leading starts define the initial tracks
+    let skip = events
+        .iter()
+        .take_while(|event| matches!(event, Event::StartTrack(_) | Event::StartTracks(_)))
+        .count();
+
+    let mut inverted = Vec::with_capacity(events.len());
+
+    for (i, event) in events.iter().enumerate() {
+        if i >= skip {
+            inverted.push(invert(event, &state.tracks));
+        }
+
+        render_event(&mut rows, &mut state, event);
+        rows.clear();
+    }
+
+    let mut reversed = Vec::with_capacity(events.len());
+
+    // The default track `0` is always the first track
+    let tracks = match state.tracks.split_first() {
+        Some((TrackId(0), tracks)) => tracks,
+        _ => {
+            reversed.push(Event::StopTrack(TrackId(0)));
+            &state.tracks[..]
+        }
+    };
+
+    reversed.extend(tracks.iter().map(|&track_id| Event::StartTrack(track_id)));
+    reversed.extend(inverted.into_iter().rev().flatten());
+
+    reversed
+}
+
+/// Returns the events undoing `event`, given the `tracks` before `event`.
+fn invert<'a>(event: &Event<'a>, tracks: &[TrackId]) -> Vec<Event<'a>> {
+    use Event::*;
+    match event {
+        &StartTrack(track_id) | &StartTrackAt(track_id, _) => vec![StopTrack(track_id)],
+        &StartTracks(track_ids) => vec![StopTracks(track_ids)],
+
+        &StopTrack(track_id) | &StopTrackLabeled(track_id, _) => {
+            let track_index = tracks.iter().position(|&id| id == track_id);
+
+            // Splitting from a track that does not exist, inserts the
+            // track to the right of its neighbor, as if it was started
+            match track_index
+                .and_then(|i| i.checked_sub(1))
+                .map(|i| tracks[i])
+            {
+                Some(neighbor_track_id) => {
+                    vec![SplitTrackAt(track_id, track_id, neighbor_track_id)]
+                }
+                None => vec![StartTrack(track_id)],
+            }
+        }
+
+        &StopTracks(track_ids) => vec![StartTracks(track_ids)],
+
+        &SplitTrack(from_track_id, new_track_id)
+        | &SplitTrackAt(from_track_id, new_track_id, _) => {
+            vec![JoinTrack(new_track_id, from_track_id)]
+        }
+
+        SplitStation(from_track_id, new_track_id, text) => vec![
+            Station(*new_track_id, text.clone()),
+            JoinTrack(*new_track_id, *from_track_id),
+        ],
+
+        SplitTrackN(from_track_id, new_track_ids) => {
+            let mut new_tracks = Vec::new();
+            for &new_track_id in new_track_ids.iter() {
+                if !tracks.contains(&new_track_id) && !new_tracks.contains(&new_track_id) {
+                    new_tracks.push(new_track_id);
+                }
+            }
+
+            // The rightmost track is joined first, such that
+            // the joins render as the fan in reverse
+            new_tracks
+                .into_iter()
+                .rev()
+                .map(|new_track_id| JoinTrack(new_track_id, *from_track_id))
+                .collect()
+        }
+
+        &JoinTrack(from_track_id, to_track_id)
+        | &JoinTrackToward(from_track_id, to_track_id)
+        | &JoinTrackKeepSource(to_track_id, from_track_id) => {
+            split_back(tracks, from_track_id, to_track_id)
+                .into_iter()
+                .collect()
+        }
+
+        &JoinTrackOrRename(from_track_id, to_track_id) => {
+            if tracks.contains(&from_track_id) && !tracks.contains(&to_track_id) {
+                vec![JoinTrackOrRename(to_track_id, from_track_id)]
+            } else {
+                split_back(tracks, from_track_id, to_track_id)
+                    .into_iter()
+                    .collect()
+            }
+        }
+
+        &JoinTracks(from_track_ids, to_track_id) => tracks
+            .iter()
+            .filter(|id| from_track_ids.contains(id))
+            .flat_map(|&from_track_id| split_back(tracks, from_track_id, to_track_id))
+            .collect(),
+
+        &RenameTrack(old_track_id, new_track_id) => {
+            if tracks.contains(&old_track_id) && !tracks.contains(&new_track_id) {
+                vec![RenameTrack(new_track_id, old_track_id)]
+            } else {
+                vec![]
+            }
+        }
+
+        event => vec![event.clone()],
+    }
+}
+
+/// Returns the split undoing `from_track_id` joining `to_track_id`,
+/// placing the track where it was before being joined.
+fn split_back(
+    tracks: &[TrackId],
+    from_track_id: TrackId,
+    to_track_id: TrackId,
+) -> Option<Event<'static>> {
+    let from_track_index = tracks.iter().position(|&id| id == from_track_id)?;
+
+    // Joining a track with itself, or with a track
+    // that does not exist, only stops the track
+    if (from_track_id == to_track_id) || !tracks.contains(&to_track_id) {
+        return Some(Event::StartTrack(from_track_id));
+    }
+
+    match from_track_index.checked_sub(1).map(|i| tracks[i]) {
+        Some(neighbor_track_id) if neighbor_track_id != to_track_id => Some(Event::SplitTrackAt(
+            to_track_id,
+            from_track_id,
+            neighbor_track_id,
+        )),
+        _ => Some(Event::SplitTrack(to_track_id, from_track_id)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::reverse;
+    use crate::to_string;
+    use crate::Event::{self, *};
+    use crate::TrackId;
+
+    #[test]
+    fn reverse_twice() {
+        let events = [
+            StartTrack(TrackId(0)),
+            Event::station(0, "Station 1"),
+            SplitTrack(TrackId(0), TrackId(1)),
+            Event::station(1, "Station 2"),
+            SplitTrack(TrackId(1), TrackId(2)),
+            SplitTrack(TrackId(0), TrackId(3)),
+            Event::station(2, "Station 3"),
+            JoinTrack(TrackId(2), TrackId(0)),
+            Event::station(3, "Station 4"),
+            StartTrack(TrackId(4)),
+            StopTrack(TrackId(3)),
+            Event::station(1, "Station 5"),
+            JoinTrack(TrackId(4), TrackId(1)),
+            RenameTrack(TrackId(1), TrackId(6)),
+            Event::station(6, "Station 6"),
+            JoinTrackOrRename(TrackId(6), TrackId(7)),
+            Event::station(7, "Station 7"),
+            NoEvent,
+        ];
+
+        let reversed = reverse(&events);
+        let twice = reverse(&reversed);
+
+        assert_eq!(to_string(&twice).unwrap(), to_string(&events).unwrap());
+    }
+
+    #[test]
+    fn remaining_tracks() {
+        let events = [
+            Event::station(0, "Station 1"),
+            SplitTrack(TrackId(0), TrackId(1)),
+            Event::station(1, "Station 2"),
+            SplitTrack(TrackId(1), TrackId(2)),
+            Event::station(2, "Station 3"),
+            JoinTrack(TrackId(1), TrackId(0)),
+            Event::station(0, "Station 4"),
+        ];
+
+        let reversed = reverse(&events);
+
+        assert_eq!(
+            to_string(&reversed).unwrap(),
+            r#"| |
+* | Station 4
+|\ \
+| | * Station 3
+| |/
+| * Station 2
+|/
+* Station 1
+"#
+        );
+    }
+}
@@ -0,0 +1,140 @@
+use crate::events::{render_event, Event, RenderState};
+
+/// Simplify `&[`[`Event`]`]`, by removing events that render nothing,
+/// and limiting runs of consecutive `NoEvent`s to `max_no_events`.
+/// Defines a default track with `track_id` of `0`.
+///
+/// Events that render nothing and leave the tracks unchanged are removed,
+/// e.g. `StartTrack` of a track that already exists, or `JoinTrack` from
+/// a track that does not exist. `LabelTrack` is always kept.
+///
+/// All stations, splits, and joins are preserved, so the rendered
+/// output is the same, except for the removed `NoEvent` rows.
+///
+/// [`Event`]: enum.Event.html
+///
+/// # Example
+///
+/// ```
+/// use metro::Event::*;
+/// use metro::TrackId;
+///
+/// let events = [
+///     StartTrack(TrackId(0)),
+///     NoEvent,
+///     NoEvent,
+///     StopTrack(TrackId(5)),
+///     NoEvent,
+///     SplitTrack(TrackId(0), TrackId(1)),
+/// ];
+///
+/// let simplified = metro::simplify(&events, 1);
+///
+/// assert_eq!(simplified, [NoEvent, SplitTrack(TrackId(0), TrackId(1))]);
+/// ```
+pub fn simplify<'a>(events: &[Event<'a>], max_no_events: usize) -> Vec<Event<'a>> {
+    let mut state = RenderState::new();
+    let mut rows = Vec::new();
+
+    let mut simplified = Vec::with_capacity(events.len());
+    let mut no_events = 0;
+
+    for event in events {
+        let tracks = state.tracks.clone();
+
+        render_event(&mut rows, &mut state, event);
+
+        let no_op = rows.is_empty() && (tracks == state.tracks);
+        rows.clear();
+
+        match event {
+            Event::NoEvent => {
+                no_events += 1;
+
+                if no_events <= max_no_events {
+                    simplified.push(Event::NoEvent);
+                }
+            }
+            Event::LabelTrack(..) => simplified.push(event.clone()),
+            _ if no_op => {}
+            _ => {
+                no_events = 0;
+                simplified.push(event.clone());
+            }
+        }
+    }
+
+    simplified
+}
+
+#[cfg(test)]
+mod tests {
+    use super::simplify;
+    use crate::to_string;
+    use crate::Event::{self, *};
+    use crate::TrackId;
+
+    #[test]
+    fn no_events() {
+        let events = [
+            NoEvent,
+            NoEvent,
+            NoEvent,
+            Event::station(0, "Station 1"),
+            NoEvent,
+            StartTrack(TrackId(0)),
+            NoEvent,
+        ];
+
+        assert_eq!(simplify(&events, 0), [Event::station(0, "Station 1")]);
+        assert_eq!(
+            simplify(&events, 1),
+            [NoEvent, Event::station(0, "Station 1"), NoEvent]
+        );
+        assert_eq!(
+            simplify(&events, 3),
+            [
+                NoEvent,
+                NoEvent,
+                NoEvent,
+                Event::station(0, "Station 1"),
+                NoEvent,
+                NoEvent,
+            ]
+        );
+    }
+
+    #[test]
+    fn no_ops() {
+        let events = [
+            StartTracks(&[TrackId(0), TrackId(1)]),
+            StartTrack(TrackId(1)),
+            SplitTrack(TrackId(0), TrackId(1)),
+            Event::station(1, "Station 1"),
+            JoinTrack(TrackId(2), TrackId(0)),
+            StopTrack(TrackId(2)),
+            StopTracks(&[TrackId(2), TrackId(3)]),
+            Legend,
+            Event::label_track(1, "Track 1"),
+            Legend,
+            JoinTrack(TrackId(1), TrackId(0)),
+            Event::station(0, "Station 2"),
+        ];
+
+        let simplified = simplify(&events, 1);
+
+        assert_eq!(
+            simplified,
+            [
+                StartTracks(&[TrackId(0), TrackId(1)]),
+                Event::station(1, "Station 1"),
+                Event::label_track(1, "Track 1"),
+                Legend,
+                JoinTrack(TrackId(1), TrackId(0)),
+                Event::station(0, "Station 2"),
+            ]
+        );
+
+        assert_eq!(to_string(&simplified).unwrap(), to_string(&events).unwrap());
+    }
+}
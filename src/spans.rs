@@ -0,0 +1,300 @@
+use std::borrow::Cow;
+use std::collections::HashMap;
+
+use crate::events::{render_event, Event, RenderState};
+use crate::track_id::TrackId;
+
+/// The lifespan of a track produced by [`track_spans`], i.e. from the
+/// event that started the track, through its stations, to the event
+/// that stopped it.
+///
+/// [`track_spans`]: fn.track_spans.html
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub struct TrackSpan<'a> {
+    /// The id of the track.
+    pub track_id: TrackId,
+    /// How the track was started.
+    pub start: SpanStart,
+    /// The index of the event that started the track, or `None`
+    /// for the default track `0`.
+    pub start_index: Option<usize>,
+    /// The text of the stations on the track, in order.
+    pub stations: Vec<Cow<'a, str>>,
+    /// How the track was stopped.
+    pub end: SpanEnd,
+    /// The index of the event that stopped the track, or `None`
+    /// if the track remains at the end of the events.
+    pub end_index: Option<usize>,
+}
+
+/// How a [`TrackSpan`] starts.
+///
+/// [`TrackSpan`]: struct.TrackSpan.html
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum SpanStart {
+    /// The default track `0`, which exists before any events.
+    Initial,
+    /// Started by `StartTrack`, `StartTracks` or `StartTrackAt`, or
+    /// by a split from a track that does not exist.
+    Started,
+    /// Split from the track with the given id.
+    SplitFrom(TrackId),
+    /// Renamed from the track with the given id.
+    RenamedFrom(TrackId),
+}
+
+/// How a [`TrackSpan`] ends.
+///
+/// [`TrackSpan`]: struct.TrackSpan.html
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum SpanEnd {
+    /// The track remains at the end of the events.
+    Open,
+    /// Stopped by `StopTrack`, `StopTracks` or `StopTrackLabeled`,
+    /// or by a join into a track that does not exist.
+    Stopped,
+    /// Joined into the track with the given id.
+    JoinedInto(TrackId),
+    /// Renamed to the track with the given id.
+    RenamedTo(TrackId),
+}
+
+/// Group `&[`[`Event`]`]` into the lifespans of the tracks, i.e. the
+/// start, stations and stop of each track, ordered by when they start.
+/// Defines a default track with `track_id` of `0`.
+///
+/// A track id that is reused after its track was stopped, results in
+/// multiple spans with the same `track_id`. Stations which are not
+/// tied to any track are skipped.
+///
+/// [`Event`]: enum.Event.html
+///
+/// # Example
+///
+/// ```
+/// use metro::{Event, SpanEnd, SpanStart, TrackId};
+///
+/// let events = [
+///     Event::station(0, "Station 1"),
+///     Event::SplitTrack(TrackId(0), TrackId(1)),
+///     Event::station(1, "Station 2"),
+///     Event::station(0, "Station 3"),
+///     Event::JoinTrack(TrackId(1), TrackId(0)),
+/// ];
+///
+/// let spans = metro::track_spans(&events);
+///
+/// assert_eq!(spans.len(), 2);
+///
+/// assert_eq!(spans[0].track_id, 0);
+/// assert_eq!(spans[0].stations, ["Station 1", "Station 3"]);
+/// assert_eq!(spans[0].end, SpanEnd::Open);
+///
+/// assert_eq!(spans[1].track_id, 1);
+/// assert_eq!(spans[1].start, SpanStart::SplitFrom(TrackId(0)));
+/// assert_eq!(spans[1].stations, ["Station 2"]);
+/// assert_eq!(spans[1].end, SpanEnd::JoinedInto(TrackId(0)));
+/// assert_eq!(spans[1].end_index, Some(4));
+/// ```
+pub fn track_spans<'a>(events: &[Event<'a>]) -> Vec<TrackSpan<'a>> {
+    let mut state = RenderState::new();
+    let mut rows = Vec::new();
+
+    let mut spans = vec![TrackSpan::new(TrackId(0), SpanStart::Initial, None)];
+    // Indices into `spans` of the current tracks
+    let mut open = HashMap::new();
+    open.insert(TrackId(0), 0);
+
+    for (index, event) in events.iter().enumerate() {
+        let before = state.tracks.clone();
+
+        render_event(&mut rows, &mut state, event);
+        rows.clear();
+
+        let after = &state.tracks;
+
+        use Event::*;
+        match event {
+            Station(track_id, text) | StationMarked(track_id, text, _) => {
+                if let Some(&i) = open.get(track_id) {
+                    spans[i].stations.push(text.clone());
+                }
+            }
+            StationMulti(track_ids, text) => {
+                for track_id in track_ids.iter() {
+                    if let Some(&i) = open.get(track_id) {
+                        spans[i].stations.push(text.clone());
+                    }
+                }
+            }
+            _ => {}
+        }
+
+        for &track_id in before.iter().filter(|id| !after.contains(id)) {
+            let end = match *event {
+                JoinTrackOrRename(_, to_track_id) if !before.contains(&to_track_id) => {
+                    SpanEnd::RenamedTo(to_track_id)
+                }
+                JoinTrack(from_track_id, to_track_id)
+                | JoinTrackToward(from_track_id, to_track_id)
+                | JoinTrackOrRename(from_track_id, to_track_id)
+                | JoinTrackKeepSource(to_track_id, from_track_id)
+                    if (track_id == from_track_id) && after.contains(&to_track_id) =>
+                {
+                    SpanEnd::JoinedInto(to_track_id)
+                }
+                JoinTracks(_, to_track_id) if after.contains(&to_track_id) => {
+                    SpanEnd::JoinedInto(to_track_id)
+                }
+                RenameTrack(_, new_track_id) => SpanEnd::RenamedTo(new_track_id),
+                _ => SpanEnd::Stopped,
+            };
+
+            // Safe to use `unwrap` as all current tracks are open
+            let i = open.remove(&track_id).unwrap();
+            spans[i].end = end;
+            spans[i].end_index = Some(index);
+        }
+
+        for &track_id in after.iter().filter(|id| !before.contains(id)) {
+            let start = match *event {
+                SplitTrack(from_track_id, _)
+                | SplitTrackAt(from_track_id, _, _)
+                | SplitTrackN(from_track_id, _)
+                | SplitStation(from_track_id, _, _)
+                    if before.contains(&from_track_id) =>
+                {
+                    SpanStart::SplitFrom(from_track_id)
+                }
+                RenameTrack(old_track_id, _) | JoinTrackOrRename(old_track_id, _) => {
+                    SpanStart::RenamedFrom(old_track_id)
+                }
+                _ => SpanStart::Started,
+            };
+
+            open.insert(track_id, spans.len());
+            spans.push(TrackSpan::new(track_id, start, Some(index)));
+        }
+
+        // The station is on the track started by the split
+        if let SplitStation(_, track_id, text) = event {
+            if let Some(&i) = open.get(track_id) {
+                spans[i].stations.push(text.clone());
+            }
+        }
+    }
+
+    spans
+}
+
+impl<'a> TrackSpan<'a> {
+    #[inline]
+    fn new(track_id: TrackId, start: SpanStart, start_index: Option<usize>) -> Self {
+        Self {
+            track_id,
+            start,
+            start_index,
+            stations: Vec::new(),
+            end: SpanEnd::Open,
+            end_index: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{track_spans, SpanEnd, SpanStart, TrackSpan};
+    use crate::Event::{self, *};
+    use crate::TrackId;
+
+    #[test]
+    fn empty() {
+        assert_eq!(
+            track_spans(&[]),
+            [TrackSpan {
+                track_id: TrackId(0),
+                start: SpanStart::Initial,
+                start_index: None,
+                stations: vec![],
+                end: SpanEnd::Open,
+                end_index: None,
+            }]
+        );
+    }
+
+    #[test]
+    fn starts_and_ends() {
+        let events = [
+            StartTracks(&[TrackId(1), TrackId(2), TrackId(3)]),
+            SplitTrack(TrackId(5), TrackId(4)),
+            JoinTrackKeepSource(TrackId(1), TrackId(0)),
+            RenameTrack(TrackId(2), TrackId(6)),
+            Event::station(6, "Station 1"),
+            JoinTracks(&[TrackId(3), TrackId(4)], TrackId(6)),
+            StopTrack(TrackId(1)),
+            JoinTrack(TrackId(6), TrackId(7)),
+            Event::station(7, "Detached"),
+        ];
+        let spans = track_spans(&events);
+
+        let summary = spans
+            .iter()
+            .map(|span| {
+                (
+                    span.track_id,
+                    span.start,
+                    span.start_index,
+                    span.end,
+                    span.end_index,
+                )
+            })
+            .collect::<Vec<_>>();
+
+        #[rustfmt::skip]
+        assert_eq!(
+            summary,
+            [
+                (TrackId(0), SpanStart::Initial, None, SpanEnd::JoinedInto(TrackId(1)), Some(2)),
+                (TrackId(1), SpanStart::Started, Some(0), SpanEnd::Stopped, Some(6)),
+                (TrackId(2), SpanStart::Started, Some(0), SpanEnd::RenamedTo(TrackId(6)), Some(3)),
+                (TrackId(3), SpanStart::Started, Some(0), SpanEnd::JoinedInto(TrackId(6)), Some(5)),
+                (TrackId(4), SpanStart::Started, Some(1), SpanEnd::JoinedInto(TrackId(6)), Some(5)),
+                (TrackId(6), SpanStart::RenamedFrom(TrackId(2)), Some(3), SpanEnd::Stopped, Some(7)),
+            ]
+        );
+        assert_eq!(spans[5].stations, ["Station 1"]);
+    }
+
+    #[test]
+    fn split_station() {
+        let events = [
+            Event::split_station(0, 1, "Station 1"),
+            Event::split_station(5, 2, "Station 2"),
+        ];
+        let spans = track_spans(&events);
+
+        assert_eq!(spans.len(), 3);
+        assert_eq!(spans[1].start, SpanStart::SplitFrom(TrackId(0)));
+        assert_eq!(spans[1].stations, ["Station 1"]);
+        assert_eq!(spans[2].start, SpanStart::Started);
+        assert_eq!(spans[2].stations, ["Station 2"]);
+    }
+
+    #[test]
+    fn reused_track_id() {
+        let events = [
+            SplitTrack(TrackId(0), TrackId(1)),
+            Event::station(1, "Station 1"),
+            StopTrack(TrackId(1)),
+            SplitTrack(TrackId(0), TrackId(1)),
+            Event::station(1, "Station 2"),
+        ];
+        let spans = track_spans(&events);
+
+        assert_eq!(spans.len(), 3);
+        assert_eq!(spans[1].stations, ["Station 1"]);
+        assert_eq!(spans[2].stations, ["Station 2"]);
+        assert_eq!(spans[2].start, SpanStart::SplitFrom(TrackId(0)));
+        assert_eq!(spans[2].end, SpanEnd::Open);
+    }
+}
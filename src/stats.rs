@@ -0,0 +1,183 @@
+use std::collections::HashSet;
+
+use crate::events::{render_event, Event, RenderState};
+
+/// Statistics about `&[`[`Event`]`]` produced by [`stats`].
+///
+/// [`Event`]: enum.Event.html
+/// [`stats`]: fn.stats.html
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Default, Debug)]
+pub struct Stats {
+    /// The number of stations, i.e. `Station`, `StationMarked`,
+    /// `StationMulti` and `SplitStation` events, including stations
+    /// not tied to any track.
+    pub stations: usize,
+    /// The number of tracks split from an existing track.
+    pub splits: usize,
+    /// The number of tracks joined into an existing track.
+    pub joins: usize,
+    /// The maximum number of tracks existing at the same time,
+    /// i.e. the peak number of rails in a single row.
+    pub max_tracks: usize,
+    /// The number of distinct track ids of all tracks that existed.
+    pub distinct_tracks: usize,
+}
+
+/// Computes [`Stats`] about `&[`[`Event`]`]`, without rendering them.
+/// Defines a default track with `track_id` of `0`.
+///
+/// Only events that affect the tracks are counted, e.g. a `SplitTrack`
+/// from a track that does not exist starts a track, and is thereby not
+/// counted as a split. Likewise a `JoinTrack` into a track that does
+/// not exist stops the track, and is not counted as a join.
+///
+/// [`Stats`]: struct.Stats.html
+/// [`Event`]: enum.Event.html
+///
+/// # Example
+///
+/// ```
+/// use metro::{Event, Stats, TrackId};
+///
+/// let events = [
+///     Event::station(0, "Station 1"),
+///     Event::SplitTrack(TrackId(0), TrackId(1)),
+///     Event::SplitTrack(TrackId(1), TrackId(2)),
+///     Event::station(2, "Station 2"),
+///     Event::JoinTrack(TrackId(2), TrackId(0)),
+///     Event::StopTrack(TrackId(1)),
+/// ];
+///
+/// assert_eq!(
+///     metro::stats(&events),
+///     Stats {
+///         stations: 2,
+///         splits: 2,
+///         joins: 1,
+///         max_tracks: 3,
+///         distinct_tracks: 3,
+///     }
+/// );
+/// ```
+pub fn stats(events: &[Event]) -> Stats {
+    let mut state = RenderState::new();
+    let mut rows = Vec::new();
+
+    let mut stats = Stats {
+        max_tracks: state.tracks.len(),
+        ..Stats::default()
+    };
+    let mut track_ids = state.tracks.iter().copied().collect::<HashSet<_>>();
+
+    for event in events {
+        let before = state.tracks.clone();
+
+        render_event(&mut rows, &mut state, event);
+        rows.clear();
+
+        let after = &state.tracks;
+
+        use Event::*;
+        match *event {
+            Station(..) | StationMarked(..) | StationMulti(..) | DetachedStation(_) => {
+                stats.stations += 1
+            }
+
+            SplitStation(from_track_id, _, _) => {
+                stats.stations += 1;
+
+                if before.contains(&from_track_id) {
+                    stats.splits += after.iter().filter(|id| !before.contains(id)).count();
+                }
+            }
+
+            SplitTrack(from_track_id, _)
+            | SplitTrackAt(from_track_id, _, _)
+            | SplitTrackN(from_track_id, _)
+                if before.contains(&from_track_id) =>
+            {
+                stats.splits += after.iter().filter(|id| !before.contains(id)).count();
+            }
+
+            JoinTrack(_, to_track_id)
+            | JoinTrackToward(_, to_track_id)
+            | JoinTrackOrRename(_, to_track_id)
+            | JoinTrackKeepSource(to_track_id, _)
+            | JoinTracks(_, to_track_id)
+                if before.contains(&to_track_id) && after.contains(&to_track_id) =>
+            {
+                stats.joins += before.iter().filter(|id| !after.contains(id)).count();
+            }
+
+            _ => {}
+        }
+
+        stats.max_tracks = stats.max_tracks.max(after.len());
+        track_ids.extend(after.iter().copied());
+    }
+
+    stats.distinct_tracks = track_ids.len();
+    stats
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{stats, Stats};
+    use crate::Event::{self, *};
+    use crate::TrackId;
+
+    #[test]
+    fn empty() {
+        assert_eq!(
+            stats(&[]),
+            Stats {
+                max_tracks: 1,
+                distinct_tracks: 1,
+                ..Stats::default()
+            }
+        );
+    }
+
+    #[test]
+    fn ignored() {
+        let events = [
+            SplitTrack(TrackId(5), TrackId(1)),
+            JoinTrack(TrackId(1), TrackId(6)),
+            JoinTrackOrRename(TrackId(0), TrackId(2)),
+            Event::detached_station("Station 1"),
+        ];
+
+        assert_eq!(
+            stats(&events),
+            Stats {
+                stations: 1,
+                splits: 0,
+                joins: 0,
+                max_tracks: 2,
+                distinct_tracks: 3,
+            }
+        );
+    }
+
+    #[test]
+    fn multiple() {
+        let events = [
+            Event::split_track_n(0, &[TrackId(1), TrackId(2), TrackId(3)][..]),
+            StationMulti(&[TrackId(1), TrackId(2)], "Station 1".into()),
+            JoinTracks(&[TrackId(1), TrackId(2)], TrackId(0)),
+            JoinTrackKeepSource(TrackId(3), TrackId(0)),
+            StartTrack(TrackId(1)),
+        ];
+
+        assert_eq!(
+            stats(&events),
+            Stats {
+                stations: 1,
+                splits: 3,
+                joins: 3,
+                max_tracks: 4,
+                distinct_tracks: 4,
+            }
+        );
+    }
+}
@@ -0,0 +1,414 @@
+use std::fmt::Write;
+
+use crate::events::Event;
+use crate::layout::layout;
+use crate::width::text_width;
+
+/// Options used by [`to_svg`].
+///
+/// [`to_svg`]: fn.to_svg.html
+#[derive(Clone, Debug)]
+pub struct SvgOptions {
+    /// Horizontal distance between two rails.
+    ///
+    /// Default: `20.0`
+    pub column_spacing: f32,
+
+    /// Height of a single row.
+    ///
+    /// Default: `20.0`
+    pub row_height: f32,
+
+    /// Font size used for station text.
+    ///
+    /// Default: `14.0`
+    pub font_size: f32,
+}
+
+impl Default for SvgOptions {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            column_spacing: 20.0,
+            row_height: 20.0,
+            font_size: 14.0,
+        }
+    }
+}
+
+/// Render `&[`[`Event`]`]` as an [SVG] image.
+/// Defines a default track with `track_id` of `0`.
+///
+/// Rails are drawn as vertical lines, splits and joins as
+/// diagonal lines, and stations as circles followed by their text.
+/// Annotations are drawn as text only, and links as dotted lines.
+///
+/// The rows of the SVG do not map one-to-one with the rows
+/// produced by [`to_string`]. For instance a `JoinTrack` always
+/// occupies a single row, regardless of how many rails are
+/// between the joined tracks.
+///
+/// `LabelTrack` and `Legend` are currently ignored.
+///
+/// The geometry is computed by [`layout`], which can be used
+/// directly for rendering in other formats.
+///
+/// [`Event`]: enum.Event.html
+/// [`to_string`]: fn.to_string.html
+/// [`layout`]: fn.layout.html
+///
+/// [SVG]: https://developer.mozilla.org/en-US/docs/Web/SVG
+///
+/// # Example
+///
+/// ```
+/// use metro::{Event, SvgOptions, TrackId};
+///
+/// let events = [
+///     Event::station(0, "Station 1"),
+///     Event::SplitTrack(TrackId(0), TrackId(1)),
+///     Event::station(1, "Station 2"),
+///     Event::JoinTrack(TrackId(1), TrackId(0)),
+/// ];
+///
+/// let svg = metro::to_svg(&events, &SvgOptions::default());
+///
+/// assert!(svg.starts_with("<svg"));
+/// ```
+pub fn to_svg(events: &[Event], opts: &SvgOptions) -> String {
+    let layout = layout(events);
+
+    // Coordinates of the layout are in half columns and half rows
+    let x = |x: usize| x as f32 * opts.column_spacing * 0.5;
+    let y = |y: usize| y as f32 * opts.row_height * 0.5;
+
+    let mut width = x(layout.width);
+    for text in &layout.texts {
+        // Approximate the width of a monospace font
+        let text_width = text_width(text.text) as f32 * opts.font_size * 3.0 / 5.0;
+        width = width.max(x(text.x) + text_width);
+    }
+
+    let height = y(layout.height);
+    let radius = opts.column_spacing.min(opts.row_height) * 0.25;
+
+    let mut svg = String::new();
+
+    // Writing to a `String` never fails, so it is safe to `unwrap`
+    writeln!(
+        svg,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{w}" height="{h}" viewBox="0 0 {w} {h}">"#,
+        w = width,
+        h = height,
+    )
+    .unwrap();
+
+    svg.push_str(
+        "  <g stroke=\"currentColor\" stroke-width=\"2\" stroke-linecap=\"round\" fill=\"none\">\n",
+    );
+    for segment in &layout.segments {
+        writeln!(
+            svg,
+            r#"    <line x1="{}" y1="{}" x2="{}" y2="{}"/>"#,
+            x(segment.from.0),
+            y(segment.from.1),
+            x(segment.to.0),
+            y(segment.to.1),
+        )
+        .unwrap();
+    }
+    svg.push_str("  </g>\n");
+
+    if !layout.links.is_empty() {
+        svg.push_str("  <g stroke=\"currentColor\" stroke-width=\"2\" stroke-dasharray=\"2 4\" fill=\"none\">\n");
+        for link in &layout.links {
+            writeln!(
+                svg,
+                r#"    <line x1="{}" y1="{}" x2="{}" y2="{}"/>"#,
+                x(link.from.0),
+                y(link.from.1),
+                x(link.to.0),
+                y(link.to.1),
+            )
+            .unwrap();
+        }
+        svg.push_str("  </g>\n");
+    }
+
+    svg.push_str("  <g fill=\"currentColor\">\n");
+    for station in &layout.stations {
+        writeln!(
+            svg,
+            r#"    <circle cx="{}" cy="{}" r="{}"/>"#,
+            x(station.x),
+            y(station.y),
+            radius
+        )
+        .unwrap();
+    }
+    svg.push_str("  </g>\n");
+
+    writeln!(
+        svg,
+        r#"  <g fill="currentColor" font-family="monospace" font-size="{}">"#,
+        opts.font_size
+    )
+    .unwrap();
+    for text in &layout.texts {
+        write!(
+            svg,
+            r#"    <text x="{}" y="{}" dominant-baseline="middle">"#,
+            x(text.x),
+            y(text.y)
+        )
+        .unwrap();
+        escape(&mut svg, text.text);
+        svg.push_str("</text>\n");
+    }
+    svg.push_str("  </g>\n");
+
+    svg.push_str("</svg>\n");
+
+    svg
+}
+
+fn escape(out: &mut String, text: &str) {
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            _ => out.push(c),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{to_svg, SvgOptions};
+    use crate::Event::{self, *};
+    use crate::TrackId;
+
+    fn lines(svg: &str) -> Vec<&str> {
+        svg.lines()
+            .map(str::trim)
+            .filter(|line| line.starts_with("<line"))
+            .collect()
+    }
+
+    #[test]
+    fn empty() {
+        let svg = to_svg(&[], &SvgOptions::default());
+
+        assert_eq!(
+            svg,
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="0" height="0" viewBox="0 0 0 0">
+  <g stroke="currentColor" stroke-width="2" stroke-linecap="round" fill="none">
+  </g>
+  <g fill="currentColor">
+  </g>
+  <g fill="currentColor" font-family="monospace" font-size="14">
+  </g>
+</svg>
+"#
+        );
+    }
+
+    #[test]
+    fn station() {
+        let events = [Event::station(0, "<Station & 1>")];
+        let svg = to_svg(&events, &SvgOptions::default());
+
+        assert_eq!(
+            svg,
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="129.2" height="20" viewBox="0 0 129.2 20">
+  <g stroke="currentColor" stroke-width="2" stroke-linecap="round" fill="none">
+    <line x1="10" y1="0" x2="10" y2="20"/>
+  </g>
+  <g fill="currentColor">
+    <circle cx="10" cy="10" r="5"/>
+  </g>
+  <g fill="currentColor" font-family="monospace" font-size="14">
+    <text x="20" y="10" dominant-baseline="middle">&lt;Station &amp; 1&gt;</text>
+  </g>
+</svg>
+"#
+        );
+    }
+
+    #[test]
+    fn split_track() {
+        #[rustfmt::skip]
+        let events = [
+            StartTrack(TrackId(1)),
+            SplitTrack(TrackId(0), TrackId(2)),
+        ];
+        let svg = to_svg(&events, &SvgOptions::default());
+
+        assert_eq!(
+            lines(&svg),
+            [
+                r#"<line x1="10" y1="0" x2="10" y2="20"/>"#,
+                r#"<line x1="30" y1="10" x2="30" y2="20"/>"#,
+                r#"<line x1="10" y1="20" x2="10" y2="40"/>"#,
+                r#"<line x1="30" y1="20" x2="50" y2="40"/>"#,
+                r#"<line x1="10" y1="20" x2="30" y2="40"/>"#,
+            ]
+        );
+    }
+
+    #[test]
+    fn split_track_at() {
+        #[rustfmt::skip]
+        let events = [
+            StartTracks(&[TrackId(1), TrackId(2)]),
+            SplitTrackAt(TrackId(0), TrackId(3), TrackId(1)),
+        ];
+        let svg = to_svg(&events, &SvgOptions::default());
+
+        assert_eq!(
+            lines(&svg)[3..],
+            [
+                r#"<line x1="10" y1="20" x2="10" y2="40"/>"#,
+                r#"<line x1="30" y1="20" x2="30" y2="40"/>"#,
+                r#"<line x1="50" y1="20" x2="70" y2="40"/>"#,
+                r#"<line x1="10" y1="20" x2="50" y2="40"/>"#,
+            ]
+        );
+    }
+
+    #[test]
+    fn stop_track() {
+        #[rustfmt::skip]
+        let events = [
+            StartTracks(&[TrackId(1), TrackId(2)]),
+            StopTrack(TrackId(1)),
+        ];
+        let svg = to_svg(&events, &SvgOptions::default());
+
+        assert_eq!(
+            lines(&svg)[3..],
+            [
+                r#"<line x1="10" y1="20" x2="10" y2="40"/>"#,
+                r#"<line x1="50" y1="20" x2="30" y2="40"/>"#,
+                r#"<line x1="30" y1="20" x2="30" y2="30"/>"#,
+            ]
+        );
+    }
+
+    #[test]
+    fn stop_tracks() {
+        #[rustfmt::skip]
+        let events = [
+            StartTracks(&[TrackId(1), TrackId(2), TrackId(3)]),
+            StopTracks(&[TrackId(1), TrackId(3), TrackId(4)]),
+        ];
+        let svg = to_svg(&events, &SvgOptions::default());
+
+        assert_eq!(
+            lines(&svg)[4..],
+            [
+                r#"<line x1="10" y1="20" x2="10" y2="40"/>"#,
+                r#"<line x1="50" y1="20" x2="30" y2="40"/>"#,
+                r#"<line x1="30" y1="20" x2="30" y2="30"/>"#,
+                r#"<line x1="70" y1="20" x2="70" y2="30"/>"#,
+            ]
+        );
+    }
+
+    #[test]
+    fn join_track_many_between() {
+        #[rustfmt::skip]
+        let events = [
+            StartTracks(&[TrackId(0), TrackId(1), TrackId(2), TrackId(3), TrackId(4)]),
+            JoinTrack(TrackId(4), TrackId(0)),
+            NoEvent,
+        ];
+        let opts = SvgOptions {
+            column_spacing: 10.0,
+            row_height: 30.0,
+            ..SvgOptions::default()
+        };
+        let svg = to_svg(&events, &opts);
+
+        assert_eq!(
+            svg,
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="50" height="90" viewBox="0 0 50 90">
+  <g stroke="currentColor" stroke-width="2" stroke-linecap="round" fill="none">
+    <line x1="5" y1="0" x2="5" y2="30"/>
+    <line x1="15" y1="15" x2="15" y2="30"/>
+    <line x1="25" y1="15" x2="25" y2="30"/>
+    <line x1="35" y1="15" x2="35" y2="30"/>
+    <line x1="45" y1="15" x2="45" y2="30"/>
+    <line x1="5" y1="30" x2="5" y2="60"/>
+    <line x1="15" y1="30" x2="15" y2="60"/>
+    <line x1="25" y1="30" x2="25" y2="60"/>
+    <line x1="35" y1="30" x2="35" y2="60"/>
+    <line x1="45" y1="30" x2="5" y2="60"/>
+    <line x1="5" y1="60" x2="5" y2="90"/>
+    <line x1="15" y1="60" x2="15" y2="90"/>
+    <line x1="25" y1="60" x2="25" y2="90"/>
+    <line x1="35" y1="60" x2="35" y2="90"/>
+  </g>
+  <g fill="currentColor">
+  </g>
+  <g fill="currentColor" font-family="monospace" font-size="14">
+  </g>
+</svg>
+"#
+        );
+    }
+
+    #[test]
+    fn join_tracks() {
+        #[rustfmt::skip]
+        let events = [
+            StartTracks(&[TrackId(0), TrackId(1), TrackId(2), TrackId(3)]),
+            JoinTracks(&[TrackId(0), TrackId(3)], TrackId(1)),
+        ];
+        let svg = to_svg(&events, &SvgOptions::default());
+
+        assert_eq!(
+            lines(&svg)[4..],
+            [
+                r#"<line x1="30" y1="20" x2="10" y2="40"/>"#,
+                r#"<line x1="50" y1="20" x2="30" y2="40"/>"#,
+                r#"<line x1="10" y1="20" x2="10" y2="40"/>"#,
+                r#"<line x1="70" y1="20" x2="10" y2="40"/>"#,
+            ]
+        );
+    }
+
+    #[test]
+    fn join_track_non_existing_to_track() {
+        let events1 = [
+            StartTracks(&[TrackId(0), TrackId(1), TrackId(2)]),
+            JoinTrack(TrackId(1), TrackId(5)),
+        ];
+        let events2 = [
+            StartTracks(&[TrackId(0), TrackId(1), TrackId(2)]),
+            StopTrack(TrackId(1)),
+        ];
+
+        let opts = SvgOptions::default();
+
+        assert_eq!(to_svg(&events1, &opts), to_svg(&events2, &opts));
+    }
+
+    #[test]
+    fn link() {
+        let events = [
+            StartTracks(&[TrackId(0), TrackId(1), TrackId(2)]),
+            Link(TrackId(2), TrackId(0)),
+        ];
+        let svg = to_svg(&events, &SvgOptions::default());
+
+        assert!(svg.contains(
+            r#"<g stroke="currentColor" stroke-width="2" stroke-dasharray="2 4" fill="none">
+    <line x1="50" y1="30" x2="10" y2="30"/>
+  </g>"#
+        ));
+    }
+}
@@ -0,0 +1,816 @@
+//! Thread-safe variants of [`Metro`] and [`Track`].
+//!
+//! [`SyncMetro`] and [`SyncTrack`] mirror the API of [`Metro`] and [`Track`],
+//! but use an [`Arc`]`<`[`Mutex`]`<MetroState>>` internally. This allows
+//! [`SyncTrack`]s to be sent to other threads, e.g. to construct each
+//! track of the graph from a different thread.
+//!
+//! [`Metro`]: ../struct.Metro.html
+//! [`Track`]: ../struct.Track.html
+//! [`SyncMetro`]: struct.SyncMetro.html
+//! [`SyncTrack`]: struct.SyncTrack.html
+//!
+//! [`Arc`]: https://doc.rust-lang.org/stable/std/sync/struct.Arc.html
+//! [`Mutex`]: https://doc.rust-lang.org/stable/std/sync/struct.Mutex.html
+//!
+//! # Event Ordering
+//!
+//! Every method producing an [`Event`] locks the state once, and pushes
+//! its [`Event`] while holding the lock. So the resulting [`Event`]s are
+//! in the order the lock was acquired in.
+//!
+//! - Calls made from the same thread, are always in program order.
+//! - Calls made concurrently from different threads, e.g. `add_station`
+//!   on two different tracks, are ordered arbitrarily relative to each
+//!   other. Each call is still atomic, so the [`Event`]s of different
+//!   calls are never interleaved.
+//!
+//! If a specific order is needed across threads, then the threads must
+//! synchronize themselves, e.g. by joining a thread before continuing.
+//!
+//! [`Event`]: ../enum.Event.html
+//!
+//! # Example
+//!
+//! ```
+//! use std::thread;
+//!
+//! use metro::sync::SyncMetro;
+//!
+//! let mut metro = SyncMetro::new();
+//!
+//! let mut track1 = metro.new_track();
+//! track1.add_station("Station 1");
+//!
+//! let track2 = track1.split();
+//!
+//! let handle = thread::spawn(move || {
+//!     let mut track2 = track2;
+//!     track2.add_station("Station 2");
+//!     track2
+//! });
+//!
+//! let track2 = handle.join().unwrap();
+//! track2.join(&track1);
+//!
+//! track1.add_station("Station 3");
+//!
+//! assert_eq!(
+//!     metro.to_string().unwrap(),
+//!     "* Station 1\n|\\\n| * Station 2\n|/\n* Station 3\n"
+//! );
+//! ```
+
+use std::borrow::Cow;
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::mem;
+use std::path::Path;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::vec;
+
+use crate::events::{
+    to_fmt_writer, to_string, to_string_with, to_vec, to_vec_with, to_writer, to_writer_with, Event,
+};
+use crate::metro::{Checkpoint, MetroState, TrackLimitError};
+use crate::options::Options;
+use crate::spans::{track_spans, TrackSpan};
+use crate::track_id::TrackId;
+
+type ArcMetro<'a> = Arc<Mutex<MetroState<'a>>>;
+
+/// Locks the state, recovering it if another thread panicked while holding the lock.
+///
+/// The state is always left consistent between events, so it is safe to
+/// continue using it after a panic.
+#[inline]
+fn lock<'s, 'a>(state: &'s ArcMetro<'a>) -> MutexGuard<'s, MetroState<'a>> {
+    state.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// The thread-safe variant of [`Metro`].
+///
+/// *[See `Metro` for more information.][`Metro`]*
+///
+/// *[See the module for a complete example.][sync]*
+///
+/// [`Metro`]: ../struct.Metro.html
+/// [sync]: index.html
+#[allow(missing_debug_implementations)]
+pub struct SyncMetro<'a> {
+    state: ArcMetro<'a>,
+}
+
+impl<'a> SyncMetro<'a> {
+    /// Create a new `SyncMetro`.
+    #[allow(clippy::new_without_default)]
+    #[inline]
+    pub fn new() -> Self {
+        Self {
+            state: Arc::new(Mutex::new(MetroState::new())),
+        }
+    }
+
+    /// *[See `Metro::from_events`.][`Metro::from_events`]*
+    ///
+    /// [`Metro::from_events`]: ../struct.Metro.html#method.from_events
+    #[inline]
+    pub fn from_events(events: Vec<Event<'a>>) -> Self {
+        Self {
+            state: Arc::new(Mutex::new(MetroState::from_events(events))),
+        }
+    }
+
+    /// *[See `Metro::extend_events`.][`Metro::extend_events`]*
+    ///
+    /// [`Metro::extend_events`]: ../struct.Metro.html#method.extend_events
+    pub fn extend_events<I>(&mut self, events: I)
+    where
+        I: IntoIterator<Item = Event<'a>>,
+    {
+        lock(&self.state).extend_events(events);
+    }
+
+    /// *[See `Metro::new_track`.][`Metro::new_track`]*
+    ///
+    /// [`Metro::new_track`]: ../struct.Metro.html#method.new_track
+    #[inline]
+    pub fn new_track(&mut self) -> SyncTrack<'a> {
+        self.try_new_track().unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    /// *[See `Metro::try_new_track`.][`Metro::try_new_track`]*
+    ///
+    /// [`Metro::try_new_track`]: ../struct.Metro.html#method.try_new_track
+    #[inline]
+    pub fn try_new_track(&mut self) -> Result<SyncTrack<'a>, TrackLimitError> {
+        let mut state = lock(&self.state);
+
+        // The id is reserved and started under the same lock, such that
+        // no other thread can start a track with the id in between
+        let id = state.try_next_id()?;
+        state.start_track(id);
+
+        drop(state);
+
+        Ok(SyncTrack::new(Arc::clone(&self.state), id))
+    }
+
+    /// *[See `Metro::new_track_with_id`.][`Metro::new_track_with_id`]*
+    ///
+    /// [`Metro::new_track_with_id`]: ../struct.Metro.html#method.new_track_with_id
+    #[inline]
+    pub fn new_track_with_id<T: Into<TrackId>>(&mut self, track_id: T) -> SyncTrack<'a> {
+        let track_id = track_id.into();
+        lock(&self.state).start_track(track_id);
+        SyncTrack::new(Arc::clone(&self.state), track_id)
+    }
+
+    /// *[See `Metro::get_or_create_track`.][`Metro::get_or_create_track`]*
+    ///
+    /// [`Metro::get_or_create_track`]: ../struct.Metro.html#method.get_or_create_track
+    #[inline]
+    pub fn get_or_create_track<T: Into<TrackId>>(&mut self, track_id: T) -> (SyncTrack<'a>, bool) {
+        let track_id = track_id.into();
+        let created = lock(&self.state).start_track(track_id);
+        (SyncTrack::new(Arc::clone(&self.state), track_id), created)
+    }
+
+    /// *[See `Metro::track_ids`.][`Metro::track_ids`]*
+    ///
+    /// [`Metro::track_ids`]: ../struct.Metro.html#method.track_ids
+    #[inline]
+    pub fn track_ids(&self) -> Vec<TrackId> {
+        lock(&self.state).track_ids()
+    }
+
+    /// *[See `Metro::event_count`.][`Metro::event_count`]*
+    ///
+    /// [`Metro::event_count`]: ../struct.Metro.html#method.event_count
+    #[inline]
+    pub fn event_count(&self) -> usize {
+        lock(&self.state).event_count()
+    }
+
+    /// *[See `Metro::active_track_count`.][`Metro::active_track_count`]*
+    ///
+    /// [`Metro::active_track_count`]: ../struct.Metro.html#method.active_track_count
+    #[inline]
+    pub fn active_track_count(&self) -> usize {
+        lock(&self.state).active_track_count()
+    }
+
+    /// *[See `Metro::checkpoint`.][`Metro::checkpoint`]*
+    ///
+    /// [`Metro::checkpoint`]: ../struct.Metro.html#method.checkpoint
+    #[inline]
+    pub fn checkpoint(&self) -> Checkpoint {
+        lock(&self.state).checkpoint()
+    }
+
+    /// *[See `Metro::rewind`.][`Metro::rewind`]*
+    ///
+    /// [`Metro::rewind`]: ../struct.Metro.html#method.rewind
+    #[inline]
+    pub fn rewind(&mut self, checkpoint: Checkpoint) {
+        lock(&self.state).rewind(checkpoint);
+    }
+
+    /// *[See `Metro::clear`.][`Metro::clear`]*
+    ///
+    /// [`Metro::clear`]: ../struct.Metro.html#method.clear
+    #[inline]
+    pub fn clear(&mut self) {
+        lock(&self.state).clear();
+    }
+
+    /// *[See `Metro::get_track`.][`Metro::get_track`]*
+    ///
+    /// [`Metro::get_track`]: ../struct.Metro.html#method.get_track
+    #[inline]
+    pub fn get_track<T: Into<TrackId>>(&mut self, track_id: T) -> Option<SyncTrack<'a>> {
+        let track_id = track_id.into();
+        if lock(&self.state).has_track(track_id) {
+            Some(SyncTrack::new(Arc::clone(&self.state), track_id))
+        } else {
+            None
+        }
+    }
+
+    /// *[See `Metro::join_tracks`.][`Metro::join_tracks`]*
+    ///
+    /// [`Metro::join_tracks`]: ../struct.Metro.html#method.join_tracks
+    #[inline]
+    pub fn join_tracks<F, T>(&mut self, from_track_id: F, to_track_id: T)
+    where
+        F: Into<TrackId>,
+        T: Into<TrackId>,
+    {
+        lock(&self.state).join_track(from_track_id.into(), to_track_id.into());
+    }
+
+    /// *[See `Metro::add_station`.][`Metro::add_station`]*
+    ///
+    /// [`Metro::add_station`]: ../struct.Metro.html#method.add_station
+    #[inline]
+    pub fn add_station<S: Into<Cow<'a, str>>>(&mut self, text: S) {
+        lock(&self.state).add_event(Event::detached_station(text));
+    }
+
+    /// *[See `Metro::add_annotation`.][`Metro::add_annotation`]*
+    ///
+    /// [`Metro::add_annotation`]: ../struct.Metro.html#method.add_annotation
+    #[inline]
+    pub fn add_annotation<S: Into<Cow<'a, str>>>(&mut self, text: S) {
+        lock(&self.state).add_event(Event::annotation(text));
+    }
+
+    /// *[See `Metro::add_no_event`.][`Metro::add_no_event`]*
+    ///
+    /// [`Metro::add_no_event`]: ../struct.Metro.html#method.add_no_event
+    #[inline]
+    pub fn add_no_event(&mut self) {
+        lock(&self.state).add_event(Event::NoEvent);
+    }
+
+    /// *[See `Metro::add_legend`.][`Metro::add_legend`]*
+    ///
+    /// [`Metro::add_legend`]: ../struct.Metro.html#method.add_legend
+    #[inline]
+    pub fn add_legend(&mut self) {
+        lock(&self.state).add_event(Event::Legend);
+    }
+
+    /// *[See `to_writer`.][`to_writer`]*
+    ///
+    /// [`to_writer`]: ../fn.to_writer.html
+    #[inline]
+    pub fn to_writer<W: Write>(&self, writer: W) -> io::Result<()> {
+        let state = lock(&self.state);
+        to_writer(writer, &state.events)
+    }
+
+    /// *[See `to_writer_with`.][`to_writer_with`]*
+    ///
+    /// [`to_writer_with`]: ../fn.to_writer_with.html
+    #[inline]
+    pub fn to_writer_with<W: Write>(&self, writer: W, opts: &Options) -> io::Result<()> {
+        let state = lock(&self.state);
+        to_writer_with(writer, &state.events, opts)
+    }
+
+    /// *[See `Metro::render_to_file`.][`Metro::render_to_file`]*
+    ///
+    /// [`Metro::render_to_file`]: ../struct.Metro.html#method.render_to_file
+    pub fn render_to_file<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        self.to_writer(&mut writer)?;
+
+        // Flush explicitly, as errors are ignored when dropping a `BufWriter`
+        writer.flush()
+    }
+
+    /// *[See `to_vec`.][`to_vec`]*
+    ///
+    /// [`to_vec`]: ../fn.to_vec.html
+    #[inline]
+    pub fn to_vec(&self) -> io::Result<Vec<u8>> {
+        let state = lock(&self.state);
+        to_vec(&state.events)
+    }
+
+    /// *[See `to_vec_with`.][`to_vec_with`]*
+    ///
+    /// [`to_vec_with`]: ../fn.to_vec_with.html
+    #[inline]
+    pub fn to_vec_with(&self, opts: &Options) -> io::Result<Vec<u8>> {
+        let state = lock(&self.state);
+        to_vec_with(&state.events, opts)
+    }
+
+    /// *[See `to_string`.][`to_string`]*
+    ///
+    /// [`to_string`]: ../fn.to_string.html
+    #[inline]
+    pub fn to_string(&self) -> io::Result<String> {
+        let state = lock(&self.state);
+        to_string(&state.events)
+    }
+
+    /// *[See `to_string_with`.][`to_string_with`]*
+    ///
+    /// [`to_string_with`]: ../fn.to_string_with.html
+    #[inline]
+    pub fn to_string_with(&self, opts: &Options) -> io::Result<String> {
+        let state = lock(&self.state);
+        to_string_with(&state.events, opts)
+    }
+
+    /// *[See `Metro::to_string_pretty`.][`Metro::to_string_pretty`]*
+    ///
+    /// [`Metro::to_string_pretty`]: ../struct.Metro.html#method.to_string_pretty
+    #[inline]
+    pub fn to_string_pretty(&self) -> io::Result<String> {
+        let opts = Options {
+            align_text: true,
+            ..Options::default()
+        };
+        self.to_string_with(&opts)
+    }
+
+    /// *[See `Metro::to_events`.][`Metro::to_events`]*
+    ///
+    /// [`Metro::to_events`]: ../struct.Metro.html#method.to_events
+    #[inline]
+    pub fn to_events(&self) -> Vec<Event<'a>> {
+        let state = lock(&self.state);
+        state.events.clone()
+    }
+
+    /// *[See `Metro::to_spans`.][`Metro::to_spans`]*
+    ///
+    /// [`Metro::to_spans`]: ../struct.Metro.html#method.to_spans
+    #[inline]
+    pub fn to_spans(&self) -> Vec<TrackSpan<'a>> {
+        track_spans(&lock(&self.state).events)
+    }
+
+    /// *[See `Metro::into_events`.][`Metro::into_events`]*
+    ///
+    /// [`Metro::into_events`]: ../struct.Metro.html#method.into_events
+    #[inline]
+    pub fn into_events(self) -> Vec<Event<'a>> {
+        let mut state = lock(&self.state);
+        mem::take(&mut state.events)
+    }
+}
+
+impl<'a> Extend<Event<'a>> for SyncMetro<'a> {
+    /// *[See `Metro::extend_events`.][`Metro::extend_events`]*
+    ///
+    /// [`Metro::extend_events`]: ../struct.Metro.html#method.extend_events
+    #[inline]
+    fn extend<I: IntoIterator<Item = Event<'a>>>(&mut self, events: I) {
+        self.extend_events(events);
+    }
+}
+
+impl<'a> IntoIterator for SyncMetro<'a> {
+    type Item = Event<'a>;
+    type IntoIter = vec::IntoIter<Event<'a>>;
+
+    /// *[See `Metro`'s `IntoIterator` implementation.][`Metro`]*
+    ///
+    /// [`Metro`]: ../struct.Metro.html#impl-IntoIterator
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.into_events().into_iter()
+    }
+}
+
+impl<'a> IntoIterator for &SyncMetro<'a> {
+    type Item = Event<'a>;
+    type IntoIter = vec::IntoIter<Event<'a>>;
+
+    /// *[See `Metro`'s `IntoIterator` implementation.][`Metro`]*
+    ///
+    /// [`Metro`]: ../struct.Metro.html#impl-IntoIterator
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.to_events().into_iter()
+    }
+}
+
+impl fmt::Display for SyncMetro<'_> {
+    /// *[See `Metro`'s `Display` implementation.][`Metro`]*
+    ///
+    /// [`Metro`]: ../struct.Metro.html#impl-Display
+    #[inline]
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        let state = lock(&self.state);
+        to_fmt_writer(fmt, &state.events)
+    }
+}
+
+/// The thread-safe variant of [`Track`].
+///
+/// *[See `Track` for more information.][`Track`]*
+///
+/// [`Track`]: ../struct.Track.html
+pub struct SyncTrack<'a> {
+    state: ArcMetro<'a>,
+    id: TrackId,
+}
+
+impl<'a> SyncTrack<'a> {
+    #[inline]
+    fn new(state: ArcMetro<'a>, id: TrackId) -> Self {
+        Self { state, id }
+    }
+
+    /// Returns the track id.
+    #[inline]
+    pub fn id(&self) -> TrackId {
+        self.id
+    }
+
+    /// *[See `Track::stop`.][`Track::stop`]*
+    ///
+    /// [`Track::stop`]: ../struct.Track.html#method.stop
+    #[inline]
+    pub fn stop(self) {
+        // Method is empty as the logic is implemented in Drop for SyncTrack
+    }
+
+    /// *[See `Track::add_station`.][`Track::add_station`]*
+    ///
+    /// [`Track::add_station`]: ../struct.Track.html#method.add_station
+    #[inline]
+    pub fn add_station<S: Into<Cow<'a, str>>>(&mut self, text: S) {
+        lock(&self.state).add_event(Event::station(self.id, text));
+    }
+
+    /// *[See `Track::add_stations`.][`Track::add_stations`]*
+    ///
+    /// [`Track::add_stations`]: ../struct.Track.html#method.add_stations
+    pub fn add_stations(&mut self, texts: &[&'a str]) {
+        let mut state = lock(&self.state);
+
+        for &text in texts {
+            state.add_event(Event::station(self.id, text));
+        }
+    }
+
+    /// *[See `Track::add_station_marked`.][`Track::add_station_marked`]*
+    ///
+    /// [`Track::add_station_marked`]: ../struct.Track.html#method.add_station_marked
+    #[inline]
+    pub fn add_station_marked<S: Into<Cow<'a, str>>>(&mut self, text: S, marker: char) {
+        lock(&self.state).add_event(Event::station_marked(self.id, text, marker));
+    }
+
+    /// *[See `Track::set_label`.][`Track::set_label`]*
+    ///
+    /// [`Track::set_label`]: ../struct.Track.html#method.set_label
+    #[inline]
+    pub fn set_label<S: Into<Cow<'a, str>>>(&mut self, label: S) {
+        lock(&self.state).add_event(Event::label_track(self.id, label));
+    }
+
+    /// *[See `Track::split`.][`Track::split`]*
+    ///
+    /// [`Track::split`]: ../struct.Track.html#method.split
+    #[inline]
+    pub fn split(&self) -> SyncTrack<'a> {
+        self.try_split().unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    /// *[See `Track::try_split`.][`Track::try_split`]*
+    ///
+    /// [`Track::try_split`]: ../struct.Track.html#method.try_split
+    #[inline]
+    pub fn try_split(&self) -> Result<SyncTrack<'a>, TrackLimitError> {
+        let mut state = lock(&self.state);
+
+        // The id is reserved and split under the same lock, such that
+        // no other thread can start a track with the id in between
+        let id = state.try_next_id()?;
+        state.split_track(self.id, id);
+
+        drop(state);
+
+        Ok(SyncTrack::new(Arc::clone(&self.state), id))
+    }
+
+    /// *[See `Track::split_n`.][`Track::split_n`]*
+    ///
+    /// [`Track::split_n`]: ../struct.Track.html#method.split_n
+    pub fn split_n(&self, n: usize) -> Vec<SyncTrack<'a>> {
+        let new_track_ids = lock(&self.state).split_tracks(self.id, n);
+
+        new_track_ids
+            .into_iter()
+            .map(|new_track_id| SyncTrack::new(Arc::clone(&self.state), new_track_id))
+            .collect()
+    }
+
+    /// *[See `Track::split_station`.][`Track::split_station`]*
+    ///
+    /// [`Track::split_station`]: ../struct.Track.html#method.split_station
+    pub fn split_station<S: Into<Cow<'a, str>>>(&self, text: S) -> SyncTrack<'a> {
+        let new_track_id = lock(&self.state).split_station(self.id, text.into());
+        SyncTrack::new(Arc::clone(&self.state), new_track_id)
+    }
+
+    /// *[See `Track::split_with_id`.][`Track::split_with_id`]*
+    ///
+    /// [`Track::split_with_id`]: ../struct.Track.html#method.split_with_id
+    #[inline]
+    pub fn split_with_id<T: Into<TrackId>>(&self, new_track_id: T) -> SyncTrack<'a> {
+        let new_track_id = new_track_id.into();
+        lock(&self.state).split_track(self.id, new_track_id);
+        SyncTrack::new(Arc::clone(&self.state), new_track_id)
+    }
+
+    /// *[See `Track::split_at`.][`Track::split_at`]*
+    ///
+    /// [`Track::split_at`]: ../struct.Track.html#method.split_at
+    #[inline]
+    pub fn split_at(&self, neighbor: &SyncTrack) -> SyncTrack<'a> {
+        let mut state = lock(&self.state);
+
+        let new_track_id = state.next_id();
+        state.split_track_at(self.id, new_track_id, neighbor.id);
+
+        drop(state);
+
+        SyncTrack::new(Arc::clone(&self.state), new_track_id)
+    }
+
+    /// *[See `Track::join`.][`Track::join`]*
+    ///
+    /// [`Track::join`]: ../struct.Track.html#method.join
+    #[inline]
+    pub fn join(self, to_track: &SyncTrack) {
+        lock(&self.state).join_track(self.id, to_track.id);
+    }
+
+    /// *[See `Track::link`.][`Track::link`]*
+    ///
+    /// [`Track::link`]: ../struct.Track.html#method.link
+    #[inline]
+    pub fn link(&self, to_track: &SyncTrack) {
+        lock(&self.state).add_event(Event::Link(self.id, to_track.id));
+    }
+
+    /// *[See `Track::rename_id`.][`Track::rename_id`]*
+    ///
+    /// [`Track::rename_id`]: ../struct.Track.html#method.rename_id
+    #[inline]
+    pub fn rename_id<T: Into<TrackId>>(&mut self, new_id: T) -> bool {
+        let new_id = new_id.into();
+        if new_id == self.id {
+            return !self.is_dangling();
+        }
+
+        let mut state = lock(&self.state);
+
+        if state.rename_track(self.id, new_id) {
+            state.add_event(Event::RenameTrack(self.id, new_id));
+
+            drop(state);
+
+            self.id = new_id;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// *[See `Track::is_dangling`.][`Track::is_dangling`]*
+    ///
+    /// [`Track::is_dangling`]: ../struct.Track.html#method.is_dangling
+    #[inline]
+    pub fn is_dangling(&self) -> bool {
+        !lock(&self.state).has_track(self.id)
+    }
+
+    /// *[See `Track::column`.][`Track::column`]*
+    ///
+    /// [`Track::column`]: ../struct.Track.html#method.column
+    #[inline]
+    pub fn column(&self) -> Option<usize> {
+        lock(&self.state).column(self.id)
+    }
+
+    /// *[See `Track::parent_id`.][`Track::parent_id`]*
+    ///
+    /// [`Track::parent_id`]: ../struct.Track.html#method.parent_id
+    #[inline]
+    pub fn parent_id(&self) -> Option<TrackId> {
+        lock(&self.state).parent(self.id)
+    }
+}
+
+impl<'a> Drop for SyncTrack<'a> {
+    /// Drop implicitly calls [`SyncTrack::stop`].
+    ///
+    /// [`SyncTrack::stop`]: struct.SyncTrack.html#method.stop
+    #[inline]
+    fn drop(&mut self) {
+        lock(&self.state).stop_track(self.id);
+    }
+}
+
+impl fmt::Debug for SyncTrack<'_> {
+    #[inline]
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt.debug_struct("SyncTrack").field("id", &self.id).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+
+    use super::SyncMetro;
+    use crate::{Event, Metro};
+
+    #[test]
+    fn lib_example() {
+        let mut metro = Metro::new();
+
+        let mut track1 = metro.new_track();
+        track1.add_station("Station 1");
+        track1.add_station("Station 2");
+        track1.add_station("Station 3");
+
+        let mut track2 = track1.split();
+        track2.add_station("Station 4");
+
+        let mut track3 = track2.split();
+        track2.add_station("Station 5");
+        track3.add_station("Station 6");
+
+        track1.add_station("Station 7");
+        track2.add_station("Station 8");
+        track3.add_station("Station 9");
+
+        let mut track4 = track3.split();
+        let track5 = track4.split();
+
+        metro.add_station("Station 10 (Detached)");
+
+        track5.join(&track1);
+
+        track4.add_station("Station 11");
+
+        track2.stop();
+
+        track1.add_station("Station 12");
+        track3.add_station("Station 13");
+        track4.add_station("Station 14");
+
+        track4.join(&track1);
+
+        track3.add_station("Station 15");
+
+        track3.stop();
+
+        track1.add_station("Station 16");
+
+        let string1 = metro.to_string().unwrap();
+
+        let mut metro = SyncMetro::new();
+
+        let mut track1 = metro.new_track();
+        track1.add_station("Station 1");
+        track1.add_station("Station 2");
+        track1.add_station("Station 3");
+
+        let mut track2 = track1.split();
+        track2.add_station("Station 4");
+
+        let mut track3 = track2.split();
+        track2.add_station("Station 5");
+        track3.add_station("Station 6");
+
+        track1.add_station("Station 7");
+        track2.add_station("Station 8");
+        track3.add_station("Station 9");
+
+        let mut track4 = track3.split();
+        let track5 = track4.split();
+
+        metro.add_station("Station 10 (Detached)");
+
+        track5.join(&track1);
+
+        track4.add_station("Station 11");
+
+        track2.stop();
+
+        track1.add_station("Station 12");
+        track3.add_station("Station 13");
+        track4.add_station("Station 14");
+
+        track4.join(&track1);
+
+        track3.add_station("Station 15");
+
+        track3.stop();
+
+        track1.add_station("Station 16");
+
+        let string2 = metro.to_string().unwrap();
+
+        assert_eq!(string1, string2);
+    }
+
+    #[test]
+    fn threads() {
+        let mut metro = SyncMetro::new();
+
+        let track1 = metro.new_track();
+        let tracks = (0..4).map(|_| track1.split()).collect::<Vec<_>>();
+
+        let handles = tracks
+            .into_iter()
+            .map(|mut track| {
+                thread::spawn(move || {
+                    for i in 0..10 {
+                        track.add_station(format!("Track {} Station {}", track.id(), i));
+                    }
+                    track
+                })
+            })
+            .collect::<Vec<_>>();
+
+        for handle in handles {
+            handle.join().unwrap().join(&track1);
+        }
+
+        let events = metro.into_events();
+
+        assert_eq!(events.len(), 1 + 4 + 4 * 10 + 4);
+
+        // Stations of each track are in program order
+        for track_id in 1..=4 {
+            let stations = events
+                .iter()
+                .filter_map(|event| match event {
+                    Event::Station(id, text) if *id == track_id => Some(text.as_ref()),
+                    _ => None,
+                })
+                .collect::<Vec<_>>();
+
+            let expected = (0..10)
+                .map(|i| format!("Track {} Station {}", track_id, i))
+                .collect::<Vec<_>>();
+
+            assert_eq!(stations, expected);
+        }
+    }
+
+    #[test]
+    fn dangling() {
+        let mut metro = SyncMetro::new();
+
+        let track1 = metro.new_track();
+        let track2 = metro.get_track(track1.id()).unwrap();
+
+        assert!(!track1.is_dangling());
+        assert!(!track2.is_dangling());
+
+        track1.stop();
+
+        assert!(track2.is_dangling());
+        assert!(metro.get_track(track2.id()).is_none());
+
+        let track3 = metro.new_track_with_id(track2.id());
+
+        assert!(!track2.is_dangling());
+        assert!(!track3.is_dangling());
+    }
+}
@@ -0,0 +1,118 @@
+//! Helpers for testing the rendered output of events.
+//!
+//! *Requires the `testing` feature.*
+
+use std::fmt::Write;
+
+use crate::{to_string_with, Event, Options};
+
+/// Asserts that `events` render into `expected`, using the
+/// default [`Options`].
+///
+/// *See also [`assert_renders_with`].*
+///
+/// [`Options`]: ../struct.Options.html
+/// [`assert_renders_with`]: fn.assert_renders_with.html
+///
+/// # Panics
+///
+/// Panics if the rendered output differs from `expected`. The message
+/// lists every line, prefixing the expected lines that are missing with
+/// `-` and the rendered lines that are not expected with `+`.
+///
+/// # Example
+///
+/// ```
+/// use metro::test_util::assert_renders;
+/// use metro::{Event, TrackId};
+///
+/// let events = [
+///     Event::station(0, "Station 1"),
+///     Event::SplitTrack(TrackId(0), TrackId(1)),
+///     Event::station(1, "Station 2"),
+/// ];
+///
+/// assert_renders(
+///     &events,
+///     r"* Station 1
+/// |\
+/// | * Station 2
+/// ",
+/// );
+/// ```
+#[track_caller]
+pub fn assert_renders(events: &[Event], expected: &str) {
+    assert_renders_with(events, &Options::default(), expected)
+}
+
+/// Asserts that `events` render into `expected`, using `opts`.
+///
+/// *See also [`assert_renders`].*
+///
+/// [`assert_renders`]: fn.assert_renders.html
+///
+/// # Panics
+///
+/// Panics if the rendered output differs from `expected`.
+#[track_caller]
+pub fn assert_renders_with(events: &[Event], opts: &Options, expected: &str) {
+    // Writing to a `String` never fails, so it is safe to `unwrap`
+    let actual = to_string_with(events, opts).unwrap();
+
+    if actual != expected {
+        panic!("rendered output differs\n{}", diff(expected, &actual));
+    }
+}
+
+/// Returns a line-by-line diff of `expected` and `actual`.
+fn diff(expected: &str, actual: &str) -> String {
+    let expected_lines = expected.split('\n').collect::<Vec<_>>();
+    let actual_lines = actual.split('\n').collect::<Vec<_>>();
+
+    let mut diff = String::new();
+
+    for i in 0..expected_lines.len().max(actual_lines.len()) {
+        // Writing to a `String` never fails, so it is safe to `unwrap`
+        match (expected_lines.get(i), actual_lines.get(i)) {
+            (Some(expected), Some(actual)) if expected == actual => {
+                writeln!(diff, "  {:?}", expected).unwrap()
+            }
+            (expected, actual) => {
+                if let Some(expected) = expected {
+                    writeln!(diff, "- {:?}", expected).unwrap();
+                }
+                if let Some(actual) = actual {
+                    writeln!(diff, "+ {:?}", actual).unwrap();
+                }
+            }
+        }
+    }
+
+    diff
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{assert_renders, diff};
+    use crate::Event;
+
+    #[test]
+    fn renders() {
+        assert_renders(&[Event::station(0, "Station 1")], "* Station 1\n");
+    }
+
+    #[test]
+    #[should_panic(expected = "rendered output differs")]
+    fn mismatch() {
+        assert_renders(&[Event::station(0, "Station 1")], "* Station 2\n");
+    }
+
+    #[test]
+    fn diff_lines() {
+        assert_eq!(
+            diff("a\nb\n", "a\nc\n"),
+            "  \"a\"\n- \"b\"\n+ \"c\"\n  \"\"\n"
+        );
+        assert_eq!(diff("a", "a\nb"), "  \"a\"\n+ \"b\"\n");
+    }
+}
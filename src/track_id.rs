@@ -0,0 +1,49 @@
+use std::fmt;
+
+/// The id of a track, used by [`Event`]s to refer to a track.
+///
+/// Track ids are distinct from column indices, e.g. the index of a rail
+/// from the left, which are plain `usize`s. A `TrackId` can be created
+/// from a `usize` using `From`, such that functions accepting
+/// `impl Into<TrackId>` can be called with integer literals.
+///
+/// [`Event`]: enum.Event.html
+///
+/// # Example
+///
+/// ```
+/// use metro::{Event, TrackId};
+///
+/// assert_eq!(TrackId::from(1), TrackId(1));
+/// assert_eq!(Event::station(1, "Station 1"), Event::station(TrackId(1), "Station 1"));
+/// ```
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default, Debug)]
+pub struct TrackId(pub usize);
+
+impl From<usize> for TrackId {
+    #[inline]
+    fn from(track_id: usize) -> Self {
+        Self(track_id)
+    }
+}
+
+impl From<TrackId> for usize {
+    #[inline]
+    fn from(track_id: TrackId) -> Self {
+        track_id.0
+    }
+}
+
+impl PartialEq<usize> for TrackId {
+    #[inline]
+    fn eq(&self, other: &usize) -> bool {
+        self.0 == *other
+    }
+}
+
+impl fmt::Display for TrackId {
+    #[inline]
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        self.0.fmt(fmt)
+    }
+}
@@ -0,0 +1,362 @@
+use std::error;
+use std::fmt;
+
+use crate::events::{render_event, Event, RenderState};
+use crate::track_id::TrackId;
+
+/// An issue found by [`validate`].
+///
+/// [`validate`]: fn.validate.html
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Validation {
+    /// The index of the [`Event`] causing the issue.
+    ///
+    /// [`Event`]: enum.Event.html
+    pub index: usize,
+
+    /// The kind of issue.
+    pub kind: ValidationKind,
+}
+
+/// The kind of issue found by [`validate`].
+///
+/// [`validate`]: fn.validate.html
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum ValidationKind {
+    /// `UnknownTrack(track_id)`
+    ///
+    /// The event refers to `track_id`, which does not exist,
+    /// i.e. it was never started, or it was already stopped.
+    UnknownTrack(TrackId),
+
+    /// `TrackExists(track_id)`
+    ///
+    /// The event starts `track_id`, which already exists.
+    TrackExists(TrackId),
+
+    /// `JoinSelf(track_id)`
+    ///
+    /// The event joins `track_id` with itself.
+    JoinSelf(TrackId),
+}
+
+impl fmt::Display for Validation {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        write!(fmt, "event {}: {}", self.index, self.kind)
+    }
+}
+
+impl fmt::Display for ValidationKind {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        use ValidationKind::*;
+        match self {
+            UnknownTrack(track_id) => write!(fmt, "track {} does not exist", track_id),
+            TrackExists(track_id) => write!(fmt, "track {} already exists", track_id),
+            JoinSelf(track_id) => write!(fmt, "track {} is joined with itself", track_id),
+        }
+    }
+}
+
+impl error::Error for Validation {}
+
+/// Validates `&[`[`Event`]`]`, and returns the issues found, in order.
+/// Defines a default track with `track_id` of `0`.
+///
+/// The events are walked with the same track bookkeeping as when
+/// rendering, but nothing is rendered. All issues are still handled
+/// when rendering, but likely produce output that was not intended.
+/// For instance a `JoinTrack` from a track that does not exist,
+/// renders nothing.
+///
+/// Stations on tracks that do not exist are reported, while an
+/// [`Event::DetachedStation`] is never tied to any track.
+///
+/// [`Event`]: enum.Event.html
+/// [`Event::DetachedStation`]: enum.Event.html#variant.DetachedStation
+///
+/// # Example
+///
+/// ```
+/// use metro::{Event::*, TrackId, Validation, ValidationKind};
+///
+/// let events = [
+///     SplitTrack(TrackId(0), TrackId(1)),
+///     JoinTrack(TrackId(5), TrackId(0)),
+///     StopTrack(TrackId(1)),
+/// ];
+///
+/// let issues = metro::validate(&events);
+///
+/// assert_eq!(
+///     issues,
+///     [Validation {
+///         index: 1,
+///         kind: ValidationKind::UnknownTrack(TrackId(5)),
+///     }]
+/// );
+/// assert_eq!(issues[0].to_string(), "event 1: track 5 does not exist");
+/// ```
+pub fn validate(events: &[Event]) -> Vec<Validation> {
+    let mut state = RenderState::new();
+    let mut rows = Vec::new();
+    let mut issues = Vec::new();
+
+    for (index, event) in events.iter().enumerate() {
+        let tracks = &state.tracks;
+        let mut kinds = Vec::new();
+
+        use Event::*;
+        match event {
+            &StartTrack(track_id) | &StartTrackAt(track_id, _) => {
+                kinds.extend(track_exists(tracks, track_id));
+            }
+
+            &StartTracks(track_ids) => {
+                for &track_id in track_ids {
+                    kinds.extend(track_exists(tracks, track_id));
+                }
+            }
+
+            &StopTrack(track_id) | &StopTrackLabeled(track_id, _) => {
+                kinds.extend(unknown_track(tracks, track_id))
+            }
+
+            &StopTracks(track_ids) => {
+                for &track_id in track_ids {
+                    kinds.extend(unknown_track(tracks, track_id));
+                }
+            }
+
+            &Station(track_id, _) | &StationMarked(track_id, _, _) => {
+                kinds.extend(unknown_track(tracks, track_id))
+            }
+
+            &StationMulti(track_ids, _) => {
+                for &track_id in track_ids {
+                    kinds.extend(unknown_track(tracks, track_id));
+                }
+            }
+
+            &SplitTrack(from_track_id, new_track_id)
+            | &SplitStation(from_track_id, new_track_id, _) => {
+                kinds.extend(unknown_track(tracks, from_track_id));
+                kinds.extend(track_exists(tracks, new_track_id));
+            }
+
+            SplitTrackN(from_track_id, new_track_ids) => {
+                kinds.extend(unknown_track(tracks, *from_track_id));
+
+                for (i, &new_track_id) in new_track_ids.iter().enumerate() {
+                    if new_track_ids[..i].contains(&new_track_id) {
+                        kinds.push(ValidationKind::TrackExists(new_track_id));
+                    } else {
+                        kinds.extend(track_exists(tracks, new_track_id));
+                    }
+                }
+            }
+
+            &SplitTrackAt(from_track_id, new_track_id, neighbor_track_id) => {
+                kinds.extend(unknown_track(tracks, from_track_id));
+                kinds.extend(unknown_track(tracks, neighbor_track_id));
+                kinds.extend(track_exists(tracks, new_track_id));
+            }
+
+            &JoinTrack(from_track_id, to_track_id)
+            | &JoinTrackToward(from_track_id, to_track_id)
+            | &JoinTrackKeepSource(to_track_id, from_track_id) => {
+                if from_track_id == to_track_id {
+                    kinds.push(ValidationKind::JoinSelf(from_track_id));
+                } else {
+                    kinds.extend(unknown_track(tracks, from_track_id));
+                    kinds.extend(unknown_track(tracks, to_track_id));
+                }
+            }
+
+            &JoinTrackOrRename(from_track_id, to_track_id) => {
+                // A `to_track_id` which does not exist is valid
+                if from_track_id == to_track_id {
+                    kinds.push(ValidationKind::JoinSelf(from_track_id));
+                } else {
+                    kinds.extend(unknown_track(tracks, from_track_id));
+                }
+            }
+
+            &JoinTracks(from_track_ids, to_track_id) => {
+                for &from_track_id in from_track_ids {
+                    if from_track_id == to_track_id {
+                        kinds.push(ValidationKind::JoinSelf(from_track_id));
+                    } else {
+                        kinds.extend(unknown_track(tracks, from_track_id));
+                    }
+                }
+
+                kinds.extend(unknown_track(tracks, to_track_id));
+            }
+
+            &Link(from_track_id, to_track_id) => {
+                kinds.extend(unknown_track(tracks, from_track_id));
+                kinds.extend(unknown_track(tracks, to_track_id));
+            }
+
+            &RenameTrack(old_track_id, new_track_id) => {
+                kinds.extend(unknown_track(tracks, old_track_id));
+                kinds.extend(track_exists(tracks, new_track_id));
+            }
+
+            &LabelTrack(track_id, _) => kinds.extend(unknown_track(tracks, track_id)),
+
+            DetachedStation(_) | Annotation(_) | Legend | NoEvent => {}
+        }
+
+        issues.extend(kinds.into_iter().map(|kind| Validation { index, kind }));
+
+        render_event(&mut rows, &mut state, event);
+        rows.clear();
+    }
+
+    issues
+}
+
+/// Returns the ids of the tracks that still exist after all
+/// `&[`[`Event`]`]`, i.e. tracks that were never stopped or joined,
+/// in the order of their rails from left to right.
+/// Defines a default track with `track_id` of `0`.
+///
+/// This is useful for asserting that every track eventually
+/// ends, e.g. that every started branch is merged.
+///
+/// [`Event`]: enum.Event.html
+///
+/// # Example
+///
+/// ```
+/// use metro::{Event, TrackId};
+///
+/// let events = [
+///     Event::SplitTrack(TrackId(0), TrackId(1)),
+///     Event::SplitTrack(TrackId(0), TrackId(2)),
+///     Event::JoinTrack(TrackId(2), TrackId(0)),
+/// ];
+///
+/// assert_eq!(metro::open_tracks(&events), [0, 1]);
+///
+/// let events = [Event::StopTrack(TrackId(0))];
+///
+/// assert!(metro::open_tracks(&events).is_empty());
+/// ```
+pub fn open_tracks(events: &[Event]) -> Vec<TrackId> {
+    let mut state = RenderState::new();
+    let mut rows = Vec::new();
+
+    for event in events {
+        render_event(&mut rows, &mut state, event);
+        rows.clear();
+    }
+
+    state.tracks
+}
+
+#[inline]
+fn unknown_track(tracks: &[TrackId], track_id: TrackId) -> Option<ValidationKind> {
+    if tracks.contains(&track_id) {
+        None
+    } else {
+        Some(ValidationKind::UnknownTrack(track_id))
+    }
+}
+
+#[inline]
+fn track_exists(tracks: &[TrackId], track_id: TrackId) -> Option<ValidationKind> {
+    if tracks.contains(&track_id) {
+        Some(ValidationKind::TrackExists(track_id))
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{open_tracks, validate, Validation, ValidationKind::*};
+    use crate::Event::{self, *};
+    use crate::TrackId;
+
+    #[test]
+    fn valid() {
+        let events = [
+            Event::station(0, "Station 1"),
+            SplitTrack(TrackId(0), TrackId(1)),
+            SplitTrackAt(TrackId(0), TrackId(2), TrackId(1)),
+            Event::detached_station("Station 2"),
+            JoinTracks(&[TrackId(1), TrackId(2)], TrackId(0)),
+            StartTracks(&[TrackId(1), TrackId(2)]),
+            StopTracks(&[TrackId(1), TrackId(2)]),
+        ];
+
+        assert_eq!(validate(&events), []);
+    }
+
+    #[test]
+    fn invalid() {
+        let events = [
+            StartTrack(TrackId(0)),
+            Event::station(1, "Station 1"),
+            SplitTrack(TrackId(0), TrackId(1)),
+            SplitTrack(TrackId(2), TrackId(1)),
+            JoinTrack(TrackId(1), TrackId(1)),
+            JoinTracks(&[TrackId(0), TrackId(1), TrackId(3)], TrackId(0)),
+            StopTrack(TrackId(1)),
+            Event::label_track(1, "Track 1"),
+        ];
+
+        let issues = validate(&events)
+            .into_iter()
+            .map(|Validation { index, kind }| (index, kind))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            issues,
+            [
+                (0, TrackExists(TrackId(0))),
+                (1, UnknownTrack(TrackId(1))),
+                (3, UnknownTrack(TrackId(2))),
+                (3, TrackExists(TrackId(1))),
+                (4, JoinSelf(TrackId(1))),
+                (5, JoinSelf(TrackId(0))),
+                (5, UnknownTrack(TrackId(1))),
+                (5, UnknownTrack(TrackId(3))),
+                (6, UnknownTrack(TrackId(1))),
+                (7, UnknownTrack(TrackId(1))),
+            ]
+        );
+    }
+
+    #[test]
+    fn detached_station_with_max_track_id() {
+        let events = [
+            SplitTrack(TrackId(0), TrackId(usize::MAX)),
+            Event::station(usize::MAX, "Station 1"),
+            Event::detached_station("Station 2"),
+            StopTrack(TrackId(usize::MAX)),
+        ];
+
+        assert_eq!(
+            crate::to_string(&events).unwrap(),
+            "|\\\n| * Station 1\n| | Station 2\n| \"\n"
+        );
+        assert_eq!(validate(&events), []);
+    }
+
+    #[test]
+    fn open() {
+        assert_eq!(open_tracks(&[]), [0]);
+
+        let events = [
+            StartTracks(&[TrackId(1), TrackId(2)]),
+            SplitTrackAt(TrackId(2), TrackId(3), TrackId(0)),
+            RenameTrack(TrackId(1), TrackId(4)),
+            JoinTracks(&[TrackId(0), TrackId(5)], TrackId(2)),
+        ];
+
+        assert_eq!(open_tracks(&events), [3, 4, 2]);
+    }
+}
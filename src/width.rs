@@ -0,0 +1,69 @@
+#[cfg(feature = "unicode-width")]
+use unicode_width::UnicodeWidthChar;
+
+/// Tabs advance to the next multiple of `TAB_WIDTH` columns.
+const TAB_WIDTH: usize = 8;
+
+/// Returns the number of columns `c` advances, when written at `column`.
+///
+/// Without the `unicode-width` feature, every `char` except tab
+/// is assumed to be a single column wide.
+#[inline]
+pub(crate) fn char_width(c: char, column: usize) -> usize {
+    if c == '\t' {
+        return TAB_WIDTH - (column % TAB_WIDTH);
+    }
+
+    #[cfg(feature = "unicode-width")]
+    {
+        c.width().unwrap_or(0)
+    }
+
+    #[cfg(not(feature = "unicode-width"))]
+    {
+        1
+    }
+}
+
+/// Returns the display width of the widest line of `text`.
+pub(crate) fn text_width(text: &str) -> usize {
+    text.lines()
+        .map(|line| {
+            line.chars()
+                .fold(0, |column, c| column + char_width(c, column))
+        })
+        .max()
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::text_width;
+
+    #[test]
+    fn tabs() {
+        assert_eq!(text_width("\t"), 8);
+        assert_eq!(text_width("abc\t"), 8);
+        assert_eq!(text_width("abc\tdef"), 11);
+        assert_eq!(text_width("12345678\t"), 16);
+    }
+
+    #[test]
+    fn lines() {
+        assert_eq!(text_width(""), 0);
+        assert_eq!(text_width("abc\nabcdef\nab"), 6);
+    }
+
+    #[cfg(feature = "unicode-width")]
+    #[test]
+    fn wide() {
+        assert_eq!(text_width("こんにちは"), 10);
+        assert_eq!(text_width("a\u{301}"), 1);
+    }
+
+    #[cfg(not(feature = "unicode-width"))]
+    #[test]
+    fn wide() {
+        assert_eq!(text_width("こんにちは"), 5);
+    }
+}